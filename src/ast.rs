@@ -4,16 +4,23 @@
 //! declaration -> varDecl
 //!              | funDecl
 //!              | statement ;
-//! varDecl     -> "var" IDENTIFIER ( "=" expression )? ";" ;
+//! varDecl     -> "var" IDENTIFIER ( "=" expression )? ";"
+//!              | "var" "(" IDENTIFIER ( "," IDENTIFIER )* ")" "=" expression ";" ;
 //! funDecl     -> "fun" function ;
 //! function    -> IDENTIFIER "(" parameters? ") block ;
 //! statement   -> exprStmt
 //!              | ifStmt
 //!              | printStmt
 //!              | returnStmt
+//!              | breakStmt
+//!              | continueStmt
+//!              | labeledStmt
 //!              | whileStmt
 //!              | forStmt
 //!              | block ;
+//! labeledStmt -> IDENTIFIER ":" ( whileStmt | forStmt ) ;
+//! breakStmt   -> "break" IDENTIFIER? ";" ;
+//! continueStmt -> "continue" IDENTIFIER? ";" ;
 //! forStmt     -> "for" "(" ( varDecl | exprStmt | ";")
 //!                expression> ";"
 //!                expression? ")" statement ;
@@ -22,32 +29,55 @@
 //!                ( "else" statement )? ;
 //! returnStmt  -> "return" expression?  ";"
 //! block       -> "" declaration* "" ;
-//! exprStmt    -> expression ";" ;
+//! exprStmt    -> expression ";"
+//!              | IDENTIFIER ( "," IDENTIFIER )+ "=" expression ( "," expression )* ";" ;
 //! printStmt   -> "print" expression ";" ;
 //! expression  -> assignment ;
-//! assignment  -> IDENTIFIER "=" assignment
+//! assignment  -> IDENTIFIER ( "[" expression "]" )* "=" assignment
 //!             | logic_or
 //! logic_or    -> logic_and ( "or" logic_and )* ;
 //! logic_and   -> equality ( "and" equality )* ;
 //! equality    -> comparison ( ("!=" | "==") comparison ) *;
-//! comparison  -> term ( (">" | ">=" | "<" | "<=" ) term ) *;
+//! comparison  -> bit_or ( (">" | ">=" | "<" | "<=" | "in" ) bit_or ) *;
+//! bit_or      -> bit_xor ( "|" bit_xor ) * ;
+//! bit_xor     -> bit_and ( "^" bit_and ) * ;
+//! bit_and     -> shift ( "&" shift ) * ;
+//! shift       -> term ( ( "<<" | ">>" ) term ) * ;
 //! term        -> factor ( ( "-" | "+" ) factor ) * ;
 //! factor      -> unary ( ( "/" | "*" ) unary ) * ;
-//! unary       -> ( "!" | "-" ) unary | call ;
-//! call        -> primary ( "(" arguments? ")" )* ;
+//! unary       -> ( "!" | "-" | "~" ) unary | call ;
+//! call        -> primary ( "(" arguments? ")" | "[" expression "]" | "?." IDENTIFIER )* ;
 //! arguments   -> expression ( "," expression )* ;
-//! primary     -> NUMBER | STRING | "true" | "false" | "nil" | "(" expression ")" | IDENTIFIER ;
+//! primary     -> NUMBER | STRING | "true" | "false" | "nil" | "(" expression ")" | tuple | list | map | lambda | IDENTIFIER ;
+//! lambda      -> "fun" "(" parameters? ")" block ;
+//! tuple       -> "(" expression ( "," expression )+ ")" ;
+//! list        -> "[" ( expression ( "," expression )* )? "]" ;
+//! map         -> "{" ( expression ":" expression ( "," expression ":" expression )* )? "}" ;
+use crate::diagnostic::Diagnostic;
 use crate::token::{Token, TokenType};
+use std::collections::HashMap;
 use std::error;
 use std::fmt;
 use std::vec::Vec;
 
+// 式の入れ子(括弧、単項演算子など)の上限。深すぎる入力でスタックオーバーフローしないように制限する
+const MAX_NEST_DEPTH: usize = 128;
+// 文の入れ子、式の入れ子、二項演算子などの連結を合わせた構文木の深さの上限
+const MAX_AST_DEPTH: usize = 512;
+// 関数の引数、仮引数の数の上限
+const MAX_ARGUMENTS: usize = 255;
+
 /// 解析エラー
 pub enum ParseError {
     CouldNotReadToken,
     NotFoundToken(String),
     NotFoundAstType(String),
     NotSupportToken(String),
+    ReservedWord(String),
+    EmptyGrouping,
+    MissingOperand(String),
+    TooManyArguments,
+    TooDeep(usize),
 }
 impl ParseError {
     fn print(&self) -> String {
@@ -56,6 +86,15 @@ impl ParseError {
             Self::NotFoundToken(token) => format!("Could not found {:?} token", token),
             Self::NotFoundAstType(ast) => format!("Could not found {:?} ast type", ast),
             Self::NotSupportToken(token) => format!("Could not support {:?} token", token),
+            Self::ReservedWord(word) => {
+                format!("'{}' is a reserved word and cannot be used as a name", word)
+            }
+            Self::EmptyGrouping => "Expected expression inside parentheses".to_string(),
+            Self::MissingOperand(operator) => format!("Expected operand after '{}'", operator),
+            Self::TooManyArguments => {
+                format!("Can not have more than {} arguments", MAX_ARGUMENTS)
+            }
+            Self::TooDeep(max) => format!("Nesting is too deep (max {})", max),
         }
     }
 }
@@ -79,22 +118,36 @@ impl error::Error for ParseError {
 }
 
 type ParseResult = Result<AstType, ParseError>;
+// 二項演算子のノード生成関数
+type BinaryOperator = fn(Box<AstType>, Box<AstType>) -> AstType;
 
 #[derive(PartialEq, Clone, Debug)]
 pub enum AstType {
     // varDecl
-    Var(String, Box<AstType>),
+    Var(String, Option<Box<AstType>>), // 変数名、初期化式(省略時はNone)
+    MultiVar(Vec<String>, Box<AstType>), // 変数名列、初期化式
     Fun(String, Vec<AstType>, Box<AstType>), // 関数名、引数列、ブロック
+    Lambda(Vec<AstType>, Box<AstType>), // 引数列、ブロック(無名関数)
 
     // statement
     Print(Box<AstType>),
     Block(Vec<AstType>),
-    While(Box<AstType>, Box<AstType>),            // 条件、ブロック
-    If(Box<AstType>, Box<AstType>, Box<AstType>), // 条件、IFブロック、ELSEブロック
+    While(Box<AstType>, Box<AstType>, Box<AstType>, Box<AstType>), // 条件、ブロック、インクリメント(for文のみ)、ELSEブロック(一度も繰り返さなかった場合)
+    If(Box<AstType>, Box<AstType>, Box<AstType>),                  // 条件、IFブロック、ELSEブロック
+    Repeat(Box<AstType>, Box<AstType>),                            // 繰り返し回数、ブロック
+    Try(Box<AstType>, String, Box<AstType>), // tryブロック、エラーを格納する変数名、catchブロック
+    Throw(Box<AstType>),                     // catchに渡す値
     Return(Box<AstType>),
+    Break(Option<String>),         // ラベル
+    Continue(Option<String>),      // ラベル
+    Labeled(String, Box<AstType>), // ラベル、ループ
+    Empty,                         // 何もしない文(pass)
+    Global(Vec<String>),           // グローバル変数として扱う変数名列
 
     // Assignment
     Assign(String, Box<AstType>),
+    MultiAssign(Vec<String>, Vec<AstType>), // 変数名列、代入式列
+    IndexAssign(Box<AstType>, Box<AstType>, Box<AstType>), // 代入先、インデックス(キー)、代入式
 
     // Equality
     BangEqual(Box<AstType>, Box<AstType>),
@@ -107,6 +160,14 @@ pub enum AstType {
     GreaterEqual(Box<AstType>, Box<AstType>),
     Less(Box<AstType>, Box<AstType>),
     LessEqual(Box<AstType>, Box<AstType>),
+    In(Box<AstType>, Box<AstType>),
+
+    // Bitwise
+    BitOr(Box<AstType>, Box<AstType>),
+    BitXor(Box<AstType>, Box<AstType>),
+    BitAnd(Box<AstType>, Box<AstType>),
+    ShiftLeft(Box<AstType>, Box<AstType>),
+    ShiftRight(Box<AstType>, Box<AstType>),
 
     // Term
     Minus(Box<AstType>, Box<AstType>),
@@ -116,13 +177,21 @@ pub enum AstType {
     Div(Box<AstType>, Box<AstType>),
     Mul(Box<AstType>, Box<AstType>),
 
+    // Power
+    Pow(Box<AstType>, Box<AstType>),
+
     // Unary
     Bang(Box<AstType>),
     UnaryMinus(Box<AstType>),
-    Call(String, Vec<AstType>), // 関数名、引数
+    BitNot(Box<AstType>),
+    Call(String, Vec<AstType>),        // 関数名、引数
+    Index(Box<AstType>, Box<AstType>), // 参照先、インデックス(キー)
+    OptionalGet(Box<AstType>, String), // 参照先(nilの場合はnilとなる)、キー
 
     // primary
     Grouping(Box<AstType>),
+    List(Vec<AstType>),
+    Map(Vec<(AstType, AstType)>), // キーと値の組
 
     // 終端記号
     Number(f64),
@@ -136,35 +205,126 @@ pub enum AstType {
 pub struct Parser<'a> {
     read_pos: usize,
     tokens: &'a Vec<Token>,
+    errors: Vec<Diagnostic>,
+    warnings: Vec<Diagnostic>,
+    positions: Vec<(usize, usize)>,  // 各文の開始位置(行、カラム)
+    spans: Vec<Vec<(usize, usize)>>, // 各文のノードの位置(帰りがけ順)
+    node_spans: Vec<(usize, usize)>, // パース中の文のノードの位置(帰りがけ順)
+    depth: usize,                    // 現在の式の入れ子の深さ
+    chain: usize,                    // 現在の式での二項演算子などの連結の段数
+    statement_depth: usize,          // 現在の文の入れ子の深さ
 }
 impl<'a> Parser<'a> {
     pub fn new(tokens: &'a Vec<Token>) -> Self {
         Parser {
             read_pos: 0,
             tokens,
+            errors: vec![],
+            warnings: vec![],
+            positions: vec![],
+            spans: vec![],
+            node_spans: vec![],
+            depth: 0,
+            chain: 0,
+            statement_depth: 0,
         }
     }
 
+    /// パースエラー取得
+    ///
+    /// # Returns
+    /// * &Vec<Diagnostic> - programで発生したエラー
+    pub fn errors(&self) -> &Vec<Diagnostic> {
+        &self.errors
+    }
+
+    /// 警告取得
+    ///
+    /// # Returns
+    /// * &Vec<Diagnostic> - programで検出した警告(実行は継続できるもの。未使用の変数はlint::unused_variablesで検出する)
+    pub fn warnings(&self) -> &Vec<Diagnostic> {
+        &self.warnings
+    }
+
+    /// 文の開始位置取得
+    ///
+    /// # Returns
+    /// * &Vec<(usize, usize)> - programの結果と同じ順序の(行、カラム)
+    pub fn positions(&self) -> &Vec<(usize, usize)> {
+        &self.positions
+    }
+
+    /// 各文のノードの位置取得
+    ///
+    /// 文ごとに、ノードの位置を帰りがけ順(子ノード、親ノードの順)で保持する。
+    /// ノードとの対応付けはnode_spansで行う
+    ///
+    /// # Returns
+    /// * &Vec<Vec<(usize, usize)>> - programの結果と同じ順序の、各ノードの(行、カラム)
+    pub fn spans(&self) -> &Vec<Vec<(usize, usize)>> {
+        &self.spans
+    }
+
+    /// ノードの位置を記録
+    ///
+    /// 子ノードを記録した後に呼び出すこと(帰りがけ順)
+    ///
+    /// # Arguments
+    /// * `span` - ノードの位置(行、カラム)
+    /// * `ast` - ノード
+    ///
+    /// # Returns
+    /// * AstType - 引数のノード
+    fn spanned(&mut self, span: (usize, usize), ast: AstType) -> AstType {
+        self.node_spans.push(span);
+        ast
+    }
+
+    /// 直前に読み込んだTokenの位置(行、カラム)
+    fn last_position(&self) -> (usize, usize) {
+        self.position(self.read_pos.saturating_sub(1))
+    }
+
+    /// 指定位置のTokenの位置(行、カラム)
+    ///
+    /// # Arguments
+    /// * `pos` - Tokenの位置。範囲外の場合は最後のTokenとする
+    ///
+    /// # Returns
+    /// * (usize, usize) - 行、カラム
+    fn position(&self, pos: usize) -> (usize, usize) {
+        self.tokens
+            .get(pos)
+            .or(self.tokens.last())
+            .map_or((0, 0), |t| (t.line(), t.col()))
+    }
+
     /// program parse
     ///
     /// # Returns
     /// * Vec<AstType> - パース結果
     pub fn program(&mut self) -> Vec<AstType> {
         let mut result = vec![];
-        loop {
-            self.declaration().map_or_else(
-                |_| {
+        while !self.end() {
+            let start = self.position(self.read_pos);
+            self.node_spans.clear();
+            match self.declaration() {
+                Ok(parse_result) => {
+                    debug_assert_eq!(count_nodes(&parse_result), self.node_spans.len());
+                    result.push(parse_result);
+                    self.positions.push(start);
+                    self.spans.push(std::mem::take(&mut self.node_spans));
+                }
+                Err(err) => {
+                    // エラーとなったTokenの位置を記録
+                    let (line, col) = self.position(self.read_pos.saturating_sub(1));
+                    self.errors
+                        .push(Diagnostic::new(err.to_string(), line, col));
+
                     // 文の区切りまでSKIPし、再度パースを行う
                     self.back();
                     self.synchronize();
-                },
-                |parse_result| {
-                    result.push(parse_result);
-                },
-            );
-
-            if self.end() {
-                break;
+                }
             }
         }
 
@@ -180,7 +340,8 @@ impl<'a> Parser<'a> {
             .map_or(Err(ParseError::CouldNotReadToken), |token| {
                 match token.token_type() {
                     TokenType::Var => self.var_declaration(),
-                    TokenType::Fun => self.fun_declaration(),
+                    // fun(...)は無名関数の式文
+                    TokenType::Fun if !self.check(&TokenType::LeftParen) => self.fun_declaration(),
                     _ => {
                         self.back();
                         self.statement()
@@ -194,6 +355,7 @@ impl<'a> Parser<'a> {
     /// # Returns
     /// * ParseResult - パース結果
     fn fun_declaration(&mut self) -> ParseResult {
+        let start = self.last_position();
         self.token()
             .map_or(Err(ParseError::CouldNotReadToken), |token| {
                 match token.token_type() {
@@ -204,9 +366,10 @@ impl<'a> Parser<'a> {
                         self.consume(Some(TokenType::RightParen))?;
                         self.consume(Some(TokenType::LeftBrace))?;
                         let body = self.block_statement()?;
-                        Ok(AstType::Fun(identifier.to_string(), args, Box::new(body)))
+                        let fun = AstType::Fun(identifier.to_string(), args, Box::new(body));
+                        Ok(self.spanned(start, fun))
                     }
-                    _ => Err(ParseError::NotFoundToken(String::from("Identifier"))),
+                    t => Err(Self::not_identifier(t)),
                 }
             })
     }
@@ -235,10 +398,8 @@ impl<'a> Parser<'a> {
                 return Err(ParseError::CouldNotReadToken);
             }
 
-            // 引数の数は255までしか解釈しない
-            if arguments.len() >= 255 {
-                println!("Can not have more than 255 arguments");
-                break;
+            if arguments.len() > MAX_ARGUMENTS {
+                return Err(ParseError::TooManyArguments);
             }
         }
 
@@ -257,7 +418,7 @@ impl<'a> Parser<'a> {
                         self.back();
                         self.primary()
                     }
-                    _ => Err(ParseError::NotFoundToken(String::from("Identifier"))),
+                    t => Err(Self::not_identifier(t)),
                 }
             })
     }
@@ -267,13 +428,46 @@ impl<'a> Parser<'a> {
     /// # Returns
     /// * ParseResult - パース結果
     fn var_declaration(&mut self) -> ParseResult {
-        self.token()
+        let start = self.last_position();
+        let var = self
+            .token()
             .map_or(Err(ParseError::CouldNotReadToken), |token| {
                 match token.token_type() {
                     TokenType::Identifier(i) => self.declaration_identifier(i.clone()),
-                    _ => Err(ParseError::NotFoundToken(String::from("Identifier"))),
+                    TokenType::LeftParen => self.declaration_multi_identifier(),
+                    t => Err(Self::not_identifier(t)),
                 }
-            })
+            })?;
+
+        Ok(self.spanned(start, var))
+    }
+
+    /// create multiple identifier declaration
+    ///
+    /// var (x, y) = expression; の形式で、右辺のリストを分割して変数定義する
+    ///
+    /// # Returns
+    /// * ParseResult - パース結果
+    fn declaration_multi_identifier(&mut self) -> ParseResult {
+        let mut identifiers = vec![];
+        loop {
+            match self.consume(None)?.token_type() {
+                TokenType::Identifier(i) => identifiers.push(i.clone()),
+                t => return Err(Self::not_identifier(t)),
+            }
+
+            match self.consume(None)?.token_type() {
+                TokenType::Comma => continue,
+                TokenType::RightParen => break,
+                _ => return Err(ParseError::NotFoundToken(String::from("RightParen"))),
+            }
+        }
+
+        self.consume(Some(TokenType::Equal))?;
+        let expr = self.expression()?;
+        self.consume(Some(TokenType::SemiColon))?;
+
+        Ok(AstType::MultiVar(identifiers, Box::new(expr)))
     }
 
     /// create identifier declaration
@@ -288,10 +482,10 @@ impl<'a> Parser<'a> {
                     TokenType::Equal => {
                         let expr = self.expression()?;
                         self.consume(Some(TokenType::SemiColon))?;
-                        Ok(AstType::Var(identifier, Box::new(expr)))
+                        Ok(AstType::Var(identifier, Some(Box::new(expr))))
                     }
-                    // 初期化されていない変数は、nilで初期化
-                    TokenType::SemiColon => Ok(AstType::Var(identifier, Box::new(AstType::Nil))),
+                    // 初期化されていない変数は、未初期化として定義(参照時はnil)
+                    TokenType::SemiColon => Ok(AstType::Var(identifier, None)),
                     _ => Err(ParseError::NotFoundToken(String::from("SemiColon"))),
                 }
             },
@@ -300,18 +494,48 @@ impl<'a> Parser<'a> {
 
     /// statement parse
     ///
+    /// if、while、ブロックなどで文が入れ子になるため、式と合わせた深さを制限する
+    ///
     /// # Returns
     /// * ParseResult - パース結果
     fn statement(&mut self) -> ParseResult {
+        self.statement_depth += 1;
+        let result = self.ast_depth().and_then(|_| self.statement_kind());
+        self.statement_depth -= 1;
+
+        result
+    }
+
+    /// 文の種類ごとのparse
+    ///
+    /// # Returns
+    /// * ParseResult - パース結果
+    fn statement_kind(&mut self) -> ParseResult {
         self.token()
             .map_or(Err(ParseError::CouldNotReadToken), |token| {
                 match token.token_type() {
                     TokenType::Print => self.print_statement(),
                     TokenType::If => self.if_statement(),
-                    TokenType::While => self.while_statement(),
-                    TokenType::For => self.for_statement(),
+                    TokenType::While => self.while_statement(None),
+                    TokenType::Repeat => self.repeat_statement(),
+                    TokenType::Try => self.try_statement(),
+                    TokenType::Throw => self.throw_statement(),
+                    TokenType::For => self.for_statement(None),
                     TokenType::Return => self.return_statement(),
+                    TokenType::Break => {
+                        let label = self.jump_label()?;
+                        Ok(self.spanned((token.line(), token.col()), AstType::Break(label)))
+                    }
+                    TokenType::Continue => {
+                        let label = self.jump_label()?;
+                        Ok(self.spanned((token.line(), token.col()), AstType::Continue(label)))
+                    }
+                    TokenType::Pass => self.pass_statement(),
+                    TokenType::Global => self.global_statement(),
                     TokenType::LeftBrace => self.block_statement(),
+                    TokenType::Identifier(label) if self.check(&TokenType::Colon) => {
+                        self.labeled_statement(label.clone())
+                    }
                     _ => {
                         self.back();
                         self.expression_stmt()
@@ -320,16 +544,94 @@ impl<'a> Parser<'a> {
             })
     }
 
+    /// labeled statement parse
+    ///
+    /// # Arguments
+    /// * `label` - ラベル
+    ///
+    /// # Returns
+    /// * ParseResult - パース結果
+    fn labeled_statement(&mut self, label: String) -> ParseResult {
+        let label = (label, self.last_position());
+        self.consume(Some(TokenType::Colon))?;
+        self.token()
+            .map_or(Err(ParseError::CouldNotReadToken), |token| {
+                match token.token_type() {
+                    TokenType::While => self.while_statement(Some(label)),
+                    TokenType::For => self.for_statement(Some(label)),
+                    _ => Err(ParseError::NotFoundToken(String::from("While or For"))),
+                }
+            })
+    }
+
+    /// pass statement parse
+    ///
+    /// 空の関数本体などを明示する、何もしない文。セミコロンは省略できる
+    ///
+    /// # Returns
+    /// * ParseResult - パース結果
+    fn pass_statement(&mut self) -> ParseResult {
+        let start = self.last_position();
+        if self.check(&TokenType::SemiColon) {
+            self.consume(Some(TokenType::SemiColon))?;
+        }
+
+        Ok(self.spanned(start, AstType::Empty))
+    }
+
+    /// global statement parse
+    ///
+    /// global x, y; の形式で、以降の代入、参照をグローバル変数に対して行う
+    ///
+    /// # Returns
+    /// * ParseResult - パース結果
+    fn global_statement(&mut self) -> ParseResult {
+        let start = self.last_position();
+        let mut identifiers = vec![];
+        loop {
+            match self.consume(None)?.token_type() {
+                TokenType::Identifier(i) => identifiers.push(i.clone()),
+                t => return Err(Self::not_identifier(t)),
+            }
+
+            match self.consume(None)?.token_type() {
+                TokenType::Comma => continue,
+                TokenType::SemiColon => break,
+                _ => return Err(ParseError::NotFoundToken(String::from("SemiColon"))),
+            }
+        }
+
+        Ok(self.spanned(start, AstType::Global(identifiers)))
+    }
+
+    /// break/continueのラベル parse
+    ///
+    /// # Returns
+    /// * Result<Option<String>, ParseError> - パース結果
+    fn jump_label(&mut self) -> Result<Option<String>, ParseError> {
+        let label = match self.consume(None)?.token_type() {
+            TokenType::Identifier(label) => Some(label.clone()),
+            _ => {
+                self.back();
+                None
+            }
+        };
+        self.consume(Some(TokenType::SemiColon))?;
+
+        Ok(label)
+    }
+
     /// return statement parse
     ///
     /// # Returns
     /// * ParseResult - パース結果
     fn return_statement(&mut self) -> ParseResult {
+        let start = self.last_position();
         let expr = if let Some(token) = self.token() {
             match token.token_type() {
                 TokenType::SemiColon => {
                     self.back();
-                    AstType::Nil
+                    self.spanned((token.line(), token.col()), AstType::Nil)
                 }
                 _ => {
                     self.back();
@@ -342,42 +644,148 @@ impl<'a> Parser<'a> {
 
         self.consume(Some(TokenType::SemiColon))?;
 
-        Ok(AstType::Return(Box::new(expr)))
+        Ok(self.spanned(start, AstType::Return(Box::new(expr))))
     }
 
     /// while statement parse
     ///
+    /// # Arguments
+    /// * `label` - ループに付与されたラベルと、ラベルの位置
+    ///
     /// # Returns
     /// * ParseResult - パース結果
-    fn while_statement(&mut self) -> ParseResult {
+    fn while_statement(&mut self, label: Option<(String, (usize, usize))>) -> ParseResult {
+        let start = self.last_position();
         self.consume(Some(TokenType::LeftParen))?;
-        let condition = self.expression()?;
+        let condition = self.condition()?;
+        self.consume(Some(TokenType::RightParen))?;
+        let stmt = self.statement()?;
+        let increment = self.spanned(start, AstType::Nil);
+
+        // 条件が最初から偽で、一度も繰り返さなかった場合に実行するelse
+        let else_stmt = if self.check(&TokenType::Else) {
+            self.consume(Some(TokenType::Else))?;
+            self.statement()?
+        } else {
+            self.spanned(start, AstType::Nil)
+        };
+
+        let while_stmt = self.spanned(
+            start,
+            AstType::While(
+                Box::new(condition),
+                Box::new(stmt),
+                Box::new(increment),
+                Box::new(else_stmt),
+            ),
+        );
+        Ok(self.labeled(label, while_stmt))
+    }
+
+    /// repeat statement parse
+    ///
+    /// # Returns
+    /// * ParseResult - パース結果
+    fn repeat_statement(&mut self) -> ParseResult {
+        let start = self.last_position();
+        self.consume(Some(TokenType::LeftParen))?;
+        let count = self.expression()?;
         self.consume(Some(TokenType::RightParen))?;
         let stmt = self.statement()?;
 
-        Ok(AstType::While(Box::new(condition), Box::new(stmt)))
+        let repeat_stmt = AstType::Repeat(Box::new(count), Box::new(stmt));
+        Ok(self.spanned(start, repeat_stmt))
+    }
+
+    /// try statement parse
+    ///
+    /// try { ... } catch (e) { ... } の形式
+    ///
+    /// # Returns
+    /// * ParseResult - パース結果
+    fn try_statement(&mut self) -> ParseResult {
+        let start = self.last_position();
+        self.consume(Some(TokenType::LeftBrace))?;
+        let try_block = self.block_statement()?;
+        self.consume(Some(TokenType::Catch))?;
+        self.consume(Some(TokenType::LeftParen))?;
+        let name = match self.consume(None)?.token_type() {
+            TokenType::Identifier(name) => name.clone(),
+            t => return Err(Self::not_identifier(t)),
+        };
+        self.consume(Some(TokenType::RightParen))?;
+        self.consume(Some(TokenType::LeftBrace))?;
+        let catch_block = self.block_statement()?;
+
+        let try_stmt = AstType::Try(Box::new(try_block), name, Box::new(catch_block));
+        Ok(self.spanned(start, try_stmt))
+    }
+
+    /// throw statement parse
+    ///
+    /// # Returns
+    /// * ParseResult - パース結果
+    fn throw_statement(&mut self) -> ParseResult {
+        let start = self.last_position();
+        let expr = self.expression()?;
+        self.consume(Some(TokenType::SemiColon))?;
+
+        Ok(self.spanned(start, AstType::Throw(Box::new(expr))))
     }
 
     /// for statement parse
     ///
+    /// # Arguments
+    /// * `label` - ループに付与されたラベルと、ラベルの位置
+    ///
     /// # Returns
     /// * ParseResult - パース結果
-    fn for_statement(&mut self) -> ParseResult {
+    fn for_statement(&mut self, label: Option<(String, (usize, usize))>) -> ParseResult {
+        let start = self.last_position();
         self.consume(Some(TokenType::LeftParen))?;
         let initialize = self.for_initialize()?;
         let condition = self.for_condition()?;
         self.consume(Some(TokenType::SemiColon))?;
+        let increment_start = self.node_spans.len();
         let increment = self.for_increment()?;
         self.consume(Some(TokenType::RightParen))?;
+        let stmt_start = self.node_spans.len();
         let stmt = self.statement()?;
 
-        Ok(AstType::Block(vec![
-            initialize,
+        // ノードの位置は、While(条件、ブロック、インクリメント)の順に並べ替える
+        let mut stmt_spans = self.node_spans.split_off(stmt_start);
+        let increment_spans = self.node_spans.split_off(increment_start);
+        self.node_spans.append(&mut stmt_spans);
+        self.node_spans.extend(increment_spans);
+        let else_stmt = self.spanned(start, AstType::Nil);
+
+        // continueでインクリメントがSKIPされないように、インクリメントはブロックと分けて保持する
+        let while_stmt = self.spanned(
+            start,
             AstType::While(
                 Box::new(condition),
-                Box::new(AstType::Block(vec![stmt, increment])),
+                Box::new(stmt),
+                Box::new(increment),
+                Box::new(else_stmt),
             ),
-        ]))
+        );
+        let while_stmt = self.labeled(label, while_stmt);
+        Ok(self.spanned(start, AstType::Block(vec![initialize, while_stmt])))
+    }
+
+    /// ラベルが指定されている場合、ループをラベル付きのループとする
+    ///
+    /// # Arguments
+    /// * `label` - ラベルと、ラベルの位置
+    /// * `ast` - ループ
+    ///
+    /// # Returns
+    /// * AstType - ラベル付きのループ
+    fn labeled(&mut self, label: Option<(String, (usize, usize))>, ast: AstType) -> AstType {
+        match label {
+            Some((label, span)) => self.spanned(span, AstType::Labeled(label, Box::new(ast))),
+            None => ast,
+        }
     }
 
     /// for initialize parse
@@ -388,7 +796,9 @@ impl<'a> Parser<'a> {
         self.token()
             .map_or(Err(ParseError::CouldNotReadToken), |token| {
                 match token.token_type() {
-                    TokenType::SemiColon => Ok(AstType::Nil),
+                    TokenType::SemiColon => {
+                        Ok(self.spanned((token.line(), token.col()), AstType::Nil))
+                    }
                     TokenType::Var => self.var_declaration(),
                     _ => {
                         self.back();
@@ -408,11 +818,11 @@ impl<'a> Parser<'a> {
                 match token.token_type() {
                     TokenType::SemiColon => {
                         self.back();
-                        Ok(AstType::True)
+                        Ok(self.spanned((token.line(), token.col()), AstType::True))
                     }
                     _ => {
                         self.back();
-                        self.expression()
+                        self.condition()
                     }
                 }
             })
@@ -428,7 +838,7 @@ impl<'a> Parser<'a> {
                 match token.token_type() {
                     TokenType::RightParen => {
                         self.back();
-                        Ok(AstType::Nil)
+                        Ok(self.spanned((token.line(), token.col()), AstType::Nil))
                     }
                     _ => {
                         self.back();
@@ -438,33 +848,46 @@ impl<'a> Parser<'a> {
             })
     }
 
+    /// if、while、forの条件式 parse
+    ///
+    /// 条件式が代入の場合(`if (x = 5)`)は、`==`の誤りの可能性があるため警告する
+    ///
+    /// # Returns
+    /// * ParseResult - パース結果
+    fn condition(&mut self) -> ParseResult {
+        let (line, col) = self.position(self.read_pos);
+        let condition = self.expression()?;
+        if let AstType::Assign(_, _) = condition {
+            self.warnings.push(Diagnostic::new(
+                "assignment used as a condition; did you mean '=='?".to_string(),
+                line,
+                col,
+            ));
+        }
+
+        Ok(condition)
+    }
+
     /// if statement parse
     ///
     /// # Returns
     /// * ParseResult - パース結果
     fn if_statement(&mut self) -> ParseResult {
+        let start = self.last_position();
         self.consume(Some(TokenType::LeftParen))?;
-        let condition = self.expression()?;
+        let condition = self.condition()?;
         self.consume(Some(TokenType::RightParen))?;
         let if_stmt = self.statement()?;
 
-        let mut else_stmt = AstType::Nil;
-        if let Some(token) = self.token() {
-            match token.token_type() {
-                TokenType::Else => {
-                    else_stmt = self.statement()?;
-                }
-                _ => {
-                    self.back();
-                }
-            }
-        }
+        let else_stmt = if self.check(&TokenType::Else) {
+            self.consume(Some(TokenType::Else))?;
+            self.statement()?
+        } else {
+            self.spanned(start, AstType::Nil)
+        };
 
-        Ok(AstType::If(
-            Box::new(condition),
-            Box::new(if_stmt),
-            Box::new(else_stmt),
-        ))
+        let if_stmt = AstType::If(Box::new(condition), Box::new(if_stmt), Box::new(else_stmt));
+        Ok(self.spanned(start, if_stmt))
     }
 
     /// print statement parse
@@ -472,10 +895,11 @@ impl<'a> Parser<'a> {
     /// # Returns
     /// * ParseResult - パース結果
     fn print_statement(&mut self) -> ParseResult {
+        let start = self.last_position();
         let expr = self.expression()?;
         self.consume(Some(TokenType::SemiColon))?;
 
-        Ok(AstType::Print(Box::new(expr)))
+        Ok(self.spanned(start, AstType::Print(Box::new(expr))))
     }
 
     /// block statement
@@ -483,6 +907,7 @@ impl<'a> Parser<'a> {
     /// # Returns
     /// * ParseResult - パース結果
     fn block_statement(&mut self) -> ParseResult {
+        let start = self.last_position();
         let mut ast = vec![];
         loop {
             if let Some(token) = self.token() {
@@ -502,7 +927,7 @@ impl<'a> Parser<'a> {
         }
         self.consume(Some(TokenType::RightBrace))?;
 
-        Ok(AstType::Block(ast))
+        Ok(self.spanned(start, AstType::Block(ast)))
     }
 
     /// exprStmt parse
@@ -511,17 +936,95 @@ impl<'a> Parser<'a> {
     /// * ParseResult - パース結果
     fn expression_stmt(&mut self) -> ParseResult {
         let expr = self.expression()?;
+        let expr = match expr {
+            AstType::Identifier(i) if self.check(&TokenType::Comma) => self.multi_assignment(i)?,
+            _ => expr,
+        };
         self.consume(Some(TokenType::SemiColon))?;
 
         Ok(expr)
     }
 
+    /// 複数代入 parse
+    ///
+    /// # Arguments
+    /// * `first` - 1つ目の代入先変数名
+    ///
+    /// # Returns
+    /// * ParseResult - パース結果
+    fn multi_assignment(&mut self, first: String) -> ParseResult {
+        // 1つ目の代入先は変数名として保持するため、識別子ノードの位置は複数代入の位置とする
+        let start = self.node_spans.pop().unwrap_or_default();
+        let mut identifiers = vec![first];
+        while self.check(&TokenType::Comma) {
+            self.consume(Some(TokenType::Comma))?;
+            match self.consume(None)?.token_type() {
+                TokenType::Identifier(i) => identifiers.push(i.clone()),
+                _ => return Err(ParseError::NotFoundToken(String::from("Identifier"))),
+            }
+        }
+        self.consume(Some(TokenType::Equal))?;
+
+        let mut exprs = vec![self.expression()?];
+        while self.check(&TokenType::Comma) {
+            self.consume(Some(TokenType::Comma))?;
+            exprs.push(self.expression()?);
+        }
+
+        Ok(self.spanned(start, AstType::MultiAssign(identifiers, exprs)))
+    }
+
     /// expression parse
     ///
     /// # Returns
     /// * ParseResult - パース結果
     fn expression(&mut self) -> ParseResult {
-        self.assignment()
+        self.nest(Self::assignment)
+    }
+
+    /// 入れ子の深さを制限してparse
+    ///
+    /// # Arguments
+    /// * `parse` - parse関数
+    ///
+    /// # Returns
+    /// * ParseResult - パース結果。上限を超えた場合はTooDeep
+    fn nest(&mut self, parse: fn(&mut Self) -> ParseResult) -> ParseResult {
+        if self.depth >= MAX_NEST_DEPTH {
+            return Err(ParseError::TooDeep(MAX_NEST_DEPTH));
+        }
+
+        // parse中に連結した段数も含めて戻す
+        let (depth, chain) = (self.depth, self.chain);
+        self.depth += 1;
+        let result = self.ast_depth().and_then(|_| parse(self));
+        (self.depth, self.chain) = (depth, chain);
+
+        result
+    }
+
+    /// 連結による構文木の深さを1段加える
+    ///
+    /// 左結合の二項演算子、インデックス参照、代入の連結は再帰せずに構文木が深くなるため、
+    /// 連結ごとに呼び出す。段数は囲んでいるnestを抜けるまで戻さない
+    ///
+    /// # Returns
+    /// * Result<(), ParseError> - 上限を超えた場合はTooDeep
+    fn deepen(&mut self) -> Result<(), ParseError> {
+        self.chain += 1;
+        self.ast_depth()
+    }
+
+    /// 構文木の深さの判定
+    ///
+    /// # Returns
+    /// * Result<(), ParseError> - 文、式の入れ子と連結の段数の合計が上限を超えた場合はTooDeep
+    fn ast_depth(&self) -> Result<(), ParseError> {
+        if self.statement_depth + self.depth + self.chain > MAX_AST_DEPTH {
+            return Err(ParseError::TooDeep(MAX_AST_DEPTH));
+        }
+
+        Ok(())
     }
 
     /// assignment parse
@@ -529,15 +1032,47 @@ impl<'a> Parser<'a> {
     /// # Returns
     /// * ParseResult - パース結果
     fn assignment(&mut self) -> ParseResult {
-        let expr = self.or_parse()?;
+        let expr = self.binary(0)?;
 
         self.token()
             .map_or(Err(ParseError::CouldNotReadToken), |token| {
                 match token.token_type() {
                     TokenType::Equal => match expr {
+                        // 代入先のノードの位置は、代入の位置とする
                         AstType::Identifier(i) => {
+                            let start = self.node_spans.pop().unwrap_or_default();
+                            self.deepen()?;
                             let right_expr = self.assignment()?;
-                            Ok(AstType::Assign(i, Box::new(right_expr)))
+                            Ok(self.spanned(start, AstType::Assign(i, Box::new(right_expr))))
+                        }
+                        // 代入できるのは変数を起点とするインデックス参照のみ
+                        AstType::Index(target, index) if Self::is_variable_path(&target) => {
+                            let start = self.node_spans.pop().unwrap_or_default();
+                            self.deepen()?;
+                            let right_expr = self.assignment()?;
+                            let assign = AstType::IndexAssign(target, index, Box::new(right_expr));
+                            Ok(self.spanned(start, assign))
+                        }
+                        _ => Err(ParseError::NotFoundAstType(String::from("Identifier"))),
+                    },
+                    // 複合代入は`x = x op 右辺`に展開する
+                    TokenType::PlusEqual
+                    | TokenType::MinusEqual
+                    | TokenType::StarEqual
+                    | TokenType::SlashEqual => match expr {
+                        AstType::Identifier(i) => {
+                            let start = *self.node_spans.last().unwrap_or(&(0, 0));
+                            let left = Box::new(AstType::Identifier(i.clone()));
+                            self.deepen()?;
+                            let right = Box::new(self.assignment()?);
+                            let op = match token.token_type() {
+                                TokenType::PlusEqual => AstType::Plus(left, right),
+                                TokenType::MinusEqual => AstType::Minus(left, right),
+                                TokenType::StarEqual => AstType::Mul(left, right),
+                                _ => AstType::Div(left, right),
+                            };
+                            let op = self.spanned((token.line(), token.col()), op);
+                            Ok(self.spanned(start, AstType::Assign(i, Box::new(op))))
                         }
                         _ => Err(ParseError::NotFoundAstType(String::from("Identifier"))),
                     },
@@ -549,213 +1084,122 @@ impl<'a> Parser<'a> {
             })
     }
 
-    /// or parse
+    /// 二項演算子 parse
+    ///
+    /// logic_orからfactorまでの左結合の二項演算子を、優先順位を使って1つの関数でparseする
+    /// (優先順位ごとに関数を再帰しないため、括弧の入れ子1段あたりのスタック使用量を抑えられる)
+    ///
+    /// # Arguments
+    /// * `precedence` - parse対象とする二項演算子の最低の優先順位
     ///
     /// # Returns
     /// * ParseResult - パース結果
-    fn or_parse(&mut self) -> ParseResult {
-        let mut expr = self.and_parse()?;
-
-        loop {
-            if let Some(token) = self.token() {
-                match token.token_type() {
-                    TokenType::Or => {
-                        let right = self.and_parse()?;
-                        expr = AstType::Or(Box::new(expr), Box::new(right));
-                    }
-                    _ => {
-                        self.back();
-                        break;
-                    }
-                };
-            } else {
-                return Err(ParseError::CouldNotReadToken);
-            }
+    fn binary(&mut self, precedence: usize) -> ParseResult {
+        // トークンは複製せずに参照する
+        let tokens = self.tokens;
+        let mut expr = self.unary()?;
+        while let Some(token) = tokens.get(self.read_pos) {
+            let (operator_precedence, operator) = match binary_operator(token.token_type()) {
+                Some((p, operator)) if p >= precedence => (p, operator),
+                _ => break,
+            };
+            self.read_pos += 1;
+            self.deepen()?;
+            // 右辺は優先順位の高い演算子のみ取り込み、同じ優先順位は左結合とする
+            let right = self.binary(operator_precedence + 1)?;
+            expr = self.spanned(
+                (token.line(), token.col()),
+                operator(Box::new(expr), Box::new(right)),
+            );
         }
 
         Ok(expr)
     }
 
-    /// and parse
+    /// unary parse
     ///
     /// # Returns
     /// * ParseResult - パース結果
-    fn and_parse(&mut self) -> ParseResult {
-        let mut expr = self.equality()?;
-
-        loop {
-            if let Some(token) = self.token() {
+    fn unary(&mut self) -> ParseResult {
+        self.token()
+            .map_or(Err(ParseError::CouldNotReadToken), |token| {
                 match token.token_type() {
-                    TokenType::And => {
-                        let right = self.equality()?;
-                        expr = AstType::And(Box::new(expr), Box::new(right));
+                    TokenType::Bang => {
+                        let unary = self.unary_operand("!")?;
+                        Ok(self
+                            .spanned((token.line(), token.col()), AstType::Bang(Box::new(unary))))
                     }
-                    _ => {
-                        self.back();
-                        break;
-                    }
-                };
-            } else {
-                return Err(ParseError::CouldNotReadToken);
-            }
-        }
-
-        Ok(expr)
-    }
-
-    /// equality parse
-    ///
-    /// # Returns
-    /// * ParseResult - パース結果
-    fn equality(&mut self) -> ParseResult {
-        let mut comp = self.comparison()?;
-        loop {
-            let token = self.token();
-            if let Some(token) = token {
-                match token.token_type() {
-                    TokenType::BangEqual => {
-                        let right = self.comparison()?;
-                        comp = AstType::BangEqual(Box::new(comp), Box::new(right))
+                    TokenType::Minus => {
+                        let unary = self.unary_operand("-")?;
+                        Ok(self.spanned(
+                            (token.line(), token.col()),
+                            AstType::UnaryMinus(Box::new(unary)),
+                        ))
                     }
-                    TokenType::EqualEqual => {
-                        let right = self.comparison()?;
-                        comp = AstType::EqualEqual(Box::new(comp), Box::new(right))
+                    TokenType::Tilde => {
+                        let unary = self.unary_operand("~")?;
+                        Ok(self.spanned(
+                            (token.line(), token.col()),
+                            AstType::BitNot(Box::new(unary)),
+                        ))
                     }
                     _ => {
                         self.back();
-                        break;
+                        self.power()
                     }
-                };
-            } else {
-                break;
-            }
-        }
-
-        Ok(comp)
+                }
+            })
     }
 
-    /// comparison parse
+    /// 単項演算子のオペランド parse
     ///
-    /// # Returns
-    /// * ParseResult - パース結果
-    fn comparison(&mut self) -> ParseResult {
-        let mut term = self.term()?;
-        loop {
-            let token = self.token();
-            if let Some(token) = token {
-                match token.token_type() {
-                    TokenType::Greater => {
-                        let right = self.term()?;
-                        term = AstType::Greater(Box::new(term), Box::new(right))
-                    }
-                    TokenType::GreaterEqual => {
-                        let right = self.term()?;
-                        term = AstType::GreaterEqual(Box::new(term), Box::new(right))
-                    }
-                    TokenType::Less => {
-                        let right = self.term()?;
-                        term = AstType::Less(Box::new(term), Box::new(right))
-                    }
-                    TokenType::LessEqual => {
-                        let right = self.term()?;
-                        term = AstType::LessEqual(Box::new(term), Box::new(right))
-                    }
-                    _ => {
-                        self.back();
-                        break;
-                    }
-                };
-            } else {
-                break;
-            }
-        }
-        Ok(term)
-    }
-
-    /// term parse
+    /// 演算子の直後で式が終わる場合(`-;`)は、演算子の位置でエラーとする
+    ///
+    /// # Arguments
+    /// * `operator` - 演算子
     ///
     /// # Returns
     /// * ParseResult - パース結果
-    fn term(&mut self) -> ParseResult {
-        let mut factor = self.factor()?;
-        loop {
-            let token = self.token();
-            if let Some(token) = token {
-                match token.token_type() {
-                    TokenType::Minus => {
-                        let right = self.factor()?;
-                        factor = AstType::Minus(Box::new(factor), Box::new(right))
-                    }
-                    TokenType::Plus => {
-                        let right = self.factor()?;
-                        factor = AstType::Plus(Box::new(factor), Box::new(right))
-                    }
-                    _ => {
-                        self.back();
-                        break;
-                    }
-                };
-            } else {
-                break;
-            }
+    fn unary_operand(&mut self, operator: &str) -> ParseResult {
+        let missing = self.end()
+            || matches!(
+                self.tokens[self.read_pos].token_type(),
+                TokenType::SemiColon
+                    | TokenType::RightParen
+                    | TokenType::RightBrace
+                    | TokenType::RightBracket
+                    | TokenType::Comma
+            );
+        if missing {
+            return Err(ParseError::MissingOperand(operator.to_string()));
         }
 
-        Ok(factor)
+        self.nest(Self::unary)
     }
 
-    /// factory parse
+    /// power parse
+    ///
+    /// 右結合。単項演算子より優先順位が高いため、-2 ** 2は-(2 ** 2)となる
     ///
     /// # Returns
     /// * ParseResult - パース結果
-    fn factor(&mut self) -> ParseResult {
-        let mut unary = self.unary()?;
-        loop {
-            let token = self.token();
-            if let Some(token) = token {
-                match token.token_type() {
-                    TokenType::Slash => {
-                        let right = self.unary()?;
-                        unary = AstType::Div(Box::new(unary), Box::new(right))
-                    }
-                    TokenType::Star => {
-                        let right = self.unary()?;
-                        unary = AstType::Mul(Box::new(unary), Box::new(right))
-                    }
-                    _ => {
-                        self.back();
-                        break;
-                    }
-                };
-            } else {
-                break;
+    fn power(&mut self) -> ParseResult {
+        let call = self.call()?;
+        match self.token() {
+            Some(token) if *token.token_type() == TokenType::StarStar => {
+                // 右辺は単項演算子を許容する(2 ** -1)
+                let right = self.nest(Self::unary)?;
+                Ok(self.spanned(
+                    (token.line(), token.col()),
+                    AstType::Pow(Box::new(call), Box::new(right)),
+                ))
             }
+            Some(_) => {
+                self.back();
+                Ok(call)
+            }
+            None => Ok(call),
         }
-
-        Ok(unary)
-    }
-
-    /// unary parse
-    ///
-    /// # Returns
-    /// * ParseResult - パース結果
-    fn unary(&mut self) -> ParseResult {
-        self.token()
-            .map_or(Err(ParseError::CouldNotReadToken), |token| {
-                match token.token_type() {
-                    TokenType::Bang => {
-                        let unary = self.unary()?;
-                        Ok(AstType::Bang(Box::new(unary)))
-                    }
-                    TokenType::Minus => {
-                        let unary = self.unary()?;
-                        Ok(AstType::UnaryMinus(Box::new(unary)))
-                    }
-                    _ => {
-                        self.back();
-                        self.call()
-                    }
-                }
-            })
     }
 
     /// call parse
@@ -763,24 +1207,60 @@ impl<'a> Parser<'a> {
     /// # Returns
     /// * ParseResult - パース結果
     fn call(&mut self) -> ParseResult {
-        let expr = self.primary()?;
+        let mut expr = self.primary()?;
 
-        self.token()
-            .map_or(Err(ParseError::CouldNotReadToken), |token| {
-                match token.token_type() {
-                    TokenType::LeftParen => {
+        loop {
+            let token = self.token().ok_or(ParseError::CouldNotReadToken)?;
+            expr = match token.token_type() {
+                // 関数名は名前として保持するため、識別子ノードの位置は関数呼び出しの位置とする
+                TokenType::LeftParen => match expr {
+                    AstType::Identifier(i) => {
+                        let start = self.node_spans.pop().unwrap_or_default();
                         let arguments = self.arguments()?;
-                        match expr {
-                            AstType::Identifier(i) => Ok(AstType::Call(i, arguments)),
-                            _ => Err(ParseError::NotFoundAstType(String::from("Identifier"))),
-                        }
+                        self.spanned(start, AstType::Call(i, arguments))
                     }
-                    _ => {
-                        self.back();
-                        Ok(expr)
+                    _ => return Err(ParseError::NotFoundAstType(String::from("Identifier"))),
+                },
+                TokenType::LeftBracket => {
+                    self.deepen()?;
+                    let index = self.expression()?;
+                    self.consume(Some(TokenType::RightBracket))?;
+                    self.spanned(
+                        (token.line(), token.col()),
+                        AstType::Index(Box::new(expr), Box::new(index)),
+                    )
+                }
+                TokenType::QuestionDot => {
+                    self.deepen()?;
+                    match self.consume(None)?.token_type() {
+                        TokenType::Identifier(name) => self.spanned(
+                            (token.line(), token.col()),
+                            AstType::OptionalGet(Box::new(expr), name.clone()),
+                        ),
+                        t => return Err(Self::not_identifier(t)),
                     }
                 }
-            })
+                _ => {
+                    self.back();
+                    return Ok(expr);
+                }
+            };
+        }
+    }
+
+    /// 変数、もしくは変数を起点とするインデックス参照か判定
+    ///
+    /// # Arguments
+    /// * `ast` - 判定対象
+    ///
+    /// # Return
+    /// * bool - true: 代入先にできる false: 代入先にできない
+    fn is_variable_path(ast: &AstType) -> bool {
+        match ast {
+            AstType::Identifier(_) => true,
+            AstType::Index(target, _) => Self::is_variable_path(target),
+            _ => false,
+        }
     }
 
     /// arguments parse
@@ -800,20 +1280,15 @@ impl<'a> Parser<'a> {
                     TokenType::Comma => continue,
                     _ => {
                         self.back();
-                        let arg = self.expression();
-                        if let Ok(arg) = arg {
-                            arguments.push(arg);
-                        }
+                        arguments.push(self.expression()?);
                     }
                 }
             } else {
                 return Err(ParseError::CouldNotReadToken);
             }
 
-            // 引数の数は255までしか解釈しない
-            if arguments.len() >= 255 {
-                println!("Can not have more than 255 arguments");
-                break;
+            if arguments.len() > MAX_ARGUMENTS {
+                return Err(ParseError::TooManyArguments);
             }
         }
 
@@ -829,26 +1304,172 @@ impl<'a> Parser<'a> {
     fn primary(&mut self) -> ParseResult {
         self.token()
             .map_or(Err(ParseError::CouldNotReadToken), |token| {
+                let span = (token.line(), token.col());
                 match token.token_type() {
-                    TokenType::Number(n) => Ok(AstType::Number(*n)),
-                    TokenType::String(s) => Ok(AstType::String(s.clone())),
-                    TokenType::True => Ok(AstType::True),
-                    TokenType::False => Ok(AstType::False),
-                    TokenType::Nil => Ok(AstType::Nil),
-                    TokenType::LeftParen => {
-                        let expr = self.expression()?;
-                        self.consume(Some(TokenType::RightParen))?;
-                        Ok(AstType::Grouping(Box::new(expr)))
+                    TokenType::Number(n) => Ok(self.spanned(span, AstType::Number(*n))),
+                    TokenType::String(s) => Ok(self.spanned(span, AstType::String(s.clone()))),
+                    TokenType::True => Ok(self.spanned(span, AstType::True)),
+                    TokenType::False => Ok(self.spanned(span, AstType::False)),
+                    TokenType::Nil => Ok(self.spanned(span, AstType::Nil)),
+                    TokenType::LeftParen => self.grouping(),
+                    TokenType::LeftBracket => self.list(),
+                    TokenType::LeftBrace => self.map(),
+                    TokenType::Fun => self.lambda(),
+                    TokenType::If => self.if_expression(),
+                    TokenType::Identifier(i) => {
+                        Ok(self.spanned(span, AstType::Identifier(i.to_string())))
                     }
-                    TokenType::Identifier(i) => Ok(AstType::Identifier(i.to_string())),
-                    _ => Err(ParseError::NotSupportToken(format!("{:?}", token))),
+                    _ => Err(ParseError::NotSupportToken(format!(
+                        "{:?}",
+                        token.token_type()
+                    ))),
                 }
             })
     }
 
-    /// リードポインターデクリメント
+    /// if expression parse
+    ///
+    /// 式の位置に記述したifは、値を返す式として扱う(elseは必須)
+    ///
+    /// # Returns
+    /// * ParseResult - パース結果
+    fn if_expression(&mut self) -> ParseResult {
+        let start = self.last_position();
+        self.consume(Some(TokenType::LeftParen))?;
+        let condition = self.condition()?;
+        self.consume(Some(TokenType::RightParen))?;
+        let if_expr = self.expression()?;
+        self.consume(Some(TokenType::Else))?;
+        let else_expr = self.expression()?;
+
+        let if_expr = AstType::If(Box::new(condition), Box::new(if_expr), Box::new(else_expr));
+        Ok(self.spanned(start, if_expr))
+    }
+
+    /// grouping parse
+    ///
+    /// カンマ区切りで複数の式が記述されている場合は、リストとして扱う
+    ///
+    /// # Returns
+    /// * ParseResult - パース結果
+    fn grouping(&mut self) -> ParseResult {
+        let start = self.last_position();
+        // 空の括弧は式の途中の誤りとして、括弧の位置でエラーとする
+        if self.check(&TokenType::RightParen) {
+            return Err(ParseError::EmptyGrouping);
+        }
+        let expr = self.expression()?;
+
+        let mut elements = vec![];
+        loop {
+            match self.consume(None)?.token_type() {
+                TokenType::RightParen => break,
+                TokenType::Comma => elements.push(self.expression()?),
+                _ => return Err(ParseError::NotFoundToken(String::from("RightParen"))),
+            }
+        }
+
+        if elements.is_empty() {
+            Ok(self.spanned(start, AstType::Grouping(Box::new(expr))))
+        } else {
+            elements.insert(0, expr);
+            Ok(self.spanned(start, AstType::List(elements)))
+        }
+    }
+
+    /// list parse
+    ///
+    /// # Returns
+    /// * ParseResult - パース結果
+    fn list(&mut self) -> ParseResult {
+        let start = self.last_position();
+        let mut elements = vec![];
+        if self.check(&TokenType::RightBracket) {
+            self.consume(Some(TokenType::RightBracket))?;
+            return Ok(self.spanned(start, AstType::List(elements)));
+        }
+
+        loop {
+            elements.push(self.expression()?);
+            match self.consume(None)?.token_type() {
+                TokenType::RightBracket => break,
+                TokenType::Comma => continue,
+                _ => return Err(ParseError::NotFoundToken(String::from("RightBracket"))),
+            }
+        }
+
+        Ok(self.spanned(start, AstType::List(elements)))
+    }
+
+    /// lambda parse
+    ///
+    /// # Returns
+    /// * ParseResult - パース結果
+    fn lambda(&mut self) -> ParseResult {
+        let start = self.last_position();
+        self.consume(Some(TokenType::LeftParen))?;
+        let args = self.fun_parameters()?;
+        self.consume(Some(TokenType::RightParen))?;
+        self.consume(Some(TokenType::LeftBrace))?;
+        let body = self.block_statement()?;
+
+        Ok(self.spanned(start, AstType::Lambda(args, Box::new(body))))
+    }
+
+    /// map parse
+    ///
+    /// # Returns
+    /// * ParseResult - パース結果
+    fn map(&mut self) -> ParseResult {
+        let start = self.last_position();
+        let mut entries = vec![];
+        if self.check(&TokenType::RightBrace) {
+            self.consume(Some(TokenType::RightBrace))?;
+            return Ok(self.spanned(start, AstType::Map(entries)));
+        }
+
+        loop {
+            let key = self.expression()?;
+            self.consume(Some(TokenType::Colon))?;
+            entries.push((key, self.expression()?));
+            match self.consume(None)?.token_type() {
+                TokenType::RightBrace => break,
+                TokenType::Comma => continue,
+                _ => return Err(ParseError::NotFoundToken(String::from("RightBrace"))),
+            }
+        }
+
+        Ok(self.spanned(start, AstType::Map(entries)))
+    }
+
+    /// 次のTokenが指定したTokenTypeか判定する。Tokenは消費しない
+    ///
+    /// # Arguments
+    /// * `token_type` - 判定するTokenType
+    ///
+    /// # Return
+    /// * bool - true: 一致 false: 不一致
+    fn check(&self, token_type: &TokenType) -> bool {
+        !self.end() && self.tokens[self.read_pos].token_type() == token_type
+    }
+
+    /// 識別子が必要な位置に別のTokenがあった場合のエラー
+    ///
+    /// # Arguments
+    /// * `token_type` - 読み込んだTokenType
+    ///
+    /// # Return
+    /// * ParseError - 予約語の場合はReservedWord、それ以外はNotFoundToken
+    fn not_identifier(token_type: &TokenType) -> ParseError {
+        match token_type.keyword() {
+            Some(word) => ParseError::ReservedWord(word.to_string()),
+            None => ParseError::NotFoundToken(String::from("Identifier")),
+        }
+    }
+
+    /// リードポインターデクリメント。先頭の場合は何もしない
     fn back(&mut self) {
-        self.read_pos -= 1;
+        self.read_pos = self.read_pos.saturating_sub(1);
     }
 
     /// token取得
@@ -927,55 +1548,495 @@ impl<'a> Parser<'a> {
     }
 }
 
+/// 式文か判定
+///
+/// 代入式は値を返すが、文としての代入は式文として扱わない
+///
+/// # Arguments
+/// * `ast` - トップレベルの文のAST
+///
+/// # Return
+/// * bool - true: 式文 false: print、var、if、代入などの文
+pub fn is_expression_stmt(ast: &AstType) -> bool {
+    !matches!(
+        ast,
+        AstType::Assign(_, _)
+            | AstType::MultiAssign(_, _)
+            | AstType::IndexAssign(_, _, _)
+            | AstType::Var(_, _)
+            | AstType::MultiVar(_, _)
+            | AstType::Fun(_, _, _)
+            | AstType::Print(_)
+            | AstType::Block(_)
+            | AstType::While(_, _, _, _)
+            | AstType::If(_, _, _)
+            | AstType::Repeat(_, _)
+            | AstType::Try(_, _, _)
+            | AstType::Throw(_)
+            | AstType::Return(_)
+            | AstType::Break(_)
+            | AstType::Continue(_)
+            | AstType::Labeled(_, _)
+            | AstType::Empty
+            | AstType::Global(_)
+    )
+}
+
+/// 子ノード取得
+///
+/// 子ノードはソース上の記述順とする(for文のインクリメントは、ループ本体の後)
+///
+/// # Arguments
+/// * `ast` - AST
+///
+/// # Return
+/// * Vec<&AstType> - 子ノード
+pub fn children(ast: &AstType) -> Vec<&AstType> {
+    match ast {
+        AstType::Var(_, o) => o.iter().map(|o| o.as_ref()).collect(),
+        AstType::MultiVar(_, o)
+        | AstType::Print(o)
+        | AstType::Return(o)
+        | AstType::Throw(o)
+        | AstType::Labeled(_, o)
+        | AstType::Assign(_, o)
+        | AstType::Bang(o)
+        | AstType::UnaryMinus(o)
+        | AstType::BitNot(o)
+        | AstType::OptionalGet(o, _)
+        | AstType::Grouping(o) => vec![o],
+        AstType::Fun(_, args, body) | AstType::Lambda(args, body) => {
+            args.iter().chain([body.as_ref()]).collect()
+        }
+        AstType::Block(o) | AstType::MultiAssign(_, o) | AstType::Call(_, o) | AstType::List(o) => {
+            o.iter().collect()
+        }
+        AstType::While(a, b, c, d) => vec![a, b, c, d],
+        AstType::If(a, b, c) | AstType::IndexAssign(a, b, c) => vec![a, b, c],
+        AstType::Try(l, _, r)
+        | AstType::Repeat(l, r)
+        | AstType::BangEqual(l, r)
+        | AstType::EqualEqual(l, r)
+        | AstType::And(l, r)
+        | AstType::Or(l, r)
+        | AstType::Greater(l, r)
+        | AstType::GreaterEqual(l, r)
+        | AstType::Less(l, r)
+        | AstType::LessEqual(l, r)
+        | AstType::In(l, r)
+        | AstType::BitOr(l, r)
+        | AstType::BitXor(l, r)
+        | AstType::BitAnd(l, r)
+        | AstType::ShiftLeft(l, r)
+        | AstType::ShiftRight(l, r)
+        | AstType::Minus(l, r)
+        | AstType::Plus(l, r)
+        | AstType::Div(l, r)
+        | AstType::Mul(l, r)
+        | AstType::Pow(l, r)
+        | AstType::Index(l, r) => vec![l, r],
+        AstType::Map(entries) => entries.iter().flat_map(|(k, v)| [k, v]).collect(),
+        AstType::Break(_)
+        | AstType::Continue(_)
+        | AstType::Number(_)
+        | AstType::String(_)
+        | AstType::True
+        | AstType::False
+        | AstType::Nil
+        | AstType::Empty
+        | AstType::Global(_)
+        | AstType::Identifier(_) => vec![],
+    }
+}
+
+/// ノード数(自身を含む)
+fn count_nodes(ast: &AstType) -> usize {
+    1 + children(ast).into_iter().map(count_nodes).sum::<usize>()
+}
+
+/// 文の各ノードと位置を対応付ける
+///
+/// 定数畳み込み前の、Parserが返した文とその位置(Parser::spans)を指定すること
+///
+/// # Arguments
+/// * `ast` - 文
+/// * `spans` - 文のノードの位置(帰りがけ順)
+///
+/// # Return
+/// * Vec<(&AstType, (usize, usize))> - ノードと位置(行、カラム)の組(帰りがけ順)
+pub fn node_spans<'b>(
+    ast: &'b AstType,
+    spans: &[(usize, usize)],
+) -> Vec<(&'b AstType, (usize, usize))> {
+    fn post_order<'b>(ast: &'b AstType, nodes: &mut Vec<&'b AstType>) {
+        children(ast).into_iter().for_each(|c| post_order(c, nodes));
+        nodes.push(ast);
+    }
+
+    let mut nodes = vec![];
+    post_order(ast, &mut nodes);
+    nodes.into_iter().zip(spans.iter().copied()).collect()
+}
+
+/// 文をJSONへ変換
+///
+/// 各ノードは種類(type)、位置(line、col。エラー表示と同じく1始まり)、子ノード(children)を持つ。
+/// 名前や値を持つノードは、name、names、valueも出力する
+///
+/// # Arguments
+/// * `ast` - 文
+/// * `spans` - 文のノードの位置(Parser::spans)
+///
+/// # Return
+/// * String - JSON
+pub fn to_json(ast: &AstType, spans: &[(usize, usize)]) -> String {
+    let spans = node_spans(ast, spans)
+        .into_iter()
+        .map(|(node, span)| (node as *const AstType, span))
+        .collect::<HashMap<_, _>>();
+
+    node_json(ast, &spans)
+}
+
+/// ノードをJSONへ変換
+fn node_json(ast: &AstType, spans: &HashMap<*const AstType, (usize, usize)>) -> String {
+    let mut fields = vec![format!("\"type\":{}", json_string(node_type(ast)))];
+    if let Some((line, col)) = spans.get(&(ast as *const AstType)) {
+        fields.push(format!("\"line\":{},\"col\":{}", line + 1, col + 1));
+    }
+    match ast {
+        AstType::Var(name, _)
+        | AstType::Fun(name, _, _)
+        | AstType::Labeled(name, _)
+        | AstType::Assign(name, _)
+        | AstType::Call(name, _)
+        | AstType::Try(_, name, _)
+        | AstType::OptionalGet(_, name)
+        | AstType::Identifier(name) => fields.push(format!("\"name\":{}", json_string(name))),
+        AstType::Break(Some(label)) | AstType::Continue(Some(label)) => {
+            fields.push(format!("\"name\":{}", json_string(label)))
+        }
+        AstType::MultiVar(names, _) | AstType::MultiAssign(names, _) | AstType::Global(names) => {
+            fields.push(format!(
+                "\"names\":[{}]",
+                names
+                    .iter()
+                    .map(|n| json_string(n))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ))
+        }
+        AstType::Number(n) => fields.push(format!("\"value\":{}", n)),
+        AstType::String(v) => fields.push(format!("\"value\":{}", json_string(v))),
+        _ => {}
+    }
+    let children = children(ast)
+        .into_iter()
+        .map(|c| node_json(c, spans))
+        .collect::<Vec<_>>();
+    fields.push(format!("\"children\":[{}]", children.join(",")));
+
+    format!("{{{}}}", fields.join(","))
+}
+
+/// ノードの種類名
+pub(crate) fn node_type(ast: &AstType) -> &'static str {
+    match ast {
+        AstType::Var(_, _) => "Var",
+        AstType::MultiVar(_, _) => "MultiVar",
+        AstType::Fun(_, _, _) => "Fun",
+        AstType::Lambda(_, _) => "Lambda",
+        AstType::Print(_) => "Print",
+        AstType::Block(_) => "Block",
+        AstType::While(_, _, _, _) => "While",
+        AstType::If(_, _, _) => "If",
+        AstType::Return(_) => "Return",
+        AstType::Throw(_) => "Throw",
+        AstType::Break(_) => "Break",
+        AstType::Continue(_) => "Continue",
+        AstType::Labeled(_, _) => "Labeled",
+        AstType::Repeat(_, _) => "Repeat",
+        AstType::Try(_, _, _) => "Try",
+        AstType::Assign(_, _) => "Assign",
+        AstType::MultiAssign(_, _) => "MultiAssign",
+        AstType::IndexAssign(_, _, _) => "IndexAssign",
+        AstType::BangEqual(_, _) => "BangEqual",
+        AstType::EqualEqual(_, _) => "EqualEqual",
+        AstType::And(_, _) => "And",
+        AstType::Or(_, _) => "Or",
+        AstType::Greater(_, _) => "Greater",
+        AstType::GreaterEqual(_, _) => "GreaterEqual",
+        AstType::Less(_, _) => "Less",
+        AstType::LessEqual(_, _) => "LessEqual",
+        AstType::In(_, _) => "In",
+        AstType::BitOr(_, _) => "BitOr",
+        AstType::BitXor(_, _) => "BitXor",
+        AstType::BitAnd(_, _) => "BitAnd",
+        AstType::ShiftLeft(_, _) => "ShiftLeft",
+        AstType::ShiftRight(_, _) => "ShiftRight",
+        AstType::Minus(_, _) => "Minus",
+        AstType::Plus(_, _) => "Plus",
+        AstType::Div(_, _) => "Div",
+        AstType::Mul(_, _) => "Mul",
+        AstType::Pow(_, _) => "Pow",
+        AstType::Bang(_) => "Bang",
+        AstType::UnaryMinus(_) => "UnaryMinus",
+        AstType::BitNot(_) => "BitNot",
+        AstType::Call(_, _) => "Call",
+        AstType::Index(_, _) => "Index",
+        AstType::OptionalGet(_, _) => "OptionalGet",
+        AstType::Grouping(_) => "Grouping",
+        AstType::List(_) => "List",
+        AstType::Map(_) => "Map",
+        AstType::Number(_) => "Number",
+        AstType::String(_) => "String",
+        AstType::True => "True",
+        AstType::False => "False",
+        AstType::Nil => "Nil",
+        AstType::Empty => "Empty",
+        AstType::Global(_) => "Global",
+        AstType::Identifier(_) => "Identifier",
+    }
+}
+
+/// JSONの文字列リテラルへ変換
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+
+    out
+}
+
+/// 定数畳み込み
+///
+/// リテラル同士の算術演算を評価前に計算しておく。実行時エラーとなる式(型不一致、0除算)は畳み込まない
+///
+/// # Arguments
+/// * `ast` - AST
+///
+/// # Return
+/// * AstType - 畳み込み後のAST
+pub fn fold(ast: AstType) -> AstType {
+    let fold_box = |ast: Box<AstType>| Box::new(fold(*ast));
+    let fold_vec = |asts: Vec<AstType>| asts.into_iter().map(fold).collect::<Vec<_>>();
+
+    match ast {
+        AstType::Plus(l, r) => match (fold(*l), fold(*r)) {
+            (AstType::Number(l), AstType::Number(r)) => AstType::Number(l + r),
+            (AstType::String(l), AstType::String(r)) => AstType::String(format!("{}{}", l, r)),
+            (l, r) => AstType::Plus(Box::new(l), Box::new(r)),
+        },
+        AstType::Minus(l, r) => match (fold(*l), fold(*r)) {
+            (AstType::Number(l), AstType::Number(r)) => AstType::Number(l - r),
+            (l, r) => AstType::Minus(Box::new(l), Box::new(r)),
+        },
+        AstType::Mul(l, r) => match (fold(*l), fold(*r)) {
+            (AstType::Number(l), AstType::Number(r)) => AstType::Number(l * r),
+            (l, r) => AstType::Mul(Box::new(l), Box::new(r)),
+        },
+        AstType::Div(l, r) => match (fold(*l), fold(*r)) {
+            (AstType::Number(l), AstType::Number(r)) if r != 0.0 => AstType::Number(l / r),
+            (l, r) => AstType::Div(Box::new(l), Box::new(r)),
+        },
+        AstType::Pow(l, r) => match (fold(*l), fold(*r)) {
+            (AstType::Number(l), AstType::Number(r)) => AstType::Number(l.powf(r)),
+            (l, r) => AstType::Pow(Box::new(l), Box::new(r)),
+        },
+        AstType::UnaryMinus(o) => match fold(*o) {
+            AstType::Number(n) => AstType::Number(-n),
+            o => AstType::UnaryMinus(Box::new(o)),
+        },
+        AstType::Grouping(o) => match fold(*o) {
+            o @ (AstType::Number(_) | AstType::String(_)) => o,
+            o => AstType::Grouping(Box::new(o)),
+        },
+        AstType::Var(i, init) => AstType::Var(i, init.map(fold_box)),
+        AstType::MultiVar(i, init) => AstType::MultiVar(i, fold_box(init)),
+        AstType::Fun(i, args, body) => AstType::Fun(i, args, fold_box(body)),
+        AstType::Lambda(args, body) => AstType::Lambda(args, fold_box(body)),
+        AstType::Print(o) => AstType::Print(fold_box(o)),
+        AstType::Block(stmts) => AstType::Block(fold_vec(stmts)),
+        AstType::While(cond, body, inc, els) => {
+            AstType::While(fold_box(cond), fold_box(body), fold_box(inc), fold_box(els))
+        }
+        AstType::If(cond, then, els) => AstType::If(fold_box(cond), fold_box(then), fold_box(els)),
+        AstType::Return(o) => AstType::Return(fold_box(o)),
+        AstType::Throw(o) => AstType::Throw(fold_box(o)),
+        AstType::Labeled(label, stmt) => AstType::Labeled(label, fold_box(stmt)),
+        AstType::Repeat(count, stmt) => AstType::Repeat(fold_box(count), fold_box(stmt)),
+        AstType::Try(try_block, name, catch_block) => {
+            AstType::Try(fold_box(try_block), name, fold_box(catch_block))
+        }
+        AstType::Assign(i, o) => AstType::Assign(i, fold_box(o)),
+        AstType::MultiAssign(i, exprs) => AstType::MultiAssign(i, fold_vec(exprs)),
+        AstType::IndexAssign(o, i, v) => {
+            AstType::IndexAssign(fold_box(o), fold_box(i), fold_box(v))
+        }
+        AstType::BangEqual(l, r) => AstType::BangEqual(fold_box(l), fold_box(r)),
+        AstType::EqualEqual(l, r) => AstType::EqualEqual(fold_box(l), fold_box(r)),
+        AstType::And(l, r) => AstType::And(fold_box(l), fold_box(r)),
+        AstType::Or(l, r) => AstType::Or(fold_box(l), fold_box(r)),
+        AstType::Greater(l, r) => AstType::Greater(fold_box(l), fold_box(r)),
+        AstType::GreaterEqual(l, r) => AstType::GreaterEqual(fold_box(l), fold_box(r)),
+        AstType::Less(l, r) => AstType::Less(fold_box(l), fold_box(r)),
+        AstType::LessEqual(l, r) => AstType::LessEqual(fold_box(l), fold_box(r)),
+        AstType::In(l, r) => AstType::In(fold_box(l), fold_box(r)),
+        AstType::BitOr(l, r) => AstType::BitOr(fold_box(l), fold_box(r)),
+        AstType::BitXor(l, r) => AstType::BitXor(fold_box(l), fold_box(r)),
+        AstType::BitAnd(l, r) => AstType::BitAnd(fold_box(l), fold_box(r)),
+        AstType::ShiftLeft(l, r) => AstType::ShiftLeft(fold_box(l), fold_box(r)),
+        AstType::ShiftRight(l, r) => AstType::ShiftRight(fold_box(l), fold_box(r)),
+        AstType::Bang(o) => AstType::Bang(fold_box(o)),
+        AstType::BitNot(o) => AstType::BitNot(fold_box(o)),
+        AstType::Call(i, args) => AstType::Call(i, fold_vec(args)),
+        AstType::Index(o, i) => AstType::Index(fold_box(o), fold_box(i)),
+        AstType::OptionalGet(o, name) => AstType::OptionalGet(fold_box(o), name),
+        AstType::List(elements) => AstType::List(fold_vec(elements)),
+        AstType::Map(entries) => AstType::Map(
+            entries
+                .into_iter()
+                .map(|(k, v)| (fold(k), fold(v)))
+                .collect(),
+        ),
+        AstType::Break(_)
+        | AstType::Continue(_)
+        | AstType::Number(_)
+        | AstType::String(_)
+        | AstType::True
+        | AstType::False
+        | AstType::Nil
+        | AstType::Empty
+        | AstType::Global(_)
+        | AstType::Identifier(_) => ast,
+    }
+}
+
+/// 二項演算子の優先順位とノード生成関数
+///
+/// # Arguments
+/// * `token_type` - トークン種別
+///
+/// # Return
+/// * Option<(usize, BinaryOperator)> - 優先順位(値が大きいほど優先)とノード生成関数。二項演算子以外はNone
+fn binary_operator(token_type: &TokenType) -> Option<(usize, BinaryOperator)> {
+    let operator: (usize, BinaryOperator) = match token_type {
+        TokenType::Or => (0, AstType::Or),
+        TokenType::And => (1, AstType::And),
+        TokenType::BangEqual => (2, AstType::BangEqual),
+        TokenType::EqualEqual => (2, AstType::EqualEqual),
+        TokenType::Greater => (3, AstType::Greater),
+        TokenType::GreaterEqual => (3, AstType::GreaterEqual),
+        TokenType::Less => (3, AstType::Less),
+        TokenType::LessEqual => (3, AstType::LessEqual),
+        TokenType::In => (3, AstType::In),
+        TokenType::Pipe => (4, AstType::BitOr),
+        TokenType::Caret => (5, AstType::BitXor),
+        TokenType::Ampersand => (6, AstType::BitAnd),
+        TokenType::LessLess => (7, AstType::ShiftLeft),
+        TokenType::GreaterGreater => (7, AstType::ShiftRight),
+        TokenType::Minus => (8, AstType::Minus),
+        TokenType::Plus => (8, AstType::Plus),
+        TokenType::Slash => (9, AstType::Div),
+        TokenType::Star => (9, AstType::Mul),
+        _ => return None,
+    };
+
+    Some(operator)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::scanner::Scanner;
 
     #[test]
     fn 終端記号_parse() {
         let tokens = vec![
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(AstType::Number(1.0), parser.program()[0]);
 
         let tokens = vec![
-            Token::new(TokenType::String(String::from("test")), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::String(String::from("test")), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(AstType::String(String::from("test")), parser.program()[0]);
 
         let tokens = vec![
-            Token::new(TokenType::True, None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::True, None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(AstType::True, parser.program()[0]);
 
         let tokens = vec![
-            Token::new(TokenType::False, None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::False, None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(AstType::False, parser.program()[0]);
 
         let tokens = vec![
-            Token::new(TokenType::Nil, None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Nil, None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(AstType::Nil, parser.program()[0]);
     }
 
+    #[test]
+    fn pass_parse() {
+        let tokens = Scanner::new(&"pass;".to_string()).scan().unwrap();
+        let mut parser = Parser::new(&tokens);
+        assert_eq!(vec![AstType::Empty], parser.program());
+        assert!(parser.errors().is_empty());
+
+        // ブロック末尾ではセミコロンを省略できる
+        let tokens = Scanner::new(&"fun todo() { pass }".to_string())
+            .scan()
+            .unwrap();
+        let mut parser = Parser::new(&tokens);
+        assert_eq!(
+            vec![AstType::Fun(
+                "todo".to_string(),
+                vec![],
+                Box::new(AstType::Block(vec![AstType::Empty]))
+            )],
+            parser.program()
+        );
+        assert!(parser.errors().is_empty());
+
+        // 予約語のため変数名には使用できない
+        let tokens = Scanner::new(&"var pass = 1;".to_string()).scan().unwrap();
+        let mut parser = Parser::new(&tokens);
+        parser.program();
+        assert_eq!(
+            "'pass' is a reserved word and cannot be used as a name",
+            parser.errors()[0].message
+        );
+    }
+
     #[test]
     fn グルーピング_parse() {
         let tokens = vec![
-            Token::new(TokenType::LeftParen, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::RightParen, None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::LeftParen, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::RightParen, None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -987,9 +2048,9 @@ mod test {
     #[test]
     fn unary_parse() {
         let tokens = vec![
-            Token::new(TokenType::Bang, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Bang, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -997,24 +2058,34 @@ mod test {
             parser.program()[0]
         );
         let tokens = vec![
-            Token::new(TokenType::Minus, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Minus, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
             AstType::UnaryMinus(Box::new(AstType::Number(1.0))),
             parser.program()[0]
         );
+        let tokens = vec![
+            Token::new(TokenType::Tilde, None, 0, 0, 0),
+            Token::new(TokenType::Number(5.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
+        ];
+        let mut parser = Parser::new(&tokens);
+        assert_eq!(
+            AstType::BitNot(Box::new(AstType::Number(5.0))),
+            parser.program()[0]
+        );
     }
 
     #[test]
     fn factor_parse() {
         let tokens = vec![
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::Slash, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::Slash, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1026,10 +2097,10 @@ mod test {
         );
 
         let tokens = vec![
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::Star, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::Star, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1041,12 +2112,12 @@ mod test {
         );
 
         let tokens = vec![
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::Star, None, 0, 0),
-            Token::new(TokenType::Number(3.0), None, 0, 0),
-            Token::new(TokenType::Slash, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::Star, None, 0, 0, 0),
+            Token::new(TokenType::Number(3.0), None, 0, 0, 0),
+            Token::new(TokenType::Slash, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1062,27 +2133,69 @@ mod test {
     }
 
     #[test]
-    fn term_parse() {
-        let tokens = vec![
-            Token::new(TokenType::String(String::from("a")), None, 0, 0),
-            Token::new(TokenType::Plus, None, 0, 0),
-            Token::new(TokenType::String(String::from("b")), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
-        ];
+    fn power_parse() {
+        // 単項演算子よりべき乗の優先順位が高い
+        let tokens = Scanner::new(&"-2 ** 2;".to_string()).scan().unwrap();
         let mut parser = Parser::new(&tokens);
         assert_eq!(
-            AstType::Plus(
-                Box::new(AstType::String(String::from("a"))),
-                Box::new(AstType::String(String::from("b")))
-            ),
+            AstType::UnaryMinus(Box::new(AstType::Pow(
+                Box::new(AstType::Number(2.0)),
+                Box::new(AstType::Number(2.0))
+            ))),
             parser.program()[0]
         );
 
-        let tokens = vec![
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::Plus, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+        // 右結合で、右辺は単項演算子を許容する
+        let tokens = Scanner::new(&"2 ** 3 ** -1;".to_string()).scan().unwrap();
+        let mut parser = Parser::new(&tokens);
+        assert_eq!(
+            AstType::Pow(
+                Box::new(AstType::Number(2.0)),
+                Box::new(AstType::Pow(
+                    Box::new(AstType::Number(3.0)),
+                    Box::new(AstType::UnaryMinus(Box::new(AstType::Number(1.0))))
+                ))
+            ),
+            parser.program()[0]
+        );
+
+        // 積算よりべき乗の優先順位が高い
+        let tokens = Scanner::new(&"2 * 3 ** 2;".to_string()).scan().unwrap();
+        let mut parser = Parser::new(&tokens);
+        assert_eq!(
+            AstType::Mul(
+                Box::new(AstType::Number(2.0)),
+                Box::new(AstType::Pow(
+                    Box::new(AstType::Number(3.0)),
+                    Box::new(AstType::Number(2.0))
+                ))
+            ),
+            parser.program()[0]
+        );
+    }
+
+    #[test]
+    fn term_parse() {
+        let tokens = vec![
+            Token::new(TokenType::String(String::from("a")), None, 0, 0, 0),
+            Token::new(TokenType::Plus, None, 0, 0, 0),
+            Token::new(TokenType::String(String::from("b")), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
+        ];
+        let mut parser = Parser::new(&tokens);
+        assert_eq!(
+            AstType::Plus(
+                Box::new(AstType::String(String::from("a"))),
+                Box::new(AstType::String(String::from("b")))
+            ),
+            parser.program()[0]
+        );
+
+        let tokens = vec![
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::Plus, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1094,10 +2207,10 @@ mod test {
         );
 
         let tokens = vec![
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::Minus, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::Minus, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1109,12 +2222,12 @@ mod test {
         );
 
         let tokens = vec![
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::Plus, None, 0, 0),
-            Token::new(TokenType::Number(3.0), None, 0, 0),
-            Token::new(TokenType::Minus, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::Plus, None, 0, 0, 0),
+            Token::new(TokenType::Number(3.0), None, 0, 0, 0),
+            Token::new(TokenType::Minus, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1129,12 +2242,12 @@ mod test {
         );
 
         let tokens = vec![
-            Token::new(TokenType::Number(10.0), None, 0, 0),
-            Token::new(TokenType::Minus, None, 0, 0),
-            Token::new(TokenType::Number(3.0), None, 0, 0),
-            Token::new(TokenType::Minus, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Number(10.0), None, 0, 0, 0),
+            Token::new(TokenType::Minus, None, 0, 0, 0),
+            Token::new(TokenType::Number(3.0), None, 0, 0, 0),
+            Token::new(TokenType::Minus, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1152,12 +2265,12 @@ mod test {
     #[test]
     fn 四則演算混合_parse() {
         let tokens = vec![
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::Plus, None, 0, 0),
-            Token::new(TokenType::Number(3.0), None, 0, 0),
-            Token::new(TokenType::Star, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::Plus, None, 0, 0, 0),
+            Token::new(TokenType::Number(3.0), None, 0, 0, 0),
+            Token::new(TokenType::Star, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1172,12 +2285,12 @@ mod test {
         );
 
         let tokens = vec![
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::Slash, None, 0, 0),
-            Token::new(TokenType::Number(3.0), None, 0, 0),
-            Token::new(TokenType::Minus, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::Slash, None, 0, 0, 0),
+            Token::new(TokenType::Number(3.0), None, 0, 0, 0),
+            Token::new(TokenType::Minus, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1195,10 +2308,10 @@ mod test {
     #[test]
     fn comparison_parse() {
         let tokens = vec![
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::Greater, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::Greater, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1210,10 +2323,10 @@ mod test {
         );
 
         let tokens = vec![
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::GreaterEqual, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::GreaterEqual, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1225,10 +2338,10 @@ mod test {
         );
 
         let tokens = vec![
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::Less, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::Less, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1240,10 +2353,10 @@ mod test {
         );
 
         let tokens = vec![
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::LessEqual, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::LessEqual, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1255,14 +2368,14 @@ mod test {
         );
 
         let tokens = vec![
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::Plus, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::Greater, None, 0, 0),
-            Token::new(TokenType::Number(3.0), None, 0, 0),
-            Token::new(TokenType::Star, None, 0, 0),
-            Token::new(TokenType::Number(4.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::Plus, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::Greater, None, 0, 0, 0),
+            Token::new(TokenType::Number(3.0), None, 0, 0, 0),
+            Token::new(TokenType::Star, None, 0, 0, 0),
+            Token::new(TokenType::Number(4.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1283,10 +2396,10 @@ mod test {
     #[test]
     fn equality_parse() {
         let tokens = vec![
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::EqualEqual, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::EqualEqual, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1298,10 +2411,10 @@ mod test {
         );
 
         let tokens = vec![
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::BangEqual, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::BangEqual, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1313,14 +2426,14 @@ mod test {
         );
 
         let tokens = vec![
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::Plus, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::EqualEqual, None, 0, 0),
-            Token::new(TokenType::Number(3.0), None, 0, 0),
-            Token::new(TokenType::Star, None, 0, 0),
-            Token::new(TokenType::Number(4.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::Plus, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::EqualEqual, None, 0, 0, 0),
+            Token::new(TokenType::Number(3.0), None, 0, 0, 0),
+            Token::new(TokenType::Star, None, 0, 0, 0),
+            Token::new(TokenType::Number(4.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1341,11 +2454,11 @@ mod test {
     #[test]
     fn synchronize() {
         let tokens = vec![
-            Token::new(TokenType::LeftParen, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
-            Token::new(TokenType::Number(8.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::LeftParen, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
+            Token::new(TokenType::Number(8.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
 
@@ -1356,10 +2469,10 @@ mod test {
     #[test]
     fn 複数行_parse() {
         let tokens = vec![
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 1, 0),
-            Token::new(TokenType::Number(2.0), None, 2, 0),
-            Token::new(TokenType::SemiColon, None, 1, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 1, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 2, 0, 0),
+            Token::new(TokenType::SemiColon, None, 1, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         let result = parser.program();
@@ -1369,7 +2482,7 @@ mod test {
 
     #[test]
     fn 文末にセミコロンがない_parse() {
-        let tokens = vec![Token::new(TokenType::Number(1.0), None, 0, 0)];
+        let tokens = vec![Token::new(TokenType::Number(1.0), None, 0, 0, 0)];
         let mut parser = Parser::new(&tokens);
         let result = parser.program();
         assert_eq!(0, result.len());
@@ -1378,9 +2491,9 @@ mod test {
     #[test]
     fn print_parse() {
         let tokens = vec![
-            Token::new(TokenType::Print, None, 0, 0),
-            Token::new(TokenType::String(String::from("test")), None, 1, 0),
-            Token::new(TokenType::SemiColon, None, 2, 0),
+            Token::new(TokenType::Print, None, 0, 0, 0),
+            Token::new(TokenType::String(String::from("test")), None, 1, 0, 0),
+            Token::new(TokenType::SemiColon, None, 2, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1392,69 +2505,69 @@ mod test {
     #[test]
     fn identifier_parse() {
         let tokens = vec![
-            Token::new(TokenType::Var, None, 0, 0),
-            Token::new(TokenType::Identifier(String::from("test")), None, 0, 0),
-            Token::new(TokenType::Equal, None, 0, 0),
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Var, None, 0, 0, 0),
+            Token::new(TokenType::Identifier(String::from("test")), None, 0, 0, 0),
+            Token::new(TokenType::Equal, None, 0, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
-            AstType::Var(String::from("test"), Box::new(AstType::Number(2.0))),
+            AstType::Var(String::from("test"), Some(Box::new(AstType::Number(2.0)))),
             parser.program()[0]
         );
 
         let tokens = vec![
-            Token::new(TokenType::Var, None, 0, 0),
-            Token::new(TokenType::Identifier(String::from("test")), None, 0, 0),
-            Token::new(TokenType::Equal, None, 0, 0),
-            Token::new(TokenType::String("Hello".to_owned()), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Var, None, 0, 0, 0),
+            Token::new(TokenType::Identifier(String::from("test")), None, 0, 0, 0),
+            Token::new(TokenType::Equal, None, 0, 0, 0),
+            Token::new(TokenType::String("Hello".to_owned()), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
             AstType::Var(
                 String::from("test"),
-                Box::new(AstType::String("Hello".to_owned()))
+                Some(Box::new(AstType::String("Hello".to_owned())))
             ),
             parser.program()[0]
         );
 
         let tokens = vec![
-            Token::new(TokenType::Var, None, 0, 0),
-            Token::new(TokenType::Identifier(String::from("test")), None, 0, 0),
-            Token::new(TokenType::Equal, None, 0, 0),
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::Plus, None, 0, 0),
-            Token::new(TokenType::Number(3.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Var, None, 0, 0, 0),
+            Token::new(TokenType::Identifier(String::from("test")), None, 0, 0, 0),
+            Token::new(TokenType::Equal, None, 0, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::Plus, None, 0, 0, 0),
+            Token::new(TokenType::Number(3.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
             AstType::Var(
                 String::from("test"),
-                Box::new(AstType::Plus(
+                Some(Box::new(AstType::Plus(
                     Box::new(AstType::Number(2.0)),
                     Box::new(AstType::Number(3.0)),
-                ))
+                )))
             ),
             parser.program()[0]
         );
 
         let tokens = vec![
-            Token::new(TokenType::Var, None, 0, 0),
-            Token::new(TokenType::Identifier(String::from("a")), None, 0, 0),
-            Token::new(TokenType::Equal, None, 0, 0),
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
-            Token::new(TokenType::Print, None, 0, 0),
-            Token::new(TokenType::Identifier("a".to_string()), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Var, None, 0, 0, 0),
+            Token::new(TokenType::Identifier(String::from("a")), None, 0, 0, 0),
+            Token::new(TokenType::Equal, None, 0, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
+            Token::new(TokenType::Print, None, 0, 0, 0),
+            Token::new(TokenType::Identifier("a".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         let result = parser.program();
         assert_eq!(
-            AstType::Var(String::from("a"), Box::new(AstType::Number(2.0))),
+            AstType::Var(String::from("a"), Some(Box::new(AstType::Number(2.0)))),
             result[0]
         );
         assert_eq!(
@@ -1466,10 +2579,10 @@ mod test {
     #[test]
     fn assign_parse() {
         let tokens = vec![
-            Token::new(TokenType::Identifier("test".to_string()), None, 0, 0),
-            Token::new(TokenType::Equal, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Identifier("test".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::Equal, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1478,12 +2591,12 @@ mod test {
         );
 
         let tokens = vec![
-            Token::new(TokenType::Identifier("test".to_string()), None, 0, 0),
-            Token::new(TokenType::Equal, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::Plus, None, 0, 0),
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Identifier("test".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::Equal, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::Plus, None, 0, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1498,15 +2611,116 @@ mod test {
         );
     }
 
+    #[test]
+    fn try_parse() {
+        let tokens = Scanner::new(&"try { 1; } catch (e) { print e; }".to_string())
+            .scan()
+            .unwrap();
+        let mut parser = Parser::new(&tokens);
+        assert_eq!(
+            vec![AstType::Try(
+                Box::new(AstType::Block(vec![AstType::Number(1.0)])),
+                String::from("e"),
+                Box::new(AstType::Block(vec![AstType::Print(Box::new(
+                    AstType::Identifier(String::from("e"))
+                ))])),
+            )],
+            parser.program()
+        );
+
+        for source in [
+            "try { 1; }",
+            "try 1; catch (e) {}",
+            "try {} catch {}",
+            "try {} catch (1) {}",
+        ] {
+            let tokens = Scanner::new(&source.to_string()).scan().unwrap();
+            let mut parser = Parser::new(&tokens);
+            parser.program();
+            assert!(!parser.errors().is_empty(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn throw_parse() {
+        let tokens = Scanner::new(&"throw \"a\" + b;".to_string())
+            .scan()
+            .unwrap();
+        let mut parser = Parser::new(&tokens);
+        assert_eq!(
+            vec![AstType::Throw(Box::new(AstType::Plus(
+                Box::new(AstType::String(String::from("a"))),
+                Box::new(AstType::Identifier(String::from("b")))
+            )))],
+            parser.program()
+        );
+
+        for source in ["throw;", "throw 1"] {
+            let tokens = Scanner::new(&source.to_string()).scan().unwrap();
+            let mut parser = Parser::new(&tokens);
+            parser.program();
+            assert_eq!(1, parser.errors().len(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn global_parse() {
+        let tokens = Scanner::new(&"global a, b;".to_string()).scan().unwrap();
+        let mut parser = Parser::new(&tokens);
+        assert_eq!(
+            vec![AstType::Global(vec![String::from("a"), String::from("b")])],
+            parser.program()
+        );
+
+        for source in ["global;", "global a", "global 1;"] {
+            let tokens = Scanner::new(&source.to_string()).scan().unwrap();
+            let mut parser = Parser::new(&tokens);
+            parser.program();
+            assert_eq!(1, parser.errors().len(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn 複合代入_parse() {
+        let tokens = Scanner::new(&"a += 1; a -= 2; a *= 3; a /= 4;".to_string())
+            .scan()
+            .unwrap();
+        let mut parser = Parser::new(&tokens);
+        let compound = |op: fn(Box<AstType>, Box<AstType>) -> AstType, n: f64| {
+            AstType::Assign(
+                String::from("a"),
+                Box::new(op(
+                    Box::new(AstType::Identifier(String::from("a"))),
+                    Box::new(AstType::Number(n)),
+                )),
+            )
+        };
+        assert_eq!(
+            vec![
+                compound(AstType::Plus, 1.0),
+                compound(AstType::Minus, 2.0),
+                compound(AstType::Mul, 3.0),
+                compound(AstType::Div, 4.0),
+            ],
+            parser.program()
+        );
+
+        // 変数以外には複合代入できない
+        let tokens = Scanner::new(&"a[0] += 1;".to_string()).scan().unwrap();
+        let mut parser = Parser::new(&tokens);
+        parser.program();
+        assert_eq!(1, parser.errors().len());
+    }
+
     #[test]
     fn block_parse() {
         let tokens = vec![
-            Token::new(TokenType::LeftBrace, None, 0, 0),
-            Token::new(TokenType::Identifier("test".to_string()), None, 0, 0),
-            Token::new(TokenType::Equal, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
-            Token::new(TokenType::RightBrace, None, 0, 0),
+            Token::new(TokenType::LeftBrace, None, 0, 0, 0),
+            Token::new(TokenType::Identifier("test".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::Equal, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
+            Token::new(TokenType::RightBrace, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1521,15 +2735,15 @@ mod test {
     #[test]
     fn if_parse() {
         let tokens = vec![
-            Token::new(TokenType::If, None, 0, 0),
-            Token::new(TokenType::LeftParen, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::RightParen, None, 0, 0),
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
-            Token::new(TokenType::Else, None, 0, 0),
-            Token::new(TokenType::Number(3.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::If, None, 0, 0, 0),
+            Token::new(TokenType::LeftParen, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::RightParen, None, 0, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
+            Token::new(TokenType::Else, None, 0, 0, 0),
+            Token::new(TokenType::Number(3.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1542,12 +2756,12 @@ mod test {
         );
 
         let tokens = vec![
-            Token::new(TokenType::If, None, 0, 0),
-            Token::new(TokenType::LeftParen, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::RightParen, None, 0, 0),
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::If, None, 0, 0, 0),
+            Token::new(TokenType::LeftParen, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::RightParen, None, 0, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1560,14 +2774,14 @@ mod test {
         );
 
         let tokens = vec![
-            Token::new(TokenType::If, None, 0, 0),
-            Token::new(TokenType::LeftParen, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::Less, None, 0, 0),
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::RightParen, None, 0, 0),
-            Token::new(TokenType::Number(3.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::If, None, 0, 0, 0),
+            Token::new(TokenType::LeftParen, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::Less, None, 0, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::RightParen, None, 0, 0, 0),
+            Token::new(TokenType::Number(3.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1583,13 +2797,43 @@ mod test {
         );
     }
 
+    #[test]
+    fn if_expression_parse() {
+        let tokens = Scanner::new(&"var m = if (a > b) a else b;".to_string())
+            .scan()
+            .unwrap();
+        let mut parser = Parser::new(&tokens);
+        assert_eq!(
+            AstType::Var(
+                "m".to_string(),
+                Some(Box::new(AstType::If(
+                    Box::new(AstType::Greater(
+                        Box::new(AstType::Identifier("a".to_string())),
+                        Box::new(AstType::Identifier("b".to_string()))
+                    )),
+                    Box::new(AstType::Identifier("a".to_string())),
+                    Box::new(AstType::Identifier("b".to_string()))
+                )))
+            ),
+            parser.program()[0]
+        );
+
+        // 式の位置ではelseは必須
+        let tokens = Scanner::new(&"var m = if (true) 1;".to_string())
+            .scan()
+            .unwrap();
+        let mut parser = Parser::new(&tokens);
+        parser.program();
+        assert_eq!(1, parser.errors().len());
+    }
+
     #[test]
     fn or_parse() {
         let tokens = vec![
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::Or, None, 0, 0),
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::Or, None, 0, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1601,12 +2845,12 @@ mod test {
         );
 
         let tokens = vec![
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::Or, None, 0, 0),
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::Or, None, 0, 0),
-            Token::new(TokenType::Number(3.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::Or, None, 0, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::Or, None, 0, 0, 0),
+            Token::new(TokenType::Number(3.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1624,10 +2868,10 @@ mod test {
     #[test]
     fn and_parse() {
         let tokens = vec![
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::And, None, 0, 0),
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::And, None, 0, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1639,12 +2883,12 @@ mod test {
         );
 
         let tokens = vec![
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::And, None, 0, 0),
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::And, None, 0, 0),
-            Token::new(TokenType::Number(3.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::And, None, 0, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::And, None, 0, 0, 0),
+            Token::new(TokenType::Number(3.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1662,12 +2906,12 @@ mod test {
     #[test]
     fn or_and_parse() {
         let tokens = vec![
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::Or, None, 0, 0),
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::And, None, 0, 0),
-            Token::new(TokenType::Number(3.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::Or, None, 0, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::And, None, 0, 0, 0),
+            Token::new(TokenType::Number(3.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1685,18 +2929,104 @@ mod test {
     #[test]
     fn while_parse() {
         let tokens = vec![
-            Token::new(TokenType::While, None, 0, 0),
-            Token::new(TokenType::LeftParen, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::RightParen, None, 0, 0),
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::While, None, 0, 0, 0),
+            Token::new(TokenType::LeftParen, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::RightParen, None, 0, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
             AstType::While(
                 Box::new(AstType::Number(1.0)),
                 Box::new(AstType::Number(2.0)),
+                Box::new(AstType::Nil),
+                Box::new(AstType::Nil),
+            ),
+            parser.program()[0]
+        );
+    }
+
+    #[test]
+    fn while_else_parse() {
+        let tokens = Scanner::new(&"while (false) print 1; else print 2;".to_string())
+            .scan()
+            .unwrap();
+        let mut parser = Parser::new(&tokens);
+        assert_eq!(
+            vec![AstType::While(
+                Box::new(AstType::False),
+                Box::new(AstType::Print(Box::new(AstType::Number(1.0)))),
+                Box::new(AstType::Nil),
+                Box::new(AstType::Print(Box::new(AstType::Number(2.0)))),
+            )],
+            parser.program()
+        );
+        assert!(parser.errors().is_empty());
+        // ノードの位置は子ノードの順に記録される
+        assert_eq!(7, parser.spans()[0].len());
+    }
+
+    #[test]
+    fn repeat_parse() {
+        let tokens = Scanner::new(&"repeat (1 + 2) { print 1; }".to_string())
+            .scan()
+            .unwrap();
+        let mut parser = Parser::new(&tokens);
+        let ast = parser.program();
+        assert!(parser.errors().is_empty());
+        assert_eq!(
+            vec![AstType::Repeat(
+                Box::new(AstType::Plus(
+                    Box::new(AstType::Number(1.0)),
+                    Box::new(AstType::Number(2.0))
+                )),
+                Box::new(AstType::Block(vec![AstType::Print(Box::new(
+                    AstType::Number(1.0)
+                ))]))
+            )],
+            ast
+        );
+
+        let tokens = Scanner::new(&"repeat 3 print 1;".to_string())
+            .scan()
+            .unwrap();
+        let mut parser = Parser::new(&tokens);
+        parser.program();
+        assert_eq!(1, parser.errors().len());
+    }
+
+    #[test]
+    fn ラベル付きループ_parse() {
+        let tokens = vec![
+            Token::new(TokenType::Identifier("outer".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::Colon, None, 0, 0, 0),
+            Token::new(TokenType::While, None, 0, 0, 0),
+            Token::new(TokenType::LeftParen, None, 0, 0, 0),
+            Token::new(TokenType::True, None, 0, 0, 0),
+            Token::new(TokenType::RightParen, None, 0, 0, 0),
+            Token::new(TokenType::LeftBrace, None, 0, 0, 0),
+            Token::new(TokenType::Break, None, 0, 0, 0),
+            Token::new(TokenType::Identifier("outer".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
+            Token::new(TokenType::Continue, None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
+            Token::new(TokenType::RightBrace, None, 0, 0, 0),
+        ];
+        let mut parser = Parser::new(&tokens);
+        assert_eq!(
+            AstType::Labeled(
+                "outer".to_string(),
+                Box::new(AstType::While(
+                    Box::new(AstType::True),
+                    Box::new(AstType::Block(vec![
+                        AstType::Break(Some("outer".to_string())),
+                        AstType::Continue(None),
+                    ])),
+                    Box::new(AstType::Nil),
+                    Box::new(AstType::Nil),
+                ))
             ),
             parser.program()[0]
         );
@@ -1705,46 +3035,47 @@ mod test {
     #[test]
     fn for_parse() {
         let tokens = vec![
-            Token::new(TokenType::For, None, 0, 0),
-            Token::new(TokenType::LeftParen, None, 0, 0),
-            Token::new(TokenType::Var, None, 0, 0),
-            Token::new(TokenType::Identifier("a".to_string()), None, 0, 0),
-            Token::new(TokenType::Equal, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
-            Token::new(TokenType::Identifier("a".to_string()), None, 0, 0),
-            Token::new(TokenType::Less, None, 0, 0),
-            Token::new(TokenType::Number(10.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
-            Token::new(TokenType::Identifier("a".to_string()), None, 0, 0),
-            Token::new(TokenType::Equal, None, 0, 0),
-            Token::new(TokenType::Identifier("a".to_string()), None, 0, 0),
-            Token::new(TokenType::Plus, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::RightParen, None, 0, 0),
-            Token::new(TokenType::Print, None, 0, 0),
-            Token::new(TokenType::String("Hello".to_string()), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(TokenType::For, None, 0, 0, 0),
+            Token::new(TokenType::LeftParen, None, 0, 0, 0),
+            Token::new(TokenType::Var, None, 0, 0, 0),
+            Token::new(TokenType::Identifier("a".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::Equal, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
+            Token::new(TokenType::Identifier("a".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::Less, None, 0, 0, 0),
+            Token::new(TokenType::Number(10.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
+            Token::new(TokenType::Identifier("a".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::Equal, None, 0, 0, 0),
+            Token::new(TokenType::Identifier("a".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::Plus, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::RightParen, None, 0, 0, 0),
+            Token::new(TokenType::Print, None, 0, 0, 0),
+            Token::new(TokenType::String("Hello".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
             AstType::Block(vec![
-                AstType::Var("a".to_string(), Box::new(AstType::Number(1.0))),
+                AstType::Var("a".to_string(), Some(Box::new(AstType::Number(1.0)))),
                 AstType::While(
                     Box::new(AstType::Less(
                         Box::new(AstType::Identifier("a".to_string())),
                         Box::new(AstType::Number(10.0))
                     )),
-                    Box::new(AstType::Block(vec![
-                        AstType::Print(Box::new(AstType::String("Hello".to_string()))),
-                        AstType::Assign(
-                            "a".to_string(),
-                            Box::new(AstType::Plus(
-                                Box::new(AstType::Identifier("a".to_string())),
-                                Box::new(AstType::Number(1.0))
-                            ))
-                        )
-                    ]))
+                    Box::new(AstType::Print(Box::new(AstType::String(
+                        "Hello".to_string()
+                    )))),
+                    Box::new(AstType::Assign(
+                        "a".to_string(),
+                        Box::new(AstType::Plus(
+                            Box::new(AstType::Identifier("a".to_string())),
+                            Box::new(AstType::Number(1.0))
+                        ))
+                    )),
+                    Box::new(AstType::Nil),
                 )
             ]),
             parser.program()[0]
@@ -1754,13 +3085,19 @@ mod test {
     #[test]
     fn 関数コール_parse() {
         let tokens = vec![
-            Token::new(TokenType::Identifier("test_func".to_string()), None, 0, 0),
-            Token::new(TokenType::LeftParen, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::Comma, None, 0, 0),
-            Token::new(TokenType::Number(2.0), None, 0, 0),
-            Token::new(TokenType::RightParen, None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(
+                TokenType::Identifier("test_func".to_string()),
+                None,
+                0,
+                0,
+                0,
+            ),
+            Token::new(TokenType::LeftParen, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::Comma, None, 0, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::RightParen, None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1772,10 +3109,16 @@ mod test {
         );
 
         let tokens = vec![
-            Token::new(TokenType::Identifier("test_func".to_string()), None, 0, 0),
-            Token::new(TokenType::LeftParen, None, 0, 0),
-            Token::new(TokenType::RightParen, None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
+            Token::new(
+                TokenType::Identifier("test_func".to_string()),
+                None,
+                0,
+                0,
+                0,
+            ),
+            Token::new(TokenType::LeftParen, None, 0, 0, 0),
+            Token::new(TokenType::RightParen, None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1787,12 +3130,18 @@ mod test {
     #[test]
     fn 関数定義_parse() {
         let tokens = vec![
-            Token::new(TokenType::Fun, None, 0, 0),
-            Token::new(TokenType::Identifier("test_func".to_string()), None, 0, 0),
-            Token::new(TokenType::LeftParen, None, 0, 0),
-            Token::new(TokenType::RightParen, None, 0, 0),
-            Token::new(TokenType::LeftBrace, None, 0, 0),
-            Token::new(TokenType::RightBrace, None, 0, 0),
+            Token::new(TokenType::Fun, None, 0, 0, 0),
+            Token::new(
+                TokenType::Identifier("test_func".to_string()),
+                None,
+                0,
+                0,
+                0,
+            ),
+            Token::new(TokenType::LeftParen, None, 0, 0, 0),
+            Token::new(TokenType::RightParen, None, 0, 0, 0),
+            Token::new(TokenType::LeftBrace, None, 0, 0, 0),
+            Token::new(TokenType::RightBrace, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1805,15 +3154,21 @@ mod test {
         );
 
         let tokens = vec![
-            Token::new(TokenType::Fun, None, 0, 0),
-            Token::new(TokenType::Identifier("test_func".to_string()), None, 0, 0),
-            Token::new(TokenType::LeftParen, None, 0, 0),
-            Token::new(TokenType::RightParen, None, 0, 0),
-            Token::new(TokenType::LeftBrace, None, 0, 0),
-            Token::new(TokenType::Print, None, 0, 0),
-            Token::new(TokenType::String(String::from("test")), None, 1, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
-            Token::new(TokenType::RightBrace, None, 0, 0),
+            Token::new(TokenType::Fun, None, 0, 0, 0),
+            Token::new(
+                TokenType::Identifier("test_func".to_string()),
+                None,
+                0,
+                0,
+                0,
+            ),
+            Token::new(TokenType::LeftParen, None, 0, 0, 0),
+            Token::new(TokenType::RightParen, None, 0, 0, 0),
+            Token::new(TokenType::LeftBrace, None, 0, 0, 0),
+            Token::new(TokenType::Print, None, 0, 0, 0),
+            Token::new(TokenType::String(String::from("test")), None, 1, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
+            Token::new(TokenType::RightBrace, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1828,15 +3183,21 @@ mod test {
         );
 
         let tokens = vec![
-            Token::new(TokenType::Fun, None, 0, 0),
-            Token::new(TokenType::Identifier("test_func".to_string()), None, 0, 0),
-            Token::new(TokenType::LeftParen, None, 0, 0),
-            Token::new(TokenType::Identifier("a".to_string()), None, 0, 0),
-            Token::new(TokenType::Comma, None, 0, 0),
-            Token::new(TokenType::Identifier("b".to_string()), None, 0, 0),
-            Token::new(TokenType::RightParen, None, 0, 0),
-            Token::new(TokenType::LeftBrace, None, 0, 0),
-            Token::new(TokenType::RightBrace, None, 0, 0),
+            Token::new(TokenType::Fun, None, 0, 0, 0),
+            Token::new(
+                TokenType::Identifier("test_func".to_string()),
+                None,
+                0,
+                0,
+                0,
+            ),
+            Token::new(TokenType::LeftParen, None, 0, 0, 0),
+            Token::new(TokenType::Identifier("a".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::Comma, None, 0, 0, 0),
+            Token::new(TokenType::Identifier("b".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::RightParen, None, 0, 0, 0),
+            Token::new(TokenType::LeftBrace, None, 0, 0, 0),
+            Token::new(TokenType::RightBrace, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1855,15 +3216,21 @@ mod test {
     #[test]
     fn return_parse() {
         let tokens = vec![
-            Token::new(TokenType::Fun, None, 0, 0),
-            Token::new(TokenType::Identifier("test_func".to_string()), None, 0, 0),
-            Token::new(TokenType::LeftParen, None, 0, 0),
-            Token::new(TokenType::RightParen, None, 0, 0),
-            Token::new(TokenType::LeftBrace, None, 0, 0),
-            Token::new(TokenType::Return, None, 0, 0),
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
-            Token::new(TokenType::RightBrace, None, 0, 0),
+            Token::new(TokenType::Fun, None, 0, 0, 0),
+            Token::new(
+                TokenType::Identifier("test_func".to_string()),
+                None,
+                0,
+                0,
+                0,
+            ),
+            Token::new(TokenType::LeftParen, None, 0, 0, 0),
+            Token::new(TokenType::RightParen, None, 0, 0, 0),
+            Token::new(TokenType::LeftBrace, None, 0, 0, 0),
+            Token::new(TokenType::Return, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
+            Token::new(TokenType::RightBrace, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1878,14 +3245,20 @@ mod test {
         );
 
         let tokens = vec![
-            Token::new(TokenType::Fun, None, 0, 0),
-            Token::new(TokenType::Identifier("test_func".to_string()), None, 0, 0),
-            Token::new(TokenType::LeftParen, None, 0, 0),
-            Token::new(TokenType::RightParen, None, 0, 0),
-            Token::new(TokenType::LeftBrace, None, 0, 0),
-            Token::new(TokenType::Return, None, 0, 0),
-            Token::new(TokenType::SemiColon, None, 0, 0),
-            Token::new(TokenType::RightBrace, None, 0, 0),
+            Token::new(TokenType::Fun, None, 0, 0, 0),
+            Token::new(
+                TokenType::Identifier("test_func".to_string()),
+                None,
+                0,
+                0,
+                0,
+            ),
+            Token::new(TokenType::LeftParen, None, 0, 0, 0),
+            Token::new(TokenType::RightParen, None, 0, 0, 0),
+            Token::new(TokenType::LeftBrace, None, 0, 0, 0),
+            Token::new(TokenType::Return, None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
+            Token::new(TokenType::RightBrace, None, 0, 0, 0),
         ];
         let mut parser = Parser::new(&tokens);
         assert_eq!(
@@ -1899,4 +3272,670 @@ mod test {
             parser.program()[0]
         );
     }
+
+    #[test]
+    fn 分割代入_parse() {
+        let tokens = vec![
+            Token::new(TokenType::Var, None, 0, 0, 0),
+            Token::new(TokenType::LeftParen, None, 0, 0, 0),
+            Token::new(TokenType::Identifier("x".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::Comma, None, 0, 0, 0),
+            Token::new(TokenType::Identifier("y".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::RightParen, None, 0, 0, 0),
+            Token::new(TokenType::Equal, None, 0, 0, 0),
+            Token::new(TokenType::Identifier("pair".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::LeftParen, None, 0, 0, 0),
+            Token::new(TokenType::RightParen, None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
+        ];
+        let mut parser = Parser::new(&tokens);
+        assert_eq!(
+            AstType::MultiVar(
+                vec!["x".to_string(), "y".to_string()],
+                Box::new(AstType::Call("pair".to_string(), vec![])),
+            ),
+            parser.program()[0]
+        );
+
+        let tokens = vec![
+            Token::new(TokenType::Return, None, 0, 0, 0),
+            Token::new(TokenType::LeftParen, None, 0, 0, 0),
+            Token::new(TokenType::Identifier("a".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::Comma, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::RightParen, None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
+        ];
+        let mut parser = Parser::new(&tokens);
+        assert_eq!(
+            AstType::Return(Box::new(AstType::List(vec![
+                AstType::Identifier("a".to_string()),
+                AstType::Number(1.0),
+            ]))),
+            parser.program()[0]
+        );
+    }
+
+    #[test]
+    fn リスト_parse() {
+        let tokens = vec![
+            Token::new(TokenType::LeftBracket, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::Comma, None, 0, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::RightBracket, None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
+        ];
+        let mut parser = Parser::new(&tokens);
+        assert_eq!(
+            AstType::List(vec![AstType::Number(1.0), AstType::Number(2.0)]),
+            parser.program()[0]
+        );
+
+        let tokens = vec![
+            Token::new(TokenType::LeftBracket, None, 0, 0, 0),
+            Token::new(TokenType::RightBracket, None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
+        ];
+        let mut parser = Parser::new(&tokens);
+        assert_eq!(AstType::List(vec![]), parser.program()[0]);
+    }
+
+    #[test]
+    fn ビット演算_parse() {
+        // 1 | 2 & 3 << 1 == 3
+        let tokens = vec![
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::Pipe, None, 0, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::Ampersand, None, 0, 0, 0),
+            Token::new(TokenType::Number(3.0), None, 0, 0, 0),
+            Token::new(TokenType::LessLess, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::EqualEqual, None, 0, 0, 0),
+            Token::new(TokenType::Number(3.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
+        ];
+        let mut parser = Parser::new(&tokens);
+        assert_eq!(
+            AstType::EqualEqual(
+                Box::new(AstType::BitOr(
+                    Box::new(AstType::Number(1.0)),
+                    Box::new(AstType::BitAnd(
+                        Box::new(AstType::Number(2.0)),
+                        Box::new(AstType::ShiftLeft(
+                            Box::new(AstType::Number(3.0)),
+                            Box::new(AstType::Number(1.0)),
+                        )),
+                    )),
+                )),
+                Box::new(AstType::Number(3.0)),
+            ),
+            parser.program()[0]
+        );
+    }
+
+    #[test]
+    fn in演算子_parse() {
+        let tokens = vec![
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::In, None, 0, 0, 0),
+            Token::new(TokenType::LeftBracket, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::Comma, None, 0, 0, 0),
+            Token::new(TokenType::Number(2.0), None, 0, 0, 0),
+            Token::new(TokenType::RightBracket, None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
+        ];
+        let mut parser = Parser::new(&tokens);
+        assert_eq!(
+            AstType::In(
+                Box::new(AstType::Number(2.0)),
+                Box::new(AstType::List(vec![
+                    AstType::Number(1.0),
+                    AstType::Number(2.0)
+                ])),
+            ),
+            parser.program()[0]
+        );
+
+        let tokens = vec![
+            Token::new(TokenType::String("a".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::In, None, 0, 0, 0),
+            Token::new(TokenType::LeftBrace, None, 0, 0, 0),
+            Token::new(TokenType::String("a".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::Colon, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::RightBrace, None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
+        ];
+        let mut parser = Parser::new(&tokens);
+        assert_eq!(
+            AstType::In(
+                Box::new(AstType::String("a".to_string())),
+                Box::new(AstType::Map(vec![(
+                    AstType::String("a".to_string()),
+                    AstType::Number(1.0)
+                )])),
+            ),
+            parser.program()[0]
+        );
+    }
+
+    #[test]
+    fn インデックス代入_parse() {
+        let parse = |source: &str| {
+            let tokens = crate::scanner::Scanner::new(&source.to_string())
+                .scan()
+                .unwrap();
+            let mut parser = Parser::new(&tokens);
+            let ast = parser.program();
+            (ast, parser.errors().len())
+        };
+
+        let (ast, _) = parse("a[0] = 1;");
+        assert_eq!(
+            AstType::IndexAssign(
+                Box::new(AstType::Identifier("a".to_string())),
+                Box::new(AstType::Number(0.0)),
+                Box::new(AstType::Number(1.0)),
+            ),
+            ast[0]
+        );
+        let (ast, _) = parse("m[\"k\"] = 2;");
+        assert_eq!(
+            AstType::IndexAssign(
+                Box::new(AstType::Identifier("m".to_string())),
+                Box::new(AstType::String("k".to_string())),
+                Box::new(AstType::Number(2.0)),
+            ),
+            ast[0]
+        );
+        let (ast, _) = parse("grid[i][j] = v;");
+        assert_eq!(
+            AstType::IndexAssign(
+                Box::new(AstType::Index(
+                    Box::new(AstType::Identifier("grid".to_string())),
+                    Box::new(AstType::Identifier("i".to_string())),
+                )),
+                Box::new(AstType::Identifier("j".to_string())),
+                Box::new(AstType::Identifier("v".to_string())),
+            ),
+            ast[0]
+        );
+
+        // 変数を起点としない代入先はエラー
+        let (ast, errors) = parse("[1, 2][0] = 1;");
+        assert!(ast.is_empty());
+        assert_eq!(1, errors);
+        let (ast, errors) = parse("f()[0] = 1;");
+        assert!(ast.is_empty());
+        assert_eq!(1, errors);
+    }
+
+    #[test]
+    fn nil安全参照_parse() {
+        let tokens = vec![
+            Token::new(TokenType::Identifier("a".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::QuestionDot, None, 0, 0, 0),
+            Token::new(TokenType::Identifier("b".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::QuestionDot, None, 0, 0, 0),
+            Token::new(TokenType::Identifier("c".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
+        ];
+        let mut parser = Parser::new(&tokens);
+        assert_eq!(
+            AstType::OptionalGet(
+                Box::new(AstType::OptionalGet(
+                    Box::new(AstType::Identifier("a".to_string())),
+                    "b".to_string()
+                )),
+                "c".to_string()
+            ),
+            parser.program()[0]
+        );
+
+        // ?.の後ろは識別子のみ
+        let tokens = vec![
+            Token::new(TokenType::Identifier("a".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::QuestionDot, None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
+        ];
+        let mut parser = Parser::new(&tokens);
+        assert!(parser.program().is_empty());
+        assert_eq!(1, parser.errors().len());
+    }
+
+    #[test]
+    fn 無名関数_parse() {
+        let tokens =
+            crate::scanner::Scanner::new(&"var f = fun(a) { return a; }; fun() {};".to_string())
+                .scan()
+                .unwrap();
+        let mut parser = Parser::new(&tokens);
+        let ast = parser.program();
+        assert!(parser.errors().is_empty());
+        assert_eq!(
+            AstType::Var(
+                "f".to_string(),
+                Some(Box::new(AstType::Lambda(
+                    vec![AstType::Identifier("a".to_string())],
+                    Box::new(AstType::Block(vec![AstType::Return(Box::new(
+                        AstType::Identifier("a".to_string())
+                    ))])),
+                )))
+            ),
+            ast[0]
+        );
+        assert_eq!(
+            AstType::Lambda(vec![], Box::new(AstType::Block(vec![]))),
+            ast[1]
+        );
+    }
+
+    #[test]
+    fn 複数代入_parse() {
+        let tokens = vec![
+            Token::new(TokenType::Identifier("a".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::Comma, None, 0, 0, 0),
+            Token::new(TokenType::Identifier("b".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::Equal, None, 0, 0, 0),
+            Token::new(TokenType::Identifier("b".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::Comma, None, 0, 0, 0),
+            Token::new(TokenType::Identifier("a".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::SemiColon, None, 0, 0, 0),
+        ];
+        let mut parser = Parser::new(&tokens);
+        assert_eq!(
+            AstType::MultiAssign(
+                vec!["a".to_string(), "b".to_string()],
+                vec![
+                    AstType::Identifier("b".to_string()),
+                    AstType::Identifier("a".to_string()),
+                ],
+            ),
+            parser.program()[0]
+        );
+    }
+
+    #[test]
+    fn エラー位置_parse() {
+        let source = "var a = 1;\nprint a +;\na;".to_string();
+        let tokens = crate::scanner::Scanner::new(&source).scan().unwrap();
+        let mut parser = Parser::new(&tokens);
+        let ast = parser.program();
+
+        assert_eq!(2, ast.len());
+        assert_eq!(&vec![(0, 0), (2, 0)], parser.positions());
+        assert_eq!(1, parser.errors().len());
+        assert_eq!((1, 9), (parser.errors()[0].line, parser.errors()[0].col));
+    }
+
+    #[test]
+    fn 引数の数の上限_parse() {
+        let parse = |source: String| {
+            let tokens = Scanner::new(&source).scan().unwrap();
+            let mut parser = Parser::new(&tokens);
+            let ast = parser.program();
+            (ast, parser.errors().clone())
+        };
+        let args = |n: usize| vec!["1"; n].join(", ");
+        let params = |n: usize| {
+            (0..n)
+                .map(|i| format!("a{}", i))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let (ast, errors) = parse(format!("print 0;\nf({});", args(256)));
+        assert_eq!(1, ast.len());
+        assert_eq!(1, errors.len());
+        assert_eq!("Can not have more than 255 arguments", errors[0].message);
+        assert_eq!(1, errors[0].line);
+
+        let (ast, errors) = parse(format!("fun f({}) {{}}", params(256)));
+        assert!(ast.is_empty());
+        assert_eq!("Can not have more than 255 arguments", errors[0].message);
+
+        // 255個までは解釈する
+        let (ast, errors) = parse(format!("f({});", args(255)));
+        assert!(errors.is_empty());
+        assert!(matches!(&ast[0], AstType::Call(_, a) if a.len() == 255));
+        let (ast, errors) = parse(format!("fun f({}) {{}}", params(255)));
+        assert!(errors.is_empty());
+        assert!(matches!(&ast[0], AstType::Fun(_, a, _) if a.len() == 255));
+    }
+
+    #[test]
+    fn 空の括弧_parse() {
+        let tokens = Scanner::new(&"var a = 1;\nprint ();".to_string())
+            .scan()
+            .unwrap();
+        let mut parser = Parser::new(&tokens);
+        parser.program();
+        assert_eq!(1, parser.errors().len());
+        assert_eq!(
+            "Expected expression inside parentheses",
+            parser.errors()[0].message
+        );
+        assert_eq!((1, 6), (parser.errors()[0].line, parser.errors()[0].col));
+
+        let tokens = Scanner::new(&"(1);".to_string()).scan().unwrap();
+        let mut parser = Parser::new(&tokens);
+        let ast = parser.program();
+        assert!(parser.errors().is_empty());
+        assert_eq!(vec![AstType::Grouping(Box::new(AstType::Number(1.0)))], ast);
+    }
+
+    #[test]
+    fn オペランドなしの単項演算子_parse() {
+        let errors = |source: &str| {
+            let tokens = Scanner::new(&source.to_string()).scan().unwrap();
+            let mut parser = Parser::new(&tokens);
+            parser.program();
+            parser.errors().clone()
+        };
+
+        let e = errors("var a = 1;\nvar x = -;");
+        assert_eq!(1, e.len());
+        assert_eq!("Expected operand after '-'", e[0].message);
+        assert_eq!((1, 8), (e[0].line, e[0].col));
+
+        let e = errors("!;");
+        assert_eq!(1, e.len());
+        assert_eq!("Expected operand after '!'", e[0].message);
+        assert_eq!((0, 0), (e[0].line, e[0].col));
+
+        assert_eq!(
+            "Expected operand after '~'",
+            errors("print [~];")[0].message
+        );
+        assert_eq!(
+            "Expected operand after '-'",
+            errors("print 1 + -")[0].message
+        );
+        // 関数呼び出しの引数のエラーも報告する
+        let e = errors("f(-);");
+        assert_eq!(1, e.len());
+        assert_eq!("Expected operand after '-'", e[0].message);
+        assert_eq!((0, 2), (e[0].line, e[0].col));
+        assert_eq!(1, errors("f(1, -, 2);").len());
+        // 単項演算子の入れ子はオペランドがあればよい
+        assert!(errors("- -1; !!true;").is_empty());
+    }
+
+    #[test]
+    fn ノードの位置_parse() {
+        let source = "var a = 1;\nprint a +\n  (2 * 3);".to_string();
+        let tokens = Scanner::new(&source).scan().unwrap();
+        let mut parser = Parser::new(&tokens);
+        let ast = parser.program();
+        assert_eq!(2, parser.spans().len());
+
+        let nodes = node_spans(&ast[1], &parser.spans()[1]);
+        let find = |f: fn(&AstType) -> bool| {
+            nodes
+                .iter()
+                .find(|(node, _)| f(node))
+                .map(|(_, span)| *span)
+                .unwrap()
+        };
+        // 二項演算子は演算子の位置
+        assert_eq!((1, 8), find(|n| matches!(n, AstType::Plus(_, _))));
+        assert_eq!((2, 5), find(|n| matches!(n, AstType::Mul(_, _))));
+        assert_eq!((1, 6), find(|n| matches!(n, AstType::Identifier(_))));
+        assert_eq!((2, 2), find(|n| matches!(n, AstType::Grouping(_))));
+        assert_eq!((2, 3), find(|n| *n == AstType::Number(2.0)));
+        assert_eq!((1, 0), find(|n| matches!(n, AstType::Print(_))));
+
+        // for文、代入、関数呼び出しも各ノードの位置を持つ
+        let source = "for (var i = 0; i < 2; i = i + 1) {\n  f(i);\n}".to_string();
+        let tokens = Scanner::new(&source).scan().unwrap();
+        let mut parser = Parser::new(&tokens);
+        let ast = parser.program();
+        let nodes = node_spans(&ast[0], &parser.spans()[0]);
+        assert_eq!(count_nodes(&ast[0]), nodes.len());
+        assert!(nodes.contains(&(
+            &AstType::Call("f".to_string(), vec![AstType::Identifier("i".to_string())]),
+            (1, 2)
+        )));
+        assert!(nodes
+            .iter()
+            .any(|(n, span)| matches!(n, AstType::Assign(_, _)) && *span == (0, 23)));
+        assert!(nodes
+            .iter()
+            .any(|(n, span)| matches!(n, AstType::While(_, _, _, _)) && *span == (0, 0)));
+    }
+
+    #[test]
+    fn json_parse() {
+        let source = "var s = \"ab\";\nprint 1 + 2;".to_string();
+        let tokens = Scanner::new(&source).scan().unwrap();
+        let mut parser = Parser::new(&tokens);
+        let ast = parser.program();
+
+        assert_eq!(
+            r#"{"type":"Var","line":1,"col":1,"name":"s","children":[{"type":"String","line":1,"col":9,"value":"ab","children":[]}]}"#,
+            to_json(&ast[0], &parser.spans()[0])
+        );
+        assert_eq!(
+            concat!(
+                r#"{"type":"Print","line":2,"col":1,"children":[{"type":"Plus","line":2,"col":9,"children":["#,
+                r#"{"type":"Number","line":2,"col":7,"value":1,"children":[]},"#,
+                r#"{"type":"Number","line":2,"col":11,"value":2,"children":[]}]}]}"#
+            ),
+            to_json(&ast[1], &parser.spans()[1])
+        );
+        assert_eq!(r#""a\"b\n\\\u0001""#, json_string("a\"b\n\\\u{1}"));
+    }
+
+    #[test]
+    fn 先頭トークン不正_parse() {
+        // 先頭でTokenが読めない場合もパニックしない
+        let tokens = vec![Token::new(TokenType::Eof, None, 0, 0, 0)];
+        let mut parser = Parser::new(&tokens);
+        assert!(parser.program().is_empty());
+        assert!(parser.errors().is_empty());
+
+        let tokens = vec![];
+        let mut parser = Parser::new(&tokens);
+        assert!(parser.program().is_empty());
+
+        let tokens = vec![
+            Token::new(TokenType::RightParen, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 1, 0, 1),
+        ];
+        let mut parser = Parser::new(&tokens);
+        assert!(parser.program().is_empty());
+        assert_eq!(1, parser.errors().len());
+        assert_eq!((0, 0), (parser.errors()[0].line, parser.errors()[0].col));
+    }
+
+    #[test]
+    fn 予約語_名前_parse() {
+        for source in [
+            "var if = 1;",
+            "fun for() {}",
+            "var (a, while) = [1, 2];",
+            "fun f(nil) {}",
+        ] {
+            let tokens = crate::scanner::Scanner::new(&source.to_string())
+                .scan()
+                .unwrap();
+            let mut parser = Parser::new(&tokens);
+            parser.program();
+            assert!(parser.errors()[0]
+                .message
+                .contains("is a reserved word and cannot be used as a name"));
+        }
+
+        let tokens = crate::scanner::Scanner::new(&"var if = 1;".to_string())
+            .scan()
+            .unwrap();
+        let mut parser = Parser::new(&tokens);
+        parser.program();
+        assert_eq!(
+            "'if' is a reserved word and cannot be used as a name",
+            parser.errors()[0].message
+        );
+        let tokens = crate::scanner::Scanner::new(&"fun for() {}".to_string())
+            .scan()
+            .unwrap();
+        let mut parser = Parser::new(&tokens);
+        parser.program();
+        assert_eq!(
+            "'for' is a reserved word and cannot be used as a name",
+            parser.errors()[0].message
+        );
+    }
+
+    #[test]
+    fn 条件式の代入_警告_parse() {
+        let warnings = |source: &str| {
+            let tokens = crate::scanner::Scanner::new(&source.to_string())
+                .scan()
+                .unwrap();
+            let mut parser = Parser::new(&tokens);
+            parser.program();
+            assert!(parser.errors().is_empty());
+            parser.warnings().clone()
+        };
+
+        let w = warnings("var x = 1;\nif (x = 5) print x;");
+        assert_eq!(1, w.len());
+        assert_eq!((1, 4), (w[0].line, w[0].col));
+        assert!(w[0].message.contains("did you mean '=='?"));
+        assert_eq!(1, warnings("while (x = false) {}").len());
+        assert_eq!(1, warnings("for (;x = false;) {}").len());
+
+        assert!(warnings("if (x == 5) print x;").is_empty());
+        assert!(warnings("while (x == 5) {}").is_empty());
+        // 括弧で囲んだ場合は意図した代入とみなす
+        assert!(warnings("if ((x = 5)) print x;").is_empty());
+        assert!(warnings("x = 5;").is_empty());
+    }
+
+    #[test]
+    fn 入れ子上限_parse() {
+        // 上限を超える括弧の入れ子はエラー
+        let source = format!("{}1{};", "(".repeat(10000), ")".repeat(10000));
+        let tokens = crate::scanner::Scanner::new(&source).scan().unwrap();
+        let mut parser = Parser::new(&tokens);
+        assert!(parser.program().is_empty());
+        assert!(parser.errors()[0].message.contains("too deep"));
+
+        let source = format!("{}1;", "-".repeat(10000));
+        let tokens = crate::scanner::Scanner::new(&source).scan().unwrap();
+        let mut parser = Parser::new(&tokens);
+        assert!(parser.program().is_empty());
+        assert!(parser.errors()[0].message.contains("too deep"));
+
+        let source = format!("{}print 1;{}", "{".repeat(10000), "}".repeat(10000));
+        let tokens = crate::scanner::Scanner::new(&source).scan().unwrap();
+        let mut parser = Parser::new(&tokens);
+        parser.program();
+        assert!(parser.errors()[0].message.contains("too deep"));
+
+        // 連結した二項演算子、代入も構文木の深さとして数える
+        for source in [
+            format!("var x = 1; {}x;", "x + ".repeat(30000)),
+            format!("var x = 1; {}x;", "x = ".repeat(30000)),
+            format!("var x = [1]; x{};", "[0]".repeat(30000)),
+        ] {
+            let tokens = crate::scanner::Scanner::new(&source).scan().unwrap();
+            let mut parser = Parser::new(&tokens);
+            parser.program();
+            assert!(parser.errors()[0].message.contains("too deep (max 512)"));
+        }
+
+        // 上限以内であればパースできる
+        let source = format!("{}1{};", "(".repeat(100), ")".repeat(100));
+        let tokens = crate::scanner::Scanner::new(&source).scan().unwrap();
+        let mut parser = Parser::new(&tokens);
+        assert_eq!(1, parser.program().len());
+        assert!(parser.errors().is_empty());
+
+        // 文の入れ子は式の入れ子の上限に含めない
+        let source = format!(
+            "{}print {}1{};{}",
+            "if (true) { ".repeat(100),
+            "(".repeat(100),
+            ")".repeat(100),
+            " }".repeat(100)
+        );
+        let tokens = crate::scanner::Scanner::new(&source).scan().unwrap();
+        let mut parser = Parser::new(&tokens);
+        assert_eq!(1, parser.program().len());
+        assert!(parser.errors().is_empty());
+    }
+
+    #[test]
+    fn 定数畳み込み() {
+        // 2 + 3 * 4
+        let ast = AstType::Plus(
+            Box::new(AstType::Number(2.0)),
+            Box::new(AstType::Mul(
+                Box::new(AstType::Number(3.0)),
+                Box::new(AstType::Number(4.0)),
+            )),
+        );
+        assert_eq!(AstType::Number(14.0), fold(ast));
+
+        // "a" + "b"
+        let ast = AstType::Plus(
+            Box::new(AstType::String("a".to_string())),
+            Box::new(AstType::String("b".to_string())),
+        );
+        assert_eq!(AstType::String("ab".to_string()), fold(ast));
+
+        // -(1 - 3)
+        let ast = AstType::UnaryMinus(Box::new(AstType::Grouping(Box::new(AstType::Minus(
+            Box::new(AstType::Number(1.0)),
+            Box::new(AstType::Number(3.0)),
+        )))));
+        assert_eq!(AstType::Number(2.0), fold(ast));
+
+        // a + 2 * 3 は右辺のみ畳み込まれる
+        let ast = AstType::Plus(
+            Box::new(AstType::Identifier("a".to_string())),
+            Box::new(AstType::Mul(
+                Box::new(AstType::Number(2.0)),
+                Box::new(AstType::Number(3.0)),
+            )),
+        );
+        assert_eq!(
+            AstType::Plus(
+                Box::new(AstType::Identifier("a".to_string())),
+                Box::new(AstType::Number(6.0)),
+            ),
+            fold(ast)
+        );
+
+        // 識別子を含む式は変化しない
+        let ast = AstType::Mul(
+            Box::new(AstType::Identifier("a".to_string())),
+            Box::new(AstType::Identifier("b".to_string())),
+        );
+        assert_eq!(ast.clone(), fold(ast));
+
+        // 実行時エラーとなる式は畳み込まない
+        let ast = AstType::Plus(
+            Box::new(AstType::Number(1.0)),
+            Box::new(AstType::String("a".to_string())),
+        );
+        assert_eq!(ast.clone(), fold(ast));
+        let ast = AstType::Div(
+            Box::new(AstType::Number(1.0)),
+            Box::new(AstType::Number(0.0)),
+        );
+        assert_eq!(ast.clone(), fold(ast));
+
+        // 文の中の式も畳み込まれる
+        let ast = AstType::Print(Box::new(AstType::Plus(
+            Box::new(AstType::Number(1.0)),
+            Box::new(AstType::Number(2.0)),
+        )));
+        assert_eq!(AstType::Print(Box::new(AstType::Number(3.0))), fold(ast));
+    }
 }
@@ -0,0 +1,53 @@
+use std::cell::RefCell;
+
+// 実行時設定
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+    pub max_loop_iterations: Option<usize>, // 1つのループで繰り返せる回数の上限(Noneは無制限)
+    pub max_call_depth: Option<usize>,      // ユーザー定義関数の呼び出しの深さの上限(Noneは無制限)
+    pub max_arguments: Option<usize>,       // 関数呼び出しの引数の数の上限(Noneは無制限)
+    pub lint: bool,                         // 警告を表示するか
+    pub debug_env: bool, // 変数、関数が見つからない場合に定義済みの変数名を表示するか
+    pub strict: bool,    // 未初期化の変数の参照をエラーとするか
+    pub warnings_as_errors: bool, // 警告をエラーとして扱い、実行しないか
+    pub fold: bool,      // 評価前に定数畳み込みを行うか
+}
+
+thread_local! {
+    static CONFIG: RefCell<Config> = RefCell::new(Config::default());
+}
+
+/// 実行時設定を変更
+///
+/// # Arguments
+/// * `config` - 実行時設定
+pub fn set(config: Config) {
+    CONFIG.with(|c| *c.borrow_mut() = config);
+}
+
+/// 実行時設定を取得
+///
+/// # Return
+/// * Config - 実行時設定
+pub fn get() -> Config {
+    CONFIG.with(|c| c.borrow().clone())
+}
+
+/// 実行時設定を一時的に変更
+///
+/// # Arguments
+/// * `config` - 実行時設定
+///
+/// # Return
+/// * ConfigGuard - 破棄した時点で変更前の実行時設定へ戻す
+pub fn scoped(config: Config) -> ConfigGuard {
+    ConfigGuard(CONFIG.with(|c| c.replace(config)))
+}
+
+// 一時的に変更した実行時設定を戻す
+pub struct ConfigGuard(Config);
+impl Drop for ConfigGuard {
+    fn drop(&mut self) {
+        set(std::mem::take(&mut self.0));
+    }
+}
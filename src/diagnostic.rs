@@ -0,0 +1,106 @@
+use crate::scanner::DEFAULT_TAB_WIDTH;
+
+/// エラー発生位置付きのメッセージ
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub line: usize, // 0始まりの行番号
+    pub col: usize,  // 0始まりのカラム
+}
+impl Diagnostic {
+    pub fn new(message: String, line: usize, col: usize) -> Self {
+        Diagnostic { message, line, col }
+    }
+}
+
+/// エラー箇所のソース行と、カラム位置へのキャレットを付けて整形する
+///
+/// ```text
+/// error: Could not found "RightParen" token
+///   --> 2:9
+///   |
+/// 2 | print (1;
+///   |         ^
+/// ```
+///
+/// # Arguments
+/// * `source` - ソースコード
+/// * `diagnostic` - エラー内容
+///
+/// # Return
+/// * String - 整形後の文字列
+pub fn render(source: &str, diagnostic: &Diagnostic) -> String {
+    render_level("error", source, diagnostic)
+}
+
+/// 警告として整形する。見出しが`warning:`となる以外はrenderと同じ
+///
+/// # Arguments
+/// * `source` - ソースコード
+/// * `diagnostic` - 警告内容
+///
+/// # Return
+/// * String - 整形後の文字列
+pub fn render_warning(source: &str, diagnostic: &Diagnostic) -> String {
+    render_level("warning", source, diagnostic)
+}
+
+fn render_level(level: &str, source: &str, diagnostic: &Diagnostic) -> String {
+    let line_no = (diagnostic.line + 1).to_string();
+    let pad = " ".repeat(line_no.len());
+    let mut out = format!(
+        "{}: {}\n{} --> {}:{}\n",
+        level,
+        diagnostic.message,
+        pad,
+        diagnostic.line + 1,
+        diagnostic.col + 1
+    );
+
+    // 行が存在しない場合(EOF等)は位置のみ出力
+    if let Some(line) = source.lines().nth(diagnostic.line) {
+        // スキャナと同じ幅でタブを展開し、キャレットの位置を揃える
+        let line = line.replace('\t', &" ".repeat(DEFAULT_TAB_WIDTH));
+        out += &format!("{} |\n", pad);
+        out += &format!("{} | {}\n", line_no, line);
+        out += &format!("{} | {}^\n", pad, " ".repeat(diagnostic.col));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn キャレット_render() {
+        let source = "var a = 1;\nprint a +;\n";
+        let diagnostic = Diagnostic::new("Could not support token".to_string(), 1, 9);
+        let expect = [
+            "error: Could not support token",
+            "  --> 2:10",
+            "  |",
+            "2 | print a +;",
+            "  |          ^",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(expect, render(source, &diagnostic));
+
+        // タブはスキャナと同じ幅で展開される
+        let source = "\tx;";
+        let diagnostic = Diagnostic::new("error".to_string(), 0, 8);
+        let rendered = render(source, &diagnostic);
+        assert!(rendered.contains(&format!("1 | {}x;\n", " ".repeat(8))));
+        assert!(rendered.ends_with(&format!("  | {}^\n", " ".repeat(8))));
+
+        // 存在しない行
+        let diagnostic = Diagnostic::new("error".to_string(), 5, 0);
+        assert_eq!("error: error\n  --> 6:1\n", render(source, &diagnostic));
+        assert_eq!(
+            "warning: error\n  --> 6:1\n",
+            render_warning(source, &diagnostic)
+        );
+    }
+}
@@ -1,17 +1,1628 @@
 use crate::environment::{Environment, Value};
+use crate::eval::{
+    self, format_value, EvalResult, Frozen, MemoFunc, NativeFunc, Operand, ReturnType, RuntimeError,
+};
+use crate::output;
+use std::cmp::Ordering;
+use std::env;
+use std::process;
+use std::time::Instant;
 
-// 組み込み関数登録
+// 組み込み関数、定数登録
 pub fn register_func(env: &Environment) -> Environment {
     let mut env = env.clone();
 
-    env.define(
-        "clock".to_string(),
-        Value::EmbeddedFunc(crate::embedded::func::clock),
-    );
+    let funcs: [(&str, NativeFunc); 42] = [
+        ("clock", clock),
+        ("benchmark", benchmark),
+        ("eprint", eprint),
+        ("is_number", is_number),
+        ("is_string", is_string),
+        ("is_bool", is_bool),
+        ("is_nil", is_nil),
+        ("is_function", is_function),
+        ("is_nan", is_nan),
+        ("arity", arity),
+        ("vadd", vadd),
+        ("vsub", vsub),
+        ("vdot", vdot),
+        ("vlen", vlen),
+        ("clone", clone),
+        ("format", format),
+        ("range", range),
+        ("assert_eq", assert_eq),
+        ("assert_neq", assert_neq),
+        ("sort", sort),
+        ("freeze", freeze),
+        ("chars", chars),
+        ("to_list", to_list),
+        ("str", str),
+        ("exit", exit),
+        ("memoize", memoize),
+        ("abs", abs),
+        ("sign", sign),
+        ("sqrt", sqrt),
+        ("exp", exp),
+        ("log", log),
+        ("sin", sin),
+        ("cos", cos),
+        ("tan", tan),
+        ("fixed", fixed),
+        ("bool_to_num", bool_to_num),
+        ("getenv", getenv),
+        ("is_integer", is_integer),
+        ("trunc", trunc),
+        ("expect_error", expect_error),
+        ("clamp", clamp),
+        ("zip", zip),
+    ];
+    for (name, f) in funcs {
+        env.define(name.to_string(), Value::EmbeddedFunc(f));
+    }
+
+    // 数学定数
+    let constants = [("PI", std::f64::consts::PI), ("E", std::f64::consts::E)];
+    for (name, value) in constants {
+        env.define(name.to_string(), Value::F64(value));
+    }
 
     env
 }
 
-fn clock() {
+/// 引数の数チェック
+///
+/// # Arguments
+/// * `args` - 引数
+/// * `num` - 期待する引数の数
+///
+/// # Return
+/// * Result<(), RuntimeError> - 引数の数が一致しない場合はエラー
+fn check_args_num(args: &[Operand], num: usize) -> Result<(), RuntimeError> {
+    if args.len() != num {
+        return Err(RuntimeError::NotMatchArgsNum);
+    }
+
+    Ok(())
+}
+
+/// 引数から戻り値ラッパーを外す
+///
+/// # Arguments
+/// * `operand` - 引数
+///
+/// # Return
+/// * &Operand - ラッパーを外した値
+fn unwrap_return(operand: &Operand) -> &Operand {
+    match operand {
+        ReturnType::Return(o) => unwrap_return(o),
+        _ => operand,
+    }
+}
+
+fn clock(_args: &[Operand], _env: &mut Environment) -> EvalResult {
     println!("called clock");
+
+    Ok(ReturnType::Void)
+}
+
+// 関数を指定回数呼び出し、経過時間(秒)を返す
+fn benchmark(args: &[Operand], env: &mut Environment) -> EvalResult {
+    check_args_num(args, 2)?;
+    let iterations = match unwrap_return(&args[1]) {
+        ReturnType::F64(n) if *n >= 0.0 && n.fract() == 0.0 => *n as usize,
+        o => return Err(RuntimeError::OperandType(o.clone())),
+    };
+
+    let start = Instant::now();
+    for _ in 0..iterations {
+        eval::call_value(&args[0], &vec![], env)?;
+    }
+
+    Ok(ReturnType::F64(start.elapsed().as_secs_f64()))
+}
+
+// 引数なしで関数を呼び出し、実行時エラーとなった場合にtrueを返す。エラーは伝播しない
+fn expect_error(args: &[Operand], env: &mut Environment) -> EvalResult {
+    check_args_num(args, 1)?;
+    let func = unwrap_return(&args[0]);
+    if !matches!(
+        func,
+        ReturnType::UserFunc(_, _) | ReturnType::EmbeddedFunc(_) | ReturnType::MemoFunc(_)
+    ) {
+        return Err(RuntimeError::OperandType(func.clone()));
+    }
+
+    Ok(ReturnType::Bool(
+        eval::call_value(func, &vec![], env).is_err(),
+    ))
+}
+
+// 標準エラー出力へ出力
+fn eprint(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    check_args_num(args, 1)?;
+    output::eprintln(&format_value(unwrap_return(&args[0])));
+
+    Ok(ReturnType::Void)
+}
+
+// 数値判定
+fn is_number(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    check_args_num(args, 1)?;
+
+    Ok(ReturnType::Bool(matches!(
+        unwrap_return(&args[0]),
+        ReturnType::F64(_)
+    )))
+}
+
+// 文字列判定
+fn is_string(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    check_args_num(args, 1)?;
+
+    Ok(ReturnType::Bool(matches!(
+        unwrap_return(&args[0]),
+        ReturnType::String(_)
+    )))
+}
+
+// bool値判定
+fn is_bool(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    check_args_num(args, 1)?;
+
+    Ok(ReturnType::Bool(matches!(
+        unwrap_return(&args[0]),
+        ReturnType::Bool(_)
+    )))
+}
+
+// nil判定
+fn is_nil(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    check_args_num(args, 1)?;
+
+    Ok(ReturnType::Bool(matches!(
+        unwrap_return(&args[0]),
+        ReturnType::Void
+    )))
+}
+
+// 関数判定
+fn is_function(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    check_args_num(args, 1)?;
+
+    Ok(ReturnType::Bool(matches!(
+        unwrap_return(&args[0]),
+        ReturnType::UserFunc(_, _) | ReturnType::EmbeddedFunc(_) | ReturnType::MemoFunc(_)
+    )))
+}
+
+// NaN判定
+fn is_nan(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    check_args_num(args, 1)?;
+
+    match unwrap_return(&args[0]) {
+        ReturnType::F64(n) => Ok(ReturnType::Bool(n.is_nan())),
+        o => Err(RuntimeError::OperandType(o.clone())),
+    }
+}
+
+// 整数判定。小数部がない数値の場合にtrue(NaN、無限大はfalse)
+fn is_integer(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    check_args_num(args, 1)?;
+
+    match unwrap_return(&args[0]) {
+        ReturnType::F64(n) => Ok(ReturnType::Bool(n.fract() == 0.0)),
+        o => Err(RuntimeError::OperandType(o.clone())),
+    }
+}
+
+// 関数の引数の数。組み込み関数は引数の数が可変のため-1を返す
+fn arity(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    check_args_num(args, 1)?;
+
+    // メモ化した関数は、呼び出す関数の引数の数とする
+    let mut func = unwrap_return(&args[0]);
+    while let ReturnType::MemoFunc(m) = func {
+        func = m.func();
+    }
+    match func {
+        ReturnType::UserFunc(params, _) => Ok(ReturnType::F64(params.len() as f64)),
+        ReturnType::EmbeddedFunc(_) => Ok(ReturnType::F64(-1.0)),
+        o => Err(RuntimeError::OperandType(o.clone())),
+    }
+}
+
+/// 引数の数値を取得
+///
+/// # Arguments
+/// * `operand` - 引数
+///
+/// # Return
+/// * Result<f64, RuntimeError> - 数値以外の場合はエラー
+fn to_number(operand: &Operand) -> Result<f64, RuntimeError> {
+    match unwrap_return(operand) {
+        ReturnType::F64(n) => Ok(*n),
+        o => Err(RuntimeError::OperandType(o.clone())),
+    }
+}
+
+/// 数値1つを引数とする数学関数の評価
+///
+/// # Arguments
+/// * `args` - 引数
+/// * `f` - 数学関数
+///
+/// # Return
+/// * EvalResult - 評価後の値。定義域外の場合はNaN
+fn math(args: &[Operand], f: fn(f64) -> f64) -> EvalResult {
+    check_args_num(args, 1)?;
+
+    Ok(ReturnType::F64(f(to_number(&args[0])?)))
+}
+
+// 絶対値
+fn abs(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    math(args, f64::abs)
+}
+
+// 符号。負の数は-1、0は0、正の数は1
+fn sign(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    math(args, |n| if n == 0.0 { 0.0 } else { n.signum() })
+}
+
+// 平方根
+fn sqrt(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    math(args, f64::sqrt)
+}
+
+// 小数部の切り捨て(0方向への丸め)
+fn trunc(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    math(args, f64::trunc)
+}
+
+// 指数関数
+fn exp(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    math(args, f64::exp)
+}
+
+// 対数。log(x)は自然対数、log(x, base)は底をbaseとする対数
+fn log(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    match args {
+        [x] => Ok(ReturnType::F64(to_number(x)?.ln())),
+        [x, base] => Ok(ReturnType::F64(to_number(x)?.log(to_number(base)?))),
+        _ => Err(RuntimeError::NotMatchArgsNum),
+    }
+}
+
+// 三角関数(ラジアン)
+fn sin(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    math(args, f64::sin)
+}
+fn cos(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    math(args, f64::cos)
+}
+fn tan(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    math(args, f64::tan)
+}
+
+// bool値を数値へ変換。trueは1、falseは0
+fn bool_to_num(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    check_args_num(args, 1)?;
+
+    match unwrap_return(&args[0]) {
+        ReturnType::Bool(b) => Ok(ReturnType::F64(if *b { 1.0 } else { 0.0 })),
+        o => Err(RuntimeError::OperandType(o.clone())),
+    }
+}
+
+// 数値を範囲[lo, hi]に収める
+fn clamp(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    check_args_num(args, 3)?;
+    let (x, lo, hi) = (
+        to_number(&args[0])?,
+        to_number(&args[1])?,
+        to_number(&args[2])?,
+    );
+    if lo > hi || lo.is_nan() || hi.is_nan() {
+        return Err(RuntimeError::InvalidArgument(format!(
+            "clamp range is invalid: lo={} hi={}",
+            eval::format_number(lo),
+            eval::format_number(hi)
+        )));
+    }
+
+    Ok(ReturnType::F64(x.clamp(lo, hi)))
+}
+
+// 2つのリストの同じ位置の要素を組にしたリストを返す。長さは短い方のリストに合わせる
+fn zip(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    check_args_num(args, 2)?;
+    match (unwrap_return(&args[0]), unwrap_return(&args[1])) {
+        (ReturnType::List(a, _), ReturnType::List(b, _)) => Ok(ReturnType::List(
+            a.iter()
+                .zip(b)
+                .map(|(a, b)| ReturnType::List(vec![a.clone(), b.clone()], Frozen::default()))
+                .collect(),
+            Frozen::default(),
+        )),
+        (ReturnType::List(_, _), o) | (o, _) => Err(RuntimeError::OperandType(o.clone())),
+    }
+}
+
+// fixed関数で指定できる桁数の上限
+const MAX_FIXED_DIGITS: f64 = 100.0;
+
+// 小数点以下を指定した桁数に丸めた文字列へ変換。桁数は0以上、100以下の整数
+fn fixed(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    check_args_num(args, 2)?;
+    let n = to_number(&args[0])?;
+    let digits = to_number(&args[1])?;
+    if digits < 0.0 || digits.fract() != 0.0 {
+        return Err(RuntimeError::InvalidArgument(format!(
+            "digits must be a non-negative integer: {}",
+            eval::format_number(digits)
+        )));
+    }
+    if digits > MAX_FIXED_DIGITS {
+        return Err(RuntimeError::InvalidArgument(format!(
+            "digits must be at most {}: {}",
+            MAX_FIXED_DIGITS,
+            eval::format_number(digits)
+        )));
+    }
+
+    Ok(ReturnType::String(format!("{:.*}", digits as usize, n)))
+}
+
+/// 数値のリストをベクトルへ変換
+///
+/// # Arguments
+/// * `operand` - 引数
+///
+/// # Return
+/// * Result<Vec<f64>, RuntimeError> - リスト以外、数値以外の要素を含む場合はエラー
+fn to_vector(operand: &Operand) -> Result<Vec<f64>, RuntimeError> {
+    match unwrap_return(operand) {
+        ReturnType::List(l, _) => l
+            .iter()
+            .map(|e| match unwrap_return(e) {
+                ReturnType::F64(n) => Ok(*n),
+                e => Err(RuntimeError::OperandType(e.clone())),
+            })
+            .collect(),
+        o => Err(RuntimeError::OperandType(o.clone())),
+    }
+}
+
+/// 次元数が一致する2つのベクトルを取得
+///
+/// # Arguments
+/// * `args` - 引数
+///
+/// # Return
+/// * Result<(Vec<f64>, Vec<f64>), RuntimeError> - 次元数が一致しない場合はエラー
+fn to_vector_pair(args: &[Operand]) -> Result<(Vec<f64>, Vec<f64>), RuntimeError> {
+    check_args_num(args, 2)?;
+
+    let (a, b) = (to_vector(&args[0])?, to_vector(&args[1])?);
+    if a.len() != b.len() {
+        return Err(RuntimeError::NotMatchDimension(a.len(), b.len()));
+    }
+
+    Ok((a, b))
+}
+
+// ベクトル加算
+fn vadd(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    let (a, b) = to_vector_pair(args)?;
+
+    Ok(ReturnType::List(
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| ReturnType::F64(a + b))
+            .collect(),
+        Frozen::default(),
+    ))
+}
+
+// ベクトル減算
+fn vsub(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    let (a, b) = to_vector_pair(args)?;
+
+    Ok(ReturnType::List(
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| ReturnType::F64(a - b))
+            .collect(),
+        Frozen::default(),
+    ))
+}
+
+// ベクトル内積
+fn vdot(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    let (a, b) = to_vector_pair(args)?;
+
+    Ok(ReturnType::F64(a.iter().zip(b).map(|(a, b)| a * b).sum()))
+}
+
+// ベクトル長
+fn vlen(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    check_args_num(args, 1)?;
+    let a = to_vector(&args[0])?;
+
+    Ok(ReturnType::F64(a.iter().map(|n| n * n).sum::<f64>().sqrt()))
+}
+
+// 値の複製。リスト、マップは要素も含めて再帰的に複製し、変更可能にする
+fn clone(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    check_args_num(args, 1)?;
+
+    Ok(deep_copy(unwrap_return(&args[0])))
+}
+
+/// 値を再帰的に複製
+///
+/// 変更不可にしたリスト、マップも、複製は変更可能とする
+///
+/// # Arguments
+/// * `operand` - 複製する値
+///
+/// # Return
+/// * Operand - 複製した値
+fn deep_copy(operand: &Operand) -> Operand {
+    match operand {
+        ReturnType::List(l, _) => {
+            ReturnType::List(l.iter().map(deep_copy).collect(), Frozen::default())
+        }
+        ReturnType::Map(m, _) => ReturnType::Map(
+            m.iter()
+                .map(|(k, v)| (deep_copy(k), deep_copy(v)))
+                .collect(),
+            Frozen::default(),
+        ),
+        _ => operand.clone(),
+    }
+}
+
+// 変更不可にした値を返す。リスト、マップは要素も含めて再帰的に変更不可にする
+fn freeze(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    check_args_num(args, 1)?;
+
+    Ok(frozen_copy(unwrap_return(&args[0])))
+}
+
+/// 値を再帰的に変更不可にして複製
+///
+/// # Arguments
+/// * `operand` - 対象の値
+///
+/// # Return
+/// * Operand - 変更不可にした値（List/Map以外はそのまま）
+fn frozen_copy(operand: &Operand) -> Operand {
+    match operand {
+        ReturnType::List(l, _) => {
+            ReturnType::List(l.iter().map(frozen_copy).collect(), Frozen(true))
+        }
+        ReturnType::Map(m, _) => ReturnType::Map(
+            m.iter().map(|(k, v)| (k.clone(), frozen_copy(v))).collect(),
+            Frozen(true),
+        ),
+        _ => operand.clone(),
+    }
+}
+
+// 文字列フォーマット。{}を引数の値で置き換える({{、}}は波括弧そのもの)
+fn format(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    let template = match args.first().map(unwrap_return) {
+        Some(ReturnType::String(s)) => s,
+        Some(o) => return Err(RuntimeError::OperandType(o.clone())),
+        None => return Err(RuntimeError::NotMatchArgsNum),
+    };
+    let values = &args[1..];
+
+    let mut result = String::new();
+    let mut placeholders = 0;
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                chars.next();
+                result.push(c);
+            }
+            ('{', Some('}')) => {
+                chars.next();
+                if let Some(value) = values.get(placeholders) {
+                    result += &format_value(unwrap_return(value));
+                }
+                placeholders += 1;
+            }
+            ('{', _) | ('}', _) => return Err(RuntimeError::InvalidFormat(template.clone())),
+            _ => result.push(c),
+        }
+    }
+    if placeholders != values.len() {
+        return Err(RuntimeError::NotMatchFormatArgsNum(
+            placeholders,
+            values.len(),
+        ));
+    }
+
+    Ok(ReturnType::String(result))
+}
+
+// range関数で生成できる要素数の上限
+const MAX_RANGE_LEN: f64 = 10_000_000.0;
+
+// 数列のリスト生成。range(end)、range(start, end)、range(start, end, step)
+fn range(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    let nums = args
+        .iter()
+        .map(|a| match unwrap_return(a) {
+            ReturnType::F64(n) => Ok(*n),
+            o => Err(RuntimeError::OperandType(o.clone())),
+        })
+        .collect::<Result<Vec<f64>, RuntimeError>>()?;
+    let (start, end, step) = match nums[..] {
+        [end] => (0.0, end, 1.0),
+        [start, end] => (start, end, 1.0),
+        [start, end, step] => (start, end, step),
+        _ => return Err(RuntimeError::NotMatchArgsNum),
+    };
+    if !(start.is_finite() && end.is_finite() && step.is_finite()) {
+        return Err(RuntimeError::InvalidArgument(
+            "range arguments must be finite".to_string(),
+        ));
+    }
+    if step == 0.0 {
+        return Err(RuntimeError::InvalidArgument(
+            "range step must not be zero".to_string(),
+        ));
+    }
+
+    // 要素数を先に求め、誤差が累積しないよう各要素は開始値から計算する
+    let count = ((end - start) / step).ceil().max(0.0);
+    if count > MAX_RANGE_LEN {
+        return Err(RuntimeError::InvalidArgument(format!(
+            "range length must be at most {}: {}",
+            MAX_RANGE_LEN,
+            eval::format_number(count)
+        )));
+    }
+    let values = (0..count as usize)
+        .map(|i| ReturnType::F64(start + i as f64 * step))
+        .collect();
+
+    Ok(ReturnType::List(values, Frozen::default()))
+}
+
+// 2つの値が等しいことを検証。==演算子と同じ比較を行う
+fn assert_eq(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    check_args_num(args, 2)?;
+    let (left, right) = (unwrap_return(&args[0]), unwrap_return(&args[1]));
+
+    match eval::equal_equal(left.clone(), right.clone())? {
+        ReturnType::Bool(true) => Ok(ReturnType::Void),
+        _ => Err(RuntimeError::AssertionFailed(
+            "assert_eq".to_string(),
+            left.clone(),
+            right.clone(),
+        )),
+    }
+}
+
+// 2つの値が等しくないことを検証。!=演算子と同じ比較を行う
+fn assert_neq(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    check_args_num(args, 2)?;
+    let (left, right) = (unwrap_return(&args[0]), unwrap_return(&args[1]));
+
+    match eval::bang_equal(left.clone(), right.clone())? {
+        ReturnType::Bool(true) => Ok(ReturnType::Void),
+        _ => Err(RuntimeError::AssertionFailed(
+            "assert_neq".to_string(),
+            left.clone(),
+            right.clone(),
+        )),
+    }
+}
+
+// 昇順にソートした新しいリストを返す。sort(list)、sort(list, 比較関数)
+// 比較関数は負、0、正の数値を返す。比較関数がない場合は数値のみ、文字列のみのリストが対象
+fn sort(args: &[Operand], env: &mut Environment) -> EvalResult {
+    if args.is_empty() || args.len() > 2 {
+        return Err(RuntimeError::NotMatchArgsNum);
+    }
+    let mut values = match unwrap_return(&args[0]) {
+        ReturnType::List(l, _) => l
+            .iter()
+            .map(|v| unwrap_return(v).clone())
+            .collect::<Vec<_>>(),
+        o => return Err(RuntimeError::OperandType(o.clone())),
+    };
+
+    // sort_byはエラーを返せないため、最初に発生したエラーを保持しておく
+    let mut error = None;
+    match args.get(1) {
+        Some(func) => values.sort_by(|a, b| {
+            if error.is_some() {
+                return Ordering::Equal;
+            }
+            match eval::call_value(func, &vec![a.clone(), b.clone()], env) {
+                Ok(ret) => match unwrap_return(&ret) {
+                    ReturnType::F64(n) => n.partial_cmp(&0.0).unwrap_or(Ordering::Equal),
+                    o => {
+                        error = Some(RuntimeError::OperandType(o.clone()));
+                        Ordering::Equal
+                    }
+                },
+                Err(e) => {
+                    error = Some(e);
+                    Ordering::Equal
+                }
+            }
+        }),
+        None => values.sort_by(|a, b| match (a, b) {
+            (ReturnType::F64(l), ReturnType::F64(r)) => l.total_cmp(r),
+            (ReturnType::String(l), ReturnType::String(r)) => l.cmp(r),
+            _ => {
+                if error.is_none() {
+                    error = Some(RuntimeError::TwoOperandType(a.clone(), b.clone()));
+                }
+                Ordering::Equal
+            }
+        }),
+    }
+    if let Some(e) = error {
+        return Err(e);
+    }
+
+    Ok(ReturnType::List(values, Frozen::default()))
+}
+
+// 環境変数の値を取得。設定されていない場合はnil
+fn getenv(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    check_args_num(args, 1)?;
+
+    match unwrap_return(&args[0]) {
+        ReturnType::String(name) => Ok(env::var_os(name).map_or(ReturnType::Void, |v| {
+            ReturnType::String(v.to_string_lossy().to_string())
+        })),
+        o => Err(RuntimeError::OperandType(o.clone())),
+    }
+}
+
+// 出力をフラッシュし、指定した終了コード(0〜255)でプロセスを終了する
+fn exit(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    check_args_num(args, 1)?;
+    let code = match unwrap_return(&args[0]) {
+        ReturnType::F64(n) if n.fract() == 0.0 && (0.0..=255.0).contains(n) => *n as i32,
+        ReturnType::F64(n) => {
+            return Err(RuntimeError::InvalidArgument(format!(
+                "exit code must be an integer between 0 and 255: {}",
+                eval::format_number(*n)
+            )))
+        }
+        o => return Err(RuntimeError::OperandType(o.clone())),
+    };
+
+    output::flush();
+    process::exit(code)
+}
+
+// 引数値ごとに戻り値を保持する関数を返す。同じ引数値での呼び出しは関数を呼び出さずに保持した値を返す
+fn memoize(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    check_args_num(args, 1)?;
+
+    match unwrap_return(&args[0]) {
+        f
+        @ (ReturnType::UserFunc(_, _) | ReturnType::EmbeddedFunc(_) | ReturnType::MemoFunc(_)) => {
+            Ok(ReturnType::MemoFunc(MemoFunc::new(f.clone())))
+        }
+        o => Err(RuntimeError::OperandType(o.clone())),
+    }
+}
+
+// 値を文字列へ変換。printと同じ形式とする
+fn str(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    check_args_num(args, 1)?;
+
+    Ok(ReturnType::String(format_value(unwrap_return(&args[0]))))
+}
+
+// 文字列を1文字ずつの文字列のリストへ分割
+fn chars(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    check_args_num(args, 1)?;
+
+    match unwrap_return(&args[0]) {
+        ReturnType::String(s) => Ok(string_chars(s)),
+        o => Err(RuntimeError::OperandType(o.clone())),
+    }
+}
+
+// リストへ変換。文字列は1文字ずつ、マップはキーのリストとする
+fn to_list(args: &[Operand], _env: &mut Environment) -> EvalResult {
+    check_args_num(args, 1)?;
+
+    match unwrap_return(&args[0]) {
+        ReturnType::String(s) => Ok(string_chars(s)),
+        ReturnType::List(l, _) => Ok(ReturnType::List(l.clone(), Frozen::default())),
+        ReturnType::Map(m, _) => Ok(ReturnType::List(
+            m.iter().map(|(k, _)| k.clone()).collect(),
+            Frozen::default(),
+        )),
+        o => Err(RuntimeError::OperandType(o.clone())),
+    }
+}
+
+/// 文字列を1文字ずつの文字列のリストへ変換
+///
+/// # Arguments
+/// * `s` - 文字列
+///
+/// # Return
+/// * Operand - 文字列のリスト
+fn string_chars(s: &str) -> Operand {
+    ReturnType::List(
+        s.chars()
+            .map(|c| ReturnType::String(c.to_string()))
+            .collect(),
+        Frozen::default(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::Parser;
+    use crate::eval::eval;
+    use crate::scanner::Scanner;
+    use std::cell::RefCell;
+    use std::io::{self, Write};
+    use std::rc::Rc;
+
+    fn eval_source(source: &str) -> EvalResult {
+        let source = source.to_string();
+        let tokens = Scanner::new(&source).scan().unwrap();
+        let mut env = register_func(&Environment::new());
+        let mut ret = ReturnType::Void;
+        for ast in Parser::new(&tokens).program() {
+            ret = eval(&ast, &mut env)?;
+        }
+
+        Ok(ret)
+    }
+
+    // 書き込んだ内容を共有するバッファ
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+    impl SharedBuffer {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.borrow().clone()).unwrap()
+        }
+    }
+
+    #[test]
+    fn 標準エラー出力_func() {
+        let (out, err) = (SharedBuffer::default(), SharedBuffer::default());
+        output::set_writers(Box::new(out.clone()), Box::new(err.clone()));
+
+        eval_source("print 1; eprint(\"x\"); eprint([1, \"a\"]);").unwrap();
+        assert_eq!("1\n", out.contents());
+        assert_eq!("x\n[1, \"a\"]\n", err.contents());
+        assert!(matches!(
+            eval_source("eprint();"),
+            Err(RuntimeError::NotMatchArgsNum)
+        ));
+    }
+
+    #[test]
+    fn 型判定_func() {
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("is_number(3);").unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(false),
+            eval_source("is_string(3);").unwrap()
+        );
+        assert_eq!(ReturnType::Bool(true), eval_source("is_nil(nil);").unwrap());
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("is_string(\"a\");").unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("is_bool(false);").unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("fun f() {} is_function(f);").unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("is_function(clock);").unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(false),
+            eval_source("var x; is_function(x);").unwrap()
+        );
+        assert!(matches!(
+            eval_source("is_nil();"),
+            Err(RuntimeError::NotMatchArgsNum)
+        ));
+    }
+
+    #[test]
+    fn 非数_func() {
+        // 無限大同士の減算でNaNを作る
+        let nan = format!(
+            "var big = 1{}; var nan = big * 10 - big * 10;",
+            "0".repeat(308)
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source(&format!("{} is_nan(nan);", nan)).unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(false),
+            eval_source(&format!("{} nan == nan;", nan)).unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source(&format!("{} nan != nan;", nan)).unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(false),
+            eval_source(&format!("{} [nan] == [nan];", nan)).unwrap()
+        );
+        assert_eq!(ReturnType::Bool(false), eval_source("is_nan(1);").unwrap());
+        assert_eq!(ReturnType::Bool(true), eval_source("0 == -0;").unwrap());
+        assert!(matches!(
+            eval_source("is_nan(\"a\");"),
+            Err(RuntimeError::OperandType(_))
+        ));
+    }
+
+    #[test]
+    fn ベンチマーク_func() {
+        let elapsed = eval_source("benchmark(fun(){}, 100);").unwrap();
+        assert!(matches!(elapsed, ReturnType::F64(n) if (0.0..1.0).contains(&n)));
+
+        // 関数から環境を参照できる
+        assert_eq!(
+            ReturnType::F64(10.0),
+            eval_source("var n = 0; fun inc() { n = n + 1; } benchmark(inc, 10); n;").unwrap()
+        );
+        assert!(matches!(
+            eval_source("benchmark(1, 1);"),
+            Err(RuntimeError::OperandType(_))
+        ));
+        assert!(matches!(
+            eval_source("benchmark(fun(){}, -1);"),
+            Err(RuntimeError::OperandType(_))
+        ));
+        assert!(matches!(
+            eval_source("benchmark(fun(a){}, 1);"),
+            Err(RuntimeError::NotMatchArgsNum)
+        ));
+    }
+
+    #[test]
+    fn 引数の数_func() {
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("fun f(a, b, c) {} arity(f) == 3;").unwrap()
+        );
+        assert_eq!(
+            ReturnType::F64(0.0),
+            eval_source("fun f() {} arity(f);").unwrap()
+        );
+        assert_eq!(ReturnType::F64(-1.0), eval_source("arity(clock);").unwrap());
+        assert!(matches!(
+            eval_source("arity(1);"),
+            Err(RuntimeError::OperandType(_))
+        ));
+    }
+
+    #[test]
+    fn 数学定数_func() {
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("PI > 3.14 and PI < 3.15;").unwrap()
+        );
+        assert_eq!(ReturnType::Bool(true), eval_source("E > 2.71;").unwrap());
+        assert_eq!(
+            ReturnType::F64(std::f64::consts::PI),
+            eval_source("PI;").unwrap()
+        );
+    }
+
+    #[test]
+    fn ベクトル_func() {
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("vadd([1,2],[3,4]) == [4,6];").unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("vdot([1,0],[0,1]) == 0;").unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("vsub([3,4],[1,2]) == [2,2];").unwrap()
+        );
+        assert_eq!(ReturnType::F64(5.0), eval_source("vlen([3,4]);").unwrap());
+        assert!(matches!(
+            eval_source("vadd([1,2],[1,2,3]);"),
+            Err(RuntimeError::NotMatchDimension(2, 3))
+        ));
+        assert!(matches!(
+            eval_source("vdot([1,\"a\"],[1,2]);"),
+            Err(RuntimeError::OperandType(_))
+        ));
+    }
+
+    #[test]
+    fn 複製_func() {
+        let source = "var a = [[1, 2], [3]]; clone(a);".to_string();
+        let tokens = Scanner::new(&source).scan().unwrap();
+        let mut env = register_func(&Environment::new());
+        let mut ret = ReturnType::Void;
+        for ast in Parser::new(&tokens).program() {
+            ret = eval(&ast, &mut env).unwrap();
+        }
+
+        // 複製したリストの要素を変更しても、元のリストは変わらない
+        let original = ReturnType::List(
+            vec![
+                ReturnType::List(
+                    vec![ReturnType::F64(1.0), ReturnType::F64(2.0)],
+                    Frozen::default(),
+                ),
+                ReturnType::List(vec![ReturnType::F64(3.0)], Frozen::default()),
+            ],
+            Frozen::default(),
+        );
+        assert_eq!(original, ret);
+        if let ReturnType::List(ref mut l, _) = ret {
+            if let ReturnType::List(ref mut inner, _) = l[0] {
+                inner[0] = ReturnType::F64(100.0);
+            }
+        }
+        assert_ne!(original, ret);
+        assert_eq!(
+            Some(&Value::List(
+                match original {
+                    ReturnType::List(l, _) => l,
+                    _ => unreachable!(),
+                },
+                Frozen::default()
+            )),
+            env.get(&"a".to_string())
+        );
+
+        // 変更不可の値の複製は変更できる
+        assert_eq!(
+            ReturnType::F64(10.0),
+            eval_source("var l = clone(freeze([[1], 2])); l[0][0] = 10; l[0][0];").unwrap()
+        );
+        assert_eq!(
+            ReturnType::F64(3.0),
+            eval_source("var m = clone(freeze({\"a\": 1})); m[\"a\"] = 3; m[\"a\"];").unwrap()
+        );
+        assert!(matches!(
+            eval_source("var f = freeze([1]); var l = clone(f); l[0] = 2; f[0] = 2;"),
+            Err(RuntimeError::ModifyFrozen)
+        ));
+
+        // スカラー値はそのまま返す
+        assert_eq!(ReturnType::F64(1.0), eval_source("clone(1);").unwrap());
+        assert_eq!(
+            ReturnType::String("a".to_string()),
+            eval_source("clone(\"a\");").unwrap()
+        );
+    }
+
+    #[test]
+    fn フォーマット_func() {
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("format(\"{}-{}\", 1, 2) == \"1-2\";").unwrap()
+        );
+        assert_eq!(
+            ReturnType::String("1 + 2 = 3".to_string()),
+            eval_source("format(\"{} + {} = {}\", 1, 2, 1 + 2);").unwrap()
+        );
+        assert_eq!(
+            ReturnType::String("{a} [1, \"b\"] true".to_string()),
+            eval_source("format(\"{{{}}} {} {}\", \"a\", [1, \"b\"], true);").unwrap()
+        );
+        assert!(matches!(
+            eval_source("format(\"{}-{}\", 1);"),
+            Err(RuntimeError::NotMatchFormatArgsNum(2, 1))
+        ));
+        assert!(matches!(
+            eval_source("format(\"{}\", 1, 2);"),
+            Err(RuntimeError::NotMatchFormatArgsNum(1, 2))
+        ));
+        assert!(matches!(
+            eval_source("format(\"{\", 1);"),
+            Err(RuntimeError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn 数列_func() {
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("range(3) == [0,1,2];").unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("range(2,5) == [2,3,4];").unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("range(5,0,-2) == [5,3,1];").unwrap()
+        );
+        assert_eq!(
+            ReturnType::List(vec![], Frozen::default()),
+            eval_source("range(0);").unwrap()
+        );
+        // 小数の増分でも誤差が累積しない
+        assert_eq!(
+            ReturnType::List(
+                (0..10).map(|i| ReturnType::F64(i as f64 * 0.1)).collect(),
+                Frozen::default()
+            ),
+            eval_source("range(0, 1, 0.1);").unwrap()
+        );
+        assert!(matches!(
+            eval_source("range(0,5,0);"),
+            Err(RuntimeError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            eval_source("range(0, exp(1000));"),
+            Err(RuntimeError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            eval_source("range(0, 1, sqrt(-1));"),
+            Err(RuntimeError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            eval_source("range(1e300);"),
+            Err(RuntimeError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            eval_source("range();"),
+            Err(RuntimeError::NotMatchArgsNum)
+        ));
+        assert!(matches!(
+            eval_source("range(\"a\");"),
+            Err(RuntimeError::OperandType(_))
+        ));
+    }
+
+    #[test]
+    fn アサーション_func() {
+        assert_eq!(ReturnType::Void, eval_source("assert_eq(2, 1+1);").unwrap());
+        assert_eq!(
+            ReturnType::Void,
+            eval_source("assert_eq([1, \"a\"], [1, \"a\"]);").unwrap()
+        );
+        assert_eq!(ReturnType::Void, eval_source("assert_neq(1, 2);").unwrap());
+
+        let err = eval_source("assert_eq(1, 2);").unwrap_err();
+        assert!(matches!(err, RuntimeError::AssertionFailed(_, _, _)));
+        assert_eq!("assert_eq failed: left=1 right=2", err.to_string());
+        let err = eval_source("assert_neq(\"a\", \"a\");").unwrap_err();
+        assert_eq!("assert_neq failed: left=\"a\" right=\"a\"", err.to_string());
+
+        // ==で比較できない型同士はエラー
+        assert!(matches!(
+            eval_source("assert_eq(1, \"1\");"),
+            Err(RuntimeError::TwoOperandType(_, _))
+        ));
+    }
+
+    #[test]
+    fn ソート_func() {
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("sort([3,1,2]) == [1,2,3];").unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("sort([\"b\",\"c\",\"a\"]) == [\"a\",\"b\",\"c\"];").unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("sort([1,3,2], fun (a, b) { return b - a; }) == [3,2,1];").unwrap()
+        );
+        assert_eq!(
+            ReturnType::List(vec![], Frozen::default()),
+            eval_source("sort([]);").unwrap()
+        );
+
+        // 元のリストは変更しない
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("var l = [2,1]; sort(l); l == [2,1];").unwrap()
+        );
+
+        assert!(matches!(
+            eval_source("sort([1,\"a\"]);"),
+            Err(RuntimeError::TwoOperandType(_, _))
+        ));
+        assert!(matches!(
+            eval_source("sort([1,2], fun (a, b) { return true; });"),
+            Err(RuntimeError::OperandType(_))
+        ));
+        assert!(matches!(
+            eval_source("sort(1);"),
+            Err(RuntimeError::OperandType(_))
+        ));
+    }
+
+    #[test]
+    fn 変更不可_func() {
+        // 参照はできる
+        assert_eq!(
+            ReturnType::F64(2.0),
+            eval_source("var l = freeze([1,2]); l[1];").unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("var m = freeze({\"a\": [1]}); m[\"a\"] == [1];").unwrap()
+        );
+        // 変更不可フラグは比較に影響しない
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("freeze([1,2]) == [1,2];").unwrap()
+        );
+        assert_eq!(ReturnType::F64(1.0), eval_source("freeze(1);").unwrap());
+
+        // 要素への代入はエラー
+        assert!(matches!(
+            eval_source("var l = freeze([1,2]); l[0] = 3;"),
+            Err(RuntimeError::ModifyFrozen)
+        ));
+        assert!(matches!(
+            eval_source("var m = freeze({\"a\": 1}); m[\"b\"] = 2;"),
+            Err(RuntimeError::ModifyFrozen)
+        ));
+        // 入れ子の要素も変更不可
+        assert!(matches!(
+            eval_source("var l = freeze([[1]]); l[0][0] = 2;"),
+            Err(RuntimeError::ModifyFrozen)
+        ));
+        // 元のリストは変更できる
+        assert_eq!(
+            ReturnType::F64(3.0),
+            eval_source("var l = [1]; freeze(l); l[0] = 3; l[0];").unwrap()
+        );
+        // 変数自体への再代入はできる
+        assert_eq!(
+            ReturnType::F64(5.0),
+            eval_source("var l = freeze([1]); l = [1]; l[0] = 5; l[0];").unwrap()
+        );
+    }
+
+    #[test]
+    fn 文字分割_func() {
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("chars(\"abc\") == [\"a\",\"b\",\"c\"];").unwrap()
+        );
+        assert_eq!(
+            ReturnType::List(vec![], Frozen::default()),
+            eval_source("chars(\"\");").unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("chars(\"あい\") == [\"あ\",\"い\"];").unwrap()
+        );
+        assert!(matches!(
+            eval_source("chars(1);"),
+            Err(RuntimeError::OperandType(_))
+        ));
+
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("to_list(\"ab\") == [\"a\",\"b\"];").unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("to_list([1,2]) == [1,2];").unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("to_list({\"a\": 1, \"b\": 2}) == [\"a\",\"b\"];").unwrap()
+        );
+        // 変更不可のリストから、変更可能なリストを生成する
+        assert_eq!(
+            ReturnType::F64(3.0),
+            eval_source("var l = to_list(freeze([1])); l[0] = 3; l[0];").unwrap()
+        );
+        assert!(matches!(
+            eval_source("to_list(nil);"),
+            Err(RuntimeError::OperandType(_))
+        ));
+    }
+
+    #[test]
+    fn 文字列変換_func() {
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("str(true) == \"true\";").unwrap()
+        );
+        assert_eq!(
+            ReturnType::String("[true, false]".to_string()),
+            eval_source("str([true, false]);").unwrap()
+        );
+        assert_eq!(
+            ReturnType::String("{\"a\": false}".to_string()),
+            eval_source("str({\"a\": false});").unwrap()
+        );
+        assert_eq!(
+            ReturnType::String("true [false] 1.5".to_string()),
+            eval_source("format(\"{} {} {}\", true, [false], str(1.5));").unwrap()
+        );
+        assert!(matches!(
+            eval_source("str();"),
+            Err(RuntimeError::NotMatchArgsNum)
+        ));
+
+        let (out, err) = (SharedBuffer::default(), SharedBuffer::default());
+        output::set_writers(Box::new(out.clone()), Box::new(err.clone()));
+        eval_source("print [true, false]; print true; print format(\"{}\", false);").unwrap();
+        assert_eq!("[true, false]\ntrue\nfalse\n", out.contents());
+    }
+
+    #[test]
+    fn 終了_func() {
+        // 終了コードが不正な場合は終了せずにエラーとする
+        assert!(matches!(
+            eval_source("exit(1.5);"),
+            Err(RuntimeError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            eval_source("exit(256);"),
+            Err(RuntimeError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            eval_source("exit(\"1\");"),
+            Err(RuntimeError::OperandType(_))
+        ));
+        assert!(matches!(
+            eval_source("exit();"),
+            Err(RuntimeError::NotMatchArgsNum)
+        ));
+    }
+
+    #[test]
+    fn メモ化_func() {
+        // 同じ引数値での呼び出しは、関数を呼び出さない
+        let source = "var calls = 0;
+            fun square(n) { calls = calls + 1; return n * n; }
+            var fast = memoize(square);";
+        assert_eq!(
+            ReturnType::F64(2.0),
+            eval_source(&format!(
+                "{} fast(3); fast(4); fast(3); assert_eq(fast(4), 16); calls;",
+                source
+            ))
+            .unwrap()
+        );
+        assert_eq!(
+            ReturnType::F64(4.0),
+            eval_source(&format!(
+                "{} square(3); square(4); square(3); square(4); calls;",
+                source
+            ))
+            .unwrap()
+        );
+
+        // 再帰呼び出しもメモ化した関数を呼び出す
+        let source = "var calls = 0;
+            fun fib(n) { calls = calls + 1; if (n < 2) return n; return fib(n - 1) + fib(n - 2); }";
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source(&format!("{} fib(20) == 6765 and calls == 21891;", source)).unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source(&format!(
+                "{} fib = memoize(fib); fib(20) == 6765 and calls == 21;",
+                source
+            ))
+            .unwrap()
+        );
+
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("var f = memoize(fun (a, b) { return a + b; }); is_function(f) and arity(f) == 2 and f == f;")
+                .unwrap()
+        );
+        assert_eq!(
+            ReturnType::String("<fn>".to_string()),
+            eval_source("str(memoize(is_nil));").unwrap()
+        );
+        assert!(matches!(
+            eval_source("memoize(1);"),
+            Err(RuntimeError::OperandType(_))
+        ));
+    }
+
+    #[test]
+    fn 数学関数_func() {
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("sqrt(16) == 4;").unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("sign(-3) == -1 and sign(0) == 0 and sign(2.5) == 1;").unwrap()
+        );
+        assert_eq!(ReturnType::Bool(true), eval_source("sin(0) == 0;").unwrap());
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("cos(0) == 1 and tan(0) == 0 and abs(-2) == 2 and exp(0) == 1;").unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("log(E) == 1 and log(8, 2) == 3;").unwrap()
+        );
+
+        // 定義域外はNaN
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("is_nan(sqrt(-1)) and is_nan(log(-1));").unwrap()
+        );
+
+        assert!(matches!(
+            eval_source("sqrt(\"4\");"),
+            Err(RuntimeError::OperandType(_))
+        ));
+        assert!(matches!(
+            eval_source("sin(1, 2);"),
+            Err(RuntimeError::NotMatchArgsNum)
+        ));
+        assert!(matches!(
+            eval_source("log();"),
+            Err(RuntimeError::NotMatchArgsNum)
+        ));
+    }
+
+    #[test]
+    fn 固定小数点表記_func() {
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("fixed(3.14159, 2) == \"3.14\";").unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("fixed(5, 3) == \"5.000\";").unwrap()
+        );
+        assert_eq!(
+            ReturnType::String("3.10".to_string()),
+            eval_source("fixed(3.1, 2);").unwrap()
+        );
+        assert_eq!(
+            ReturnType::String("-3".to_string()),
+            eval_source("fixed(-2.6, 0);").unwrap()
+        );
+
+        assert!(matches!(
+            eval_source("fixed(1, -1);"),
+            Err(RuntimeError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            eval_source("fixed(1, 1.5);"),
+            Err(RuntimeError::InvalidArgument(_))
+        ));
+        // 桁数の上限
+        assert_eq!(
+            ReturnType::String(format!("1.{}", "0".repeat(100))),
+            eval_source("fixed(1, 100);").unwrap()
+        );
+        assert!(matches!(
+            eval_source("fixed(1, 101);"),
+            Err(RuntimeError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            eval_source("fixed(1, 70000);"),
+            Err(RuntimeError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            eval_source("fixed(\"1\", 2);"),
+            Err(RuntimeError::OperandType(_))
+        ));
+        assert!(matches!(
+            eval_source("fixed(1);"),
+            Err(RuntimeError::NotMatchArgsNum)
+        ));
+    }
+
+    #[test]
+    fn 真偽値の数値変換_func() {
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("bool_to_num(true) == 1;").unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("bool_to_num(false) == 0;").unwrap()
+        );
+        assert_eq!(
+            ReturnType::F64(1.0),
+            eval_source("bool_to_num(2 > 1) + bool_to_num(1 > 2);").unwrap()
+        );
+
+        // bool値の算術演算はエラー
+        assert!(matches!(
+            eval_source("true + 1;"),
+            Err(RuntimeError::TwoOperandType(_, _))
+        ));
+        assert!(matches!(
+            eval_source("bool_to_num(1);"),
+            Err(RuntimeError::OperandType(_))
+        ));
+        assert!(matches!(
+            eval_source("bool_to_num();"),
+            Err(RuntimeError::NotMatchArgsNum)
+        ));
+    }
+
+    #[test]
+    fn 環境変数_func() {
+        // 設定した値の取得はCLIのテストで確認する
+        assert_eq!(
+            ReturnType::Void,
+            eval_source("getenv(\"R_LOX_GETENV_TEST_UNSET\");").unwrap()
+        );
+
+        assert!(matches!(
+            eval_source("getenv(1);"),
+            Err(RuntimeError::OperandType(_))
+        ));
+        assert!(matches!(
+            eval_source("getenv();"),
+            Err(RuntimeError::NotMatchArgsNum)
+        ));
+    }
+
+    #[test]
+    fn 整数判定と切り捨て_func() {
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("is_integer(4);").unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(false),
+            eval_source("is_integer(4.5);").unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(false),
+            eval_source(&format!(
+                "is_integer(sqrt(-1)) or is_integer(1{} * 10);",
+                "0".repeat(308)
+            ))
+            .unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("trunc(4.9) == 4 and trunc(-4.9) == -4;").unwrap()
+        );
+
+        assert!(matches!(
+            eval_source("is_integer(\"4\");"),
+            Err(RuntimeError::OperandType(_))
+        ));
+        assert!(matches!(
+            eval_source("trunc(true);"),
+            Err(RuntimeError::OperandType(_))
+        ));
+    }
+
+    #[test]
+    fn エラー検証_func() {
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("expect_error(fun () { return 1 / 0; });").unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(false),
+            eval_source("expect_error(fun () { return 1; });").unwrap()
+        );
+        // エラーの後も評価を継続できる
+        assert_eq!(
+            ReturnType::F64(2.0),
+            eval_source("fun f() { return undefined; } var e = expect_error(f); if (e) 2; else 3;")
+                .unwrap()
+        );
+        // エラーまでに関数内で更新した変数は反映する
+        assert_eq!(
+            ReturnType::F64(1.0),
+            eval_source("var c = 0; fun f() { c = c + 1; return 1 / 0; } expect_error(f); c;")
+                .unwrap()
+        );
+
+        assert!(matches!(
+            eval_source("expect_error(1);"),
+            Err(RuntimeError::OperandType(_))
+        ));
+        assert!(matches!(
+            eval_source("expect_error();"),
+            Err(RuntimeError::NotMatchArgsNum)
+        ));
+    }
+
+    #[test]
+    fn 範囲制限_func() {
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("clamp(5, 0, 10) == 5;").unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("clamp(-1, 0, 10) == 0;").unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("clamp(20, 0, 10) == 10;").unwrap()
+        );
+        assert_eq!(
+            ReturnType::F64(3.0),
+            eval_source("clamp(7, 3, 3);").unwrap()
+        );
+
+        assert!(matches!(
+            eval_source("clamp(5, 10, 0);"),
+            Err(RuntimeError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            eval_source("clamp(5, \"0\", 10);"),
+            Err(RuntimeError::OperandType(_))
+        ));
+        assert!(matches!(
+            eval_source("clamp(5, 0);"),
+            Err(RuntimeError::NotMatchArgsNum)
+        ));
+    }
+
+    #[test]
+    fn 要素の組_func() {
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("zip([1, 2, 3], [4, 5]) == [[1, 4], [2, 5]];").unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("zip([], [1]) == [];").unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("zip([\"a\"], [true, nil]) == [[\"a\", true]];").unwrap()
+        );
+
+        assert!(matches!(
+            eval_source("zip([1], \"ab\");"),
+            Err(RuntimeError::OperandType(ReturnType::String(_)))
+        ));
+        assert!(matches!(
+            eval_source("zip(1, [1]);"),
+            Err(RuntimeError::OperandType(ReturnType::F64(_)))
+        ));
+        assert!(matches!(
+            eval_source("zip([1]);"),
+            Err(RuntimeError::NotMatchArgsNum)
+        ));
+    }
 }
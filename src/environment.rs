@@ -1,19 +1,28 @@
 use crate::ast::AstType;
-use std::collections::HashMap;
+use crate::eval::{Frozen, FuncBody, MemoFunc, NativeFunc, ReturnType};
+use std::collections::{HashMap, HashSet};
 
+// 組み込み関数の比較は関数ポインタのアドレス比較で十分なため、警告を抑止
+#[allow(unpredictable_function_pointer_comparisons)]
 #[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     F64(f64),
     String(String),
     Bool(bool),
-    UserFunc(Vec<AstType>, AstType),
-    EmbeddedFunc(fn()), // TODO: 可変長引数に対応したい
+    Nil,
+    Uninitialized, // 初期化式なしで定義され、まだ代入されていない変数
+    List(Vec<ReturnType>, Frozen),
+    Map(Vec<(ReturnType, ReturnType)>, Frozen),
+    UserFunc(Vec<AstType>, FuncBody),
+    EmbeddedFunc(NativeFunc),
+    MemoFunc(MemoFunc),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Environment {
     pub enclosing: Option<Box<Environment>>,
     variables: HashMap<String, Value>,
+    globals: HashSet<String>, // global文でグローバル変数として扱う変数名
 }
 
 impl Environment {
@@ -21,6 +30,7 @@ impl Environment {
         Environment {
             variables: HashMap::new(),
             enclosing: None,
+            globals: HashSet::new(),
         }
     }
 
@@ -36,19 +46,167 @@ impl Environment {
     }
 
     pub fn push(&mut self, key: String, value: Value) -> Option<Value> {
-        if self.variables.get(&key).is_some() {
-            self.variables.insert(key, value)
-        } else if self.enclosing.is_some() {
-            self.enclosing.as_mut().unwrap().push(key, value)
+        if self.globals.contains(&key) {
+            self.root_mut()
+                .variables
+                .get_mut(&key)
+                .map(|v| std::mem::replace(v, value))
+        } else if let Some(v) = self.variables.get_mut(&key) {
+            Some(std::mem::replace(v, value))
+        } else if let Some(enclosing) = self.enclosing.as_mut() {
+            enclosing.push(key, value)
         } else {
             None
         }
     }
 
     pub fn get(&self, key: &String) -> Option<&Value> {
+        if self.globals.contains(key) {
+            return self.root().variables.get(key);
+        }
         self.variables
             .get(key)
-            .or_else(|| self.enclosing.as_ref().unwrap().get(key))
+            .or_else(|| self.enclosing.as_ref().and_then(|e| e.get(key)))
+    }
+
+    /// グローバル変数として扱う変数名を登録
+    ///
+    /// 現在のスコープと内側のスコープでは、変数の参照、代入を最も外側のスコープに対して行う
+    ///
+    /// # Arguments
+    /// * `key` - 変数名
+    pub fn declare_global(&mut self, key: String) {
+        self.globals.insert(key);
+    }
+
+    /// 指定した数だけ外側のスコープの変数を取得
+    ///
+    /// 外側のスコープは検索せず、指定したスコープのみを参照する
+    ///
+    /// # Arguments
+    /// * `distance` - 外側へたどるスコープの数(0は現在のスコープ)
+    /// * `key` - 変数名
+    ///
+    /// # Return
+    /// * Option<&Value> - 変数の値。スコープ、変数が存在しない場合はNone
+    pub fn get_at(&self, distance: usize, key: &String) -> Option<&Value> {
+        self.ancestor(distance)
+            .and_then(|env| env.variables.get(key))
+    }
+
+    /// 指定した数だけ外側のスコープの変数へ代入
+    ///
+    /// # Arguments
+    /// * `distance` - 外側へたどるスコープの数(0は現在のスコープ)
+    /// * `key` - 変数名
+    /// * `value` - 代入する値
+    ///
+    /// # Return
+    /// * Option<Value> - 代入前の値。スコープ、変数が存在しない場合は代入せずNone
+    pub fn assign_at(&mut self, distance: usize, key: String, value: Value) -> Option<Value> {
+        self.ancestor_mut(distance)
+            .and_then(|env| env.variables.get_mut(&key))
+            .map(|v| std::mem::replace(v, value))
+    }
+
+    /// スコープの深さを取得
+    ///
+    /// # Return
+    /// * usize - 最も外側のスコープを1とした深さ
+    pub fn depth(&self) -> usize {
+        1 + self.enclosing.as_ref().map_or(0, |e| e.depth())
+    }
+
+    /// 指定した深さより内側のスコープを取り除く
+    ///
+    /// # Arguments
+    /// * `depth` - 残すスコープの深さ
+    ///
+    /// # Return
+    /// * Environment - 外側のスコープ
+    pub fn truncate(self, depth: usize) -> Environment {
+        let mut env = self;
+        while env.depth() > depth {
+            match env.enclosing {
+                Some(enclosing) => env = *enclosing,
+                None => break,
+            }
+        }
+
+        env
+    }
+
+    /// 指定した深さより内側のスコープを1つのスコープにまとめて複製
+    ///
+    /// 同名の変数は内側のスコープを優先する。外側のスコープは含めない
+    ///
+    /// # Arguments
+    /// * `depth` - まとめないスコープの深さ
+    ///
+    /// # Return
+    /// * Environment - まとめたスコープ(外側のスコープなし)
+    pub fn flatten(&self, depth: usize) -> Environment {
+        let mut frame = Environment::new();
+        let mut scope = Some(self);
+        for _ in depth..self.depth() {
+            let Some(env) = scope else { break };
+            for key in &env.globals {
+                if !frame.variables.contains_key(key) {
+                    frame.globals.insert(key.clone());
+                }
+            }
+            for (key, value) in &env.variables {
+                if !frame.globals.contains(key) && !frame.variables.contains_key(key) {
+                    frame.variables.insert(key.clone(), value.clone());
+                }
+            }
+            scope = env.enclosing.as_deref();
+        }
+
+        frame
+    }
+
+    fn root(&self) -> &Environment {
+        match self.enclosing.as_ref() {
+            Some(enclosing) => enclosing.root(),
+            None => self,
+        }
+    }
+
+    fn root_mut(&mut self) -> &mut Environment {
+        match self.enclosing {
+            Some(ref mut enclosing) => enclosing.root_mut(),
+            None => self,
+        }
+    }
+
+    fn ancestor(&self, distance: usize) -> Option<&Environment> {
+        match distance {
+            0 => Some(self),
+            _ => self.enclosing.as_ref()?.ancestor(distance - 1),
+        }
+    }
+
+    fn ancestor_mut(&mut self, distance: usize) -> Option<&mut Environment> {
+        match distance {
+            0 => Some(self),
+            _ => self.enclosing.as_mut()?.ancestor_mut(distance - 1),
+        }
+    }
+
+    /// 定義済みの変数名を取得
+    ///
+    /// # Return
+    /// * Vec<String> - 外側のスコープも含めた変数名(重複なし、昇順)
+    pub fn names(&self) -> Vec<String> {
+        let mut names = self.variables.keys().cloned().collect::<Vec<_>>();
+        if let Some(enclosing) = self.enclosing.as_ref() {
+            names.extend(enclosing.names());
+        }
+        names.sort();
+        names.dedup();
+
+        names
     }
 }
 
@@ -67,5 +225,89 @@ mod test {
         let mut block_env = Environment::with_enclosing(env.clone());
         block_env.define("a".to_string(), Value::F64(10.0));
         assert_eq!(&Value::F64(10.0), block_env.get(&"a".to_string()).unwrap());
+
+        // 未定義の変数
+        assert!(block_env.get(&"b".to_string()).is_none());
+
+        block_env.define("b".to_string(), Value::Nil);
+        assert_eq!(vec!["a", "b"], block_env.names());
+    }
+
+    #[test]
+    fn スコープ指定_環境テスト() {
+        let mut env = Environment::new();
+        env.define("x".to_string(), Value::F64(1.0));
+        env.define("y".to_string(), Value::F64(2.0));
+        let mut block_env = Environment::with_enclosing(env);
+        block_env.define("x".to_string(), Value::F64(10.0));
+        let x = "x".to_string();
+        let y = "y".to_string();
+
+        assert_eq!(Some(&Value::F64(10.0)), block_env.get_at(0, &x));
+        assert_eq!(Some(&Value::F64(1.0)), block_env.get_at(1, &x));
+        // 指定したスコープのみを参照する
+        assert_eq!(None, block_env.get_at(0, &y));
+        assert_eq!(None, block_env.get_at(2, &x));
+
+        assert_eq!(
+            Some(Value::F64(1.0)),
+            block_env.assign_at(1, x.clone(), Value::F64(5.0))
+        );
+        assert_eq!(Some(&Value::F64(5.0)), block_env.get_at(1, &x));
+        assert_eq!(Some(&Value::F64(10.0)), block_env.get_at(0, &x));
+
+        // 未定義の変数には代入しない
+        assert_eq!(None, block_env.assign_at(0, y.clone(), Value::Nil));
+        assert_eq!(None, block_env.get_at(0, &y));
+    }
+
+    #[test]
+    fn グローバル変数_環境テスト() {
+        let x = "x".to_string();
+        let mut env = Environment::new();
+        env.define(x.clone(), Value::F64(1.0));
+        let mut block_env = Environment::with_enclosing(env);
+        block_env.define(x.clone(), Value::F64(10.0));
+        let mut inner_env = Environment::with_enclosing(block_env);
+        inner_env.declare_global(x.clone());
+
+        // 途中のスコープの変数ではなく、最も外側のスコープの変数を参照、代入する
+        assert_eq!(Some(&Value::F64(1.0)), inner_env.get(&x));
+        assert_eq!(
+            Some(Value::F64(1.0)),
+            inner_env.push(x.clone(), Value::F64(2.0))
+        );
+        assert_eq!(Some(&Value::F64(10.0)), inner_env.get_at(1, &x));
+        assert_eq!(Some(&Value::F64(2.0)), inner_env.get_at(2, &x));
+
+        // グローバル変数が未定義の場合は代入しない
+        inner_env.declare_global("y".to_string());
+        assert_eq!(None, inner_env.push("y".to_string(), Value::Nil));
+    }
+
+    #[test]
+    fn スコープの統合_環境テスト() {
+        let (x, y, z) = ("x".to_string(), "y".to_string(), "z".to_string());
+        let mut env = Environment::new();
+        env.define(x.clone(), Value::F64(1.0));
+        let mut block_env = Environment::with_enclosing(env);
+        block_env.define(x.clone(), Value::F64(10.0));
+        block_env.define(y.clone(), Value::F64(20.0));
+        let mut inner_env = Environment::with_enclosing(block_env);
+        inner_env.define(y.clone(), Value::F64(200.0));
+        inner_env.declare_global(z.clone());
+        assert_eq!(3, inner_env.depth());
+
+        // 内側のスコープの値を優先し、最も外側のスコープは含めない
+        let frame = inner_env.flatten(1);
+        assert_eq!(1, frame.depth());
+        assert_eq!(Some(&Value::F64(10.0)), frame.get(&x));
+        assert_eq!(Some(&Value::F64(200.0)), frame.get(&y));
+        assert_eq!(None, frame.get(&z));
+
+        let env = inner_env.truncate(1);
+        assert_eq!(1, env.depth());
+        assert_eq!(Some(&Value::F64(1.0)), env.get(&x));
+        assert_eq!(None, env.get(&y));
     }
 }
@@ -1,15 +1,53 @@
 use crate::ast::AstType;
+use crate::config;
 use crate::environment::{Environment, Value};
+use crate::output;
+use crate::profile;
+#[cfg(feature = "vm")]
+use crate::vm::chunk::Chunk;
+#[cfg(feature = "vm")]
+use std::cell::OnceCell;
+use std::cell::{Cell, RefCell};
 use std::error;
 use std::fmt;
+use std::ops::Deref;
+use std::rc::Rc;
+
+thread_local! {
+    // 評価中のユーザー定義関数の呼び出しの深さ
+    static CALL_DEPTH: Cell<usize> = const { Cell::new(0) };
+    // 評価中のユーザー定義関数の呼び出し元の環境の深さ。関数の外ではNone(末尾呼び出しを行わない)
+    static TAIL_CALL_BASE: Cell<Option<usize>> = const { Cell::new(None) };
+}
 
 /// ランタイムエラー
 pub enum RuntimeError {
     OperandType(Operand),
     TwoOperandType(Operand, Operand),
+    ComparisonType {
+        op: &'static str,
+        left: Operand,
+        right: Operand,
+    },
     NotFoundVar(String),
     NotFoundFunc(String),
     NotMatchArgsNum,
+    NotMatchDestructureNum(usize, usize),
+    NotMatchDimension(usize, usize),
+    NotMatchFormatArgsNum(usize, usize),
+    InvalidFormat(String),
+    InvalidArgument(String),
+    AssertionFailed(String, Operand, Operand), // 関数名、左辺、右辺
+    IndexOutOfRange(i64, usize),               // インデックス、要素数
+    IntegerRange(Operand, &'static str),       // 値、整数として許容する範囲
+    NotFoundKey(Operand),
+    LoopLimitExceeded(usize),
+    CallDepthLimitExceeded(usize),
+    ArgumentLimitExceeded(usize),
+    ModifyFrozen,
+    UninitializedRead(String),
+    DivisionByZero,
+    Thrown(Operand), // throw文で渡された値
 }
 impl RuntimeError {
     fn operand_type(&self, operand: &Operand) -> Option<&str> {
@@ -19,11 +57,35 @@ impl RuntimeError {
             Some("f64")
         } else if one_type_check_bool(operand) {
             Some("bool")
+        } else if one_type_check_list(operand) {
+            Some("List")
+        } else if one_type_check_map(operand) {
+            Some("Map")
+        } else if one_type_check_func(operand) {
+            Some("function")
+        } else if one_type_check_void(operand) {
+            Some("nil")
         } else {
             None
         }
     }
 
+    fn type_name(&self, operand: &Operand) -> &str {
+        match operand {
+            ReturnType::Return(o) => self.type_name(o),
+            ReturnType::F64(_) => "number",
+            ReturnType::String(_) => "string",
+            ReturnType::Bool(_) => "bool",
+            ReturnType::List(_, _) => "list",
+            ReturnType::Map(_, _) => "map",
+            ReturnType::UserFunc(_, _) | ReturnType::EmbeddedFunc(_) | ReturnType::MemoFunc(_) => {
+                "function"
+            }
+            ReturnType::Void => "nil",
+            ReturnType::TailCall(_) | ReturnType::Break(_) | ReturnType::Continue(_) => "statement",
+        }
+    }
+
     fn print(&self) -> String {
         match self {
             Self::OperandType(o) => format!("invalid type: {:?}", self.operand_type(o)),
@@ -32,9 +94,51 @@ impl RuntimeError {
                 self.operand_type(l),
                 self.operand_type(r)
             ),
+            Self::ComparisonType { op, left, right } => format!(
+                "'{}' requires two numbers or two strings: left={} right={}",
+                op,
+                self.type_name(left),
+                self.type_name(right)
+            ),
             Self::NotFoundVar(v) => format!("Could not found variable: {:?}", v),
             Self::NotFoundFunc(v) => format!("Could not found function: {:?}", v),
             Self::NotMatchArgsNum => "Could not match Argument Length".to_string(),
+            Self::NotMatchDestructureNum(expect, actual) => format!(
+                "Could not match destructuring length: expect={} actual={}",
+                expect, actual
+            ),
+            Self::NotMatchDimension(left, right) => format!(
+                "Could not match vector dimension: left={} right={}",
+                left, right
+            ),
+            Self::NotMatchFormatArgsNum(expect, actual) => format!(
+                "Could not match format arguments: placeholders={} arguments={}",
+                expect, actual
+            ),
+            Self::InvalidFormat(template) => format!("Invalid format string: {:?}", template),
+            Self::InvalidArgument(reason) => format!("Invalid argument: {}", reason),
+            Self::IndexOutOfRange(index, len) => {
+                format!("Index out of range: index={} len={}", index, len)
+            }
+            Self::IntegerRange(value, range) => format!(
+                "Expected an integer in {}: {}",
+                range,
+                format_element(value)
+            ),
+            Self::NotFoundKey(key) => format!("Could not found key: {}", format_element(key)),
+            Self::AssertionFailed(name, l, r) => format!(
+                "{} failed: left={} right={}",
+                name,
+                format_element(l),
+                format_element(r)
+            ),
+            Self::LoopLimitExceeded(max) => format!("Loop iteration limit exceeded: {}", max),
+            Self::CallDepthLimitExceeded(max) => format!("Call depth limit exceeded: {}", max),
+            Self::ArgumentLimitExceeded(max) => format!("Argument limit exceeded: {}", max),
+            Self::ModifyFrozen => "Can not modify a frozen List or Map".to_string(),
+            Self::UninitializedRead(v) => format!("Read uninitialized variable: {:?}", v),
+            Self::DivisionByZero => "Division by zero".to_string(),
+            Self::Thrown(value) => format!("Uncaught exception: {}", format_element(value)),
         }
     }
 }
@@ -57,76 +161,498 @@ impl error::Error for RuntimeError {
     }
 }
 
+// 組み込み関数。引数と、呼び出し元の環境(ユーザー定義関数を呼び出す場合に使用)を受け取る
+pub type NativeFunc = fn(&[Operand], &mut Environment) -> EvalResult;
+
 // 評価結果
+// 組み込み関数の比較は関数ポインタのアドレス比較で十分なため、警告を抑止
+#[allow(unpredictable_function_pointer_comparisons)]
 #[derive(Clone, Debug, PartialEq)]
 pub enum ReturnType {
     Bool(bool),
     F64(f64),
     Void,
     String(String),
+    List(Vec<ReturnType>, Frozen),
+    Map(Vec<(ReturnType, ReturnType)>, Frozen), // キーと値の組(挿入順)
+    UserFunc(Vec<AstType>, FuncBody),           // 引数列、ブロック
+    EmbeddedFunc(NativeFunc),
+    MemoFunc(MemoFunc),
     Return(Box<ReturnType>),
+    TailCall(Box<TailCall>), // 関数内のreturn文での関数呼び出し
+    Break(Option<String>),
+    Continue(Option<String>),
 }
 pub type Operand = ReturnType;
-type EvalResult = Result<Operand, RuntimeError>;
+pub type EvalResult = Result<Operand, RuntimeError>;
+
+// List/Mapの変更不可フラグ
+//
+// freeze関数で立てる。比較には影響しないため、常に等しいとする
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Frozen(pub bool);
+impl PartialEq for Frozen {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+// ユーザー定義関数の本体
+//
+// 関数定義を評価するたびに生成し、複製しても同じ本体を共有する。
+// 比較は参照先の一致で行うため、同じ定義から生成された関数のみ等しくなる
+#[derive(Clone, Debug)]
+pub struct FuncBody {
+    body: Rc<AstType>,
+    #[cfg(feature = "vm")]
+    chunk: Rc<OnceCell<Option<Chunk>>>, // VMでのコンパイル結果(コンパイルできない場合はNone)
+}
+impl FuncBody {
+    pub fn new(body: AstType) -> Self {
+        FuncBody {
+            body: Rc::new(body),
+            #[cfg(feature = "vm")]
+            chunk: Rc::new(OnceCell::new()),
+        }
+    }
+
+    /// VMでのコンパイル結果
+    ///
+    /// 最初の呼び出し時にコンパイルし、以降は同じ本体を共有する関数間で使い回す
+    ///
+    /// # Arguments
+    /// * `compile` - 関数本体のコンパイル。コンパイルできない場合はNone
+    ///
+    /// # Return
+    /// * Option<&Chunk> - コンパイル結果
+    #[cfg(feature = "vm")]
+    pub(crate) fn chunk(&self, compile: impl FnOnce(&AstType) -> Option<Chunk>) -> Option<&Chunk> {
+        self.chunk.get_or_init(|| compile(&self.body)).as_ref()
+    }
+}
+impl PartialEq for FuncBody {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.body, &other.body)
+    }
+}
+impl Deref for FuncBody {
+    type Target = AstType;
+
+    fn deref(&self) -> &AstType {
+        &self.body
+    }
+}
+
+// return文での関数呼び出し
+//
+// 呼び出す関数と引数値に加え、呼び出し元の関数内のスコープを1つにまとめたものを保持する。
+// 呼び出される関数からは、通常の呼び出しと同様に呼び出し元の関数内の変数を参照できる
+#[derive(Clone, Debug)]
+pub struct TailCall {
+    args: Vec<AstType>,
+    body: FuncBody,
+    args_val: Vec<Operand>,
+    frame: Option<Environment>,
+}
+impl PartialEq for TailCall {
+    fn eq(&self, other: &Self) -> bool {
+        self.body == other.body && self.args_val == other.args_val
+    }
+}
+
+// memoize関数で生成した関数
+//
+// 呼び出す関数と、引数値ごとの戻り値を保持する。複製しても同じ保持内容を共有する。
+// 比較は参照先の一致で行う
+#[derive(Clone, Debug)]
+pub struct MemoFunc(Rc<Memo>);
+#[derive(Debug)]
+struct Memo {
+    func: Operand,
+    cache: RefCell<Vec<(Vec<Operand>, Operand)>>, // 引数値と戻り値の組
+}
+impl MemoFunc {
+    pub fn new(func: Operand) -> Self {
+        MemoFunc(Rc::new(Memo {
+            func,
+            cache: RefCell::new(vec![]),
+        }))
+    }
+
+    /// 呼び出す関数を取得
+    pub fn func(&self) -> &Operand {
+        &self.0.func
+    }
+
+    /// 関数呼び出し
+    ///
+    /// 同じ引数値で呼び出し済みの場合は、関数を呼び出さずに保持している戻り値を返す
+    ///
+    /// # Arguments
+    /// * `args_val` - 引数値
+    /// * `env` - 環境
+    ///
+    /// # Return
+    /// * EvalResult - 関数の戻り値
+    pub(crate) fn call(&self, args_val: &[Operand], env: &mut Environment) -> EvalResult {
+        let cached = self
+            .0
+            .cache
+            .borrow()
+            .iter()
+            .find(|(args, _)| args == args_val)
+            .map(|(_, ret)| ret.clone());
+        if let Some(ret) = cached {
+            return Ok(ret);
+        }
+
+        // 再帰呼び出しで保持内容を参照するため、呼び出し中は借用しない
+        let ret = call_value(self.func(), &args_val.to_vec(), env)?;
+        self.0
+            .cache
+            .borrow_mut()
+            .push((args_val.to_vec(), ret.clone()));
+
+        Ok(ret)
+    }
+}
+impl PartialEq for MemoFunc {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// トップレベルの関数定義の巻き上げ
+///
+/// 文を評価する前に関数定義を環境へ登録し、後方で定義した関数の参照や相互再帰を可能にする
+///
+/// # Arguments
+/// * `asts` - プログラムのAST
+/// * `env` - 環境
+pub fn hoist(asts: &[AstType], env: &mut Environment) {
+    asts.iter().for_each(|ast| {
+        if let AstType::Fun(fun_name, arguments, block) = ast {
+            let _ = fun_eval(fun_name, arguments, block, env);
+        }
+    });
+}
 
 /// AST評価
 ///
+/// 再帰の深さの上限に影響するため、ノードごとの評価は関数へ分け、eval自体のスタックフレームを小さく保つ
+///
 /// # Arguments
 /// * `ast` - AST
 pub fn eval(ast: &AstType, env: &mut Environment) -> EvalResult {
+    profile::record(ast);
     match ast {
-        AstType::True => Ok(ReturnType::Bool(true)),
-        AstType::False => Ok(ReturnType::Bool(false)),
-        AstType::Nil => Ok(ReturnType::Void),
-        AstType::Number(n) => Ok(ReturnType::F64(*n)),
-        AstType::String(s) => Ok(ReturnType::String(s.clone())),
-        AstType::Bang(o) => bang(eval(o, env)?),
-        AstType::UnaryMinus(o) => unary_minus(eval(o, env)?),
-        AstType::Plus(l, r) => plus(eval(l, env)?, eval(r, env)?),
-        AstType::Minus(l, r) => minus(eval(l, env)?, eval(r, env)?),
-        AstType::Mul(l, r) => mul(eval(l, env)?, eval(r, env)?),
-        AstType::Div(l, r) => div(eval(l, env)?, eval(r, env)?),
-        AstType::EqualEqual(l, r) => equal_equal(eval(l, env)?, eval(r, env)?),
-        AstType::BangEqual(l, r) => bang_equal(eval(l, env)?, eval(r, env)?),
-        AstType::Greater(l, r) => greater(eval(l, env)?, eval(r, env)?),
-        AstType::Less(l, r) => less(eval(l, env)?, eval(r, env)?),
-        AstType::GreaterEqual(l, r) => greater_equal(eval(l, env)?, eval(r, env)?),
-        AstType::LessEqual(l, r) => less_equal(eval(l, env)?, eval(r, env)?),
-        AstType::Print(o) => print_stmt(eval(o, env)?),
+        AstType::Global(names) => global_eval(names, env),
+        AstType::Bang(o) => unary_eval(bang, o, env),
+        AstType::UnaryMinus(o) => unary_eval(unary_minus, o, env),
+        AstType::BitNot(o) => unary_eval(bit_not, o, env),
+        AstType::Plus(l, r) => binary_eval(plus, l, r, env),
+        AstType::Minus(l, r) => binary_eval(minus, l, r, env),
+        AstType::Mul(l, r) => binary_eval(mul, l, r, env),
+        AstType::Div(l, r) => binary_eval(div, l, r, env),
+        AstType::Pow(l, r) => binary_eval(pow, l, r, env),
+        AstType::BitOr(l, r) => binary_eval(bit_or, l, r, env),
+        AstType::BitXor(l, r) => binary_eval(bit_xor, l, r, env),
+        AstType::BitAnd(l, r) => binary_eval(bit_and, l, r, env),
+        AstType::ShiftLeft(l, r) => binary_eval(shift_left, l, r, env),
+        AstType::ShiftRight(l, r) => binary_eval(shift_right, l, r, env),
+        AstType::EqualEqual(l, r) => binary_eval(equal_equal, l, r, env),
+        AstType::BangEqual(l, r) => binary_eval(bang_equal, l, r, env),
+        AstType::Greater(l, r) => binary_eval(greater, l, r, env),
+        AstType::Less(l, r) => binary_eval(less, l, r, env),
+        AstType::GreaterEqual(l, r) => binary_eval(greater_equal, l, r, env),
+        AstType::LessEqual(l, r) => binary_eval(less_equal, l, r, env),
+        AstType::Print(o) => unary_eval(print_stmt, o, env),
         AstType::Var(i, o) => var_decl(i, o, env),
+        AstType::MultiVar(i, o) => multi_var_decl(i, o, env),
+        AstType::MultiAssign(i, o) => multi_assign(i, o, env),
         AstType::Identifier(i) => identifier(i, env),
-        AstType::Assign(i, o) => assign(i, eval(o, env)?, env),
+        AstType::Assign(i, o) => assign_eval(i, o, env),
         AstType::Grouping(o) => eval(o, env),
+        AstType::List(o) => list(o, env),
+        AstType::Index(o, i) => binary_eval(index, o, i, env),
+        AstType::OptionalGet(o, name) => optional_get_eval(o, name, env),
+        AstType::IndexAssign(o, i, v) => index_assign(o, i, v, env),
+        AstType::Map(o) => map(o, env),
+        AstType::In(l, r) => binary_eval(in_eval, l, r, env),
         AstType::Block(o) => block(o, env),
         AstType::If(cond, if_stmt, else_stmt) => if_eval(cond, if_stmt, else_stmt, env),
-        AstType::Or(left, right) => or_eval(eval(left, env)?, eval(right, env)?),
-        AstType::And(left, right) => and_eval(eval(left, env)?, eval(right, env)?),
-        AstType::While(cond, stmt) => while_eval(cond, stmt, env),
+        AstType::Or(left, right) => logical_or(left, right, env),
+        AstType::And(left, right) => logical_and(left, right, env),
+        AstType::While(cond, stmt, increment, else_stmt) => {
+            while_eval(cond, stmt, increment, else_stmt, None, env)
+        }
+        AstType::Labeled(label, stmt) => labeled_eval(label, stmt, env),
+        AstType::Repeat(count, stmt) => repeat_eval(count, stmt, env),
+        AstType::Try(try_block, name, catch_block) => try_eval(try_block, name, catch_block, env),
         AstType::Call(callee, arguments) => call_eval(callee, arguments, env),
         AstType::Fun(fun_name, arguments, block) => fun_eval(fun_name, arguments, block, env),
+        AstType::Lambda(arguments, block) => lambda_eval(arguments, block),
         AstType::Return(o) => return_eval(o, env),
+        AstType::Throw(o) => unary_eval(throw_eval, o, env),
+        AstType::True
+        | AstType::False
+        | AstType::Nil
+        | AstType::Empty
+        | AstType::Number(_)
+        | AstType::String(_)
+        | AstType::Break(_)
+        | AstType::Continue(_) => Ok(literal(ast)),
+    }
+}
+
+/// リテラル、break、continue文評価
+///
+/// # Arguments
+/// * `ast` - AST
+///
+/// # Return
+/// * Operand - 評価後の値
+fn literal(ast: &AstType) -> Operand {
+    match ast {
+        AstType::True => ReturnType::Bool(true),
+        AstType::False => ReturnType::Bool(false),
+        AstType::Number(n) => ReturnType::F64(*n),
+        AstType::String(s) => ReturnType::String(s.clone()),
+        AstType::Break(label) => ReturnType::Break(label.clone()),
+        AstType::Continue(label) => ReturnType::Continue(label.clone()),
+        _ => ReturnType::Void,
+    }
+}
+
+/// global文評価
+///
+/// # Arguments
+/// * `names` - グローバル変数として扱う変数名列
+///
+/// # Return
+/// * EvalResult - 評価後の値
+fn global_eval(names: &[String], env: &mut Environment) -> EvalResult {
+    names
+        .iter()
+        .for_each(|name| env.declare_global(name.to_string()));
+
+    Ok(ReturnType::Void)
+}
+
+/// 代入式評価
+///
+/// # Arguments
+/// * `i` - 変数名
+/// * `o` - 代入する式
+///
+/// # Return
+/// * EvalResult - 代入した値
+fn assign_eval(i: &String, o: &AstType, env: &mut Environment) -> EvalResult {
+    let right = eval(o, env)?;
+    assign(i, right, env)
+}
+
+/// `?.`評価
+///
+/// # Arguments
+/// * `o` - マップの式
+/// * `name` - キー
+///
+/// # Return
+/// * EvalResult - 評価後の値
+fn optional_get_eval(o: &AstType, name: &str, env: &mut Environment) -> EvalResult {
+    optional_get(eval(o, env)?, name)
+}
+
+/// or演算子評価
+///
+/// 左辺がtrueの場合は右辺を評価しない
+///
+/// # Arguments
+/// * `left` - 左オペランド
+/// * `right` - 右オペランド
+///
+/// # Return
+/// * EvalResult - 評価後の値
+fn logical_or(left: &AstType, right: &AstType, env: &mut Environment) -> EvalResult {
+    match eval(left, env)? {
+        ReturnType::Bool(true) => Ok(ReturnType::Bool(true)),
+        left => or_eval(left, eval(right, env)?),
+    }
+}
+
+/// and演算子評価
+///
+/// 左辺がfalseの場合は右辺を評価しない
+///
+/// # Arguments
+/// * `left` - 左オペランド
+/// * `right` - 右オペランド
+///
+/// # Return
+/// * EvalResult - 評価後の値
+fn logical_and(left: &AstType, right: &AstType, env: &mut Environment) -> EvalResult {
+    match eval(left, env)? {
+        ReturnType::Bool(false) => Ok(ReturnType::Bool(false)),
+        left => and_eval(left, eval(right, env)?),
     }
 }
 
+/// 無名関数評価
+///
+/// # Arguments
+/// * `arguments` - 引数列
+/// * `block` - ブロック
+///
+/// # Return
+/// * EvalResult - 評価後の値
+fn lambda_eval(arguments: &[AstType], block: &AstType) -> EvalResult {
+    Ok(ReturnType::UserFunc(
+        arguments.to_owned(),
+        FuncBody::new(block.clone()),
+    ))
+}
+
+/// throw文評価
+///
+/// # Arguments
+/// * `value` - 送出する値
+///
+/// # Return
+/// * EvalResult - 常にエラー
+fn throw_eval(value: Operand) -> EvalResult {
+    Err(RuntimeError::Thrown(value))
+}
+
+/// 単項演算子評価
+///
+/// # Arguments
+/// * `op` - 演算
+/// * `o` - オペランド
+///
+/// # Return
+/// * EvalResult - 評価後の値
+fn unary_eval(op: fn(Operand) -> EvalResult, o: &AstType, env: &mut Environment) -> EvalResult {
+    op(eval(o, env)?)
+}
+
+/// 二項演算子評価
+///
+/// # Arguments
+/// * `op` - 演算
+/// * `l` - 左オペランド
+/// * `r` - 右オペランド
+///
+/// # Return
+/// * EvalResult - 評価後の値
+fn binary_eval(
+    op: fn(Operand, Operand) -> EvalResult,
+    l: &AstType,
+    r: &AstType,
+    env: &mut Environment,
+) -> EvalResult {
+    op(eval(l, env)?, eval(r, env)?)
+}
+
 /// 評価結果出力
+///
+/// 値がない場合(nil)は出力しない
 pub fn print(result: Operand) {
-    if one_type_check_f64(&result) {
-        println!("{}", downcast_f64(result))
-    } else if one_type_check_string(&result) {
-        println!("{}", downcast_string(result))
-    } else if one_type_check_bool(&result) {
-        println!("{}", downcast_bool(result))
+    match result {
+        ReturnType::Void
+        | ReturnType::TailCall(_)
+        | ReturnType::Break(_)
+        | ReturnType::Continue(_) => {}
+        ReturnType::Return(o) => print(*o),
+        _ => output::println(&format_value(&result)),
+    }
+}
+
+/// 評価結果を文字列へ変換
+///
+/// # Arguments
+/// * `operand` - オペランド
+///
+/// # Return
+/// * String - 変換後の文字列
+pub fn format_value(operand: &Operand) -> String {
+    match operand {
+        ReturnType::F64(f) => format_number(*f),
+        ReturnType::String(s) => s.to_string(),
+        ReturnType::Bool(b) => format_bool(*b),
+        ReturnType::Void => "nil".to_string(),
+        ReturnType::List(l, _) => format!(
+            "[{}]",
+            l.iter().map(format_element).collect::<Vec<_>>().join(", ")
+        ),
+        ReturnType::Map(m, _) => format!(
+            "{{{}}}",
+            m.iter()
+                .map(|(k, v)| format!("{}: {}", format_element(k), format_element(v)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        ReturnType::UserFunc(_, _) => "<fn>".to_string(),
+        ReturnType::EmbeddedFunc(_) => "<native fn>".to_string(),
+        ReturnType::MemoFunc(_) => "<fn>".to_string(),
+        ReturnType::Return(o) => format_value(o),
+        ReturnType::TailCall(_) | ReturnType::Break(_) | ReturnType::Continue(_) => String::new(),
+    }
+}
+
+/// リスト、マップの要素を文字列へ変換
+///
+/// # Arguments
+/// * `operand` - 要素
+///
+/// # Return
+/// * String - 変換後の文字列。文字列はダブルクォーテーションで囲む
+pub(crate) fn format_element(operand: &Operand) -> String {
+    match operand {
+        ReturnType::String(s) => format!("{:?}", s),
+        _ => format_value(operand),
+    }
+}
+
+/// bool値を文字列へ変換
+///
+/// print、リスト、マップの要素、format関数など、全ての出力で使用する
+///
+/// # Arguments
+/// * `b` - bool値
+///
+/// # Return
+/// * String - 変換後の文字列(true/false)
+pub fn format_bool(b: bool) -> String {
+    if b { "true" } else { "false" }.to_string()
+}
+
+/// 数値を文字列へ変換
+///
+/// 整数値は小数点なしで表示する。負のゼロは0と表示する
+///
+/// # Arguments
+/// * `n` - 数値
+///
+/// # Return
+/// * String - 変換後の文字列
+pub fn format_number(n: f64) -> String {
+    if n == 0.0 {
+        "0".to_string()
+    } else {
+        n.to_string()
     }
 }
 
 /// プラス演算子評価
 ///
 /// # Arguments
-// * `right` - 右オペランド
+/// * `left` - 左オペランド
+/// * `right` - 右オペランド
 ///
 /// # Return
-/// * Operand - 評価後の値（f64 or String）
-fn plus(left: Operand, right: Operand) -> EvalResult {
+/// * Operand - 評価後の値（f64 or String or List）。List同士は連結した新しいList
+pub(crate) fn plus(left: Operand, right: Operand) -> EvalResult {
     if type_check_f64(&left, &right) {
         Ok(ReturnType::F64(downcast_f64(left) + downcast_f64(right)))
     } else if type_check_string(&left, &right) {
@@ -135,6 +661,10 @@ fn plus(left: Operand, right: Operand) -> EvalResult {
             downcast_string(left),
             downcast_string(right)
         )))
+    } else if type_check_list(&left, &right) {
+        let mut list = downcast_list(left);
+        list.extend(downcast_list(right));
+        Ok(ReturnType::List(list, Frozen::default()))
     } else {
         Err(RuntimeError::TwoOperandType(left, right))
     }
@@ -148,7 +678,7 @@ fn plus(left: Operand, right: Operand) -> EvalResult {
 ///
 /// # Return
 /// * EvalResult - 評価後の値（f64）
-fn minus(left: Operand, right: Operand) -> EvalResult {
+pub(crate) fn minus(left: Operand, right: Operand) -> EvalResult {
     if type_check_f64(&left, &right) {
         Ok(ReturnType::F64(downcast_f64(left) - downcast_f64(right)))
     } else {
@@ -158,15 +688,58 @@ fn minus(left: Operand, right: Operand) -> EvalResult {
 
 /// 積算演算子評価
 ///
+/// 文字列と数値の場合は、文字列を繰り返す(`"ab" * 2`、`2 * "ab"`)
+///
 /// # Arguments
 /// * `left` - 左オペランド
 /// * `right` - 右オペランド
 ///
 /// # Return
-/// * EvalResult - 評価後の値（f64）
-fn mul(left: Operand, right: Operand) -> EvalResult {
+/// * EvalResult - 評価後の値（f64 or String）
+pub(crate) fn mul(left: Operand, right: Operand) -> EvalResult {
     if type_check_f64(&left, &right) {
         Ok(ReturnType::F64(downcast_f64(left) * downcast_f64(right)))
+    } else if one_type_check_string(&left) && one_type_check_f64(&right) {
+        repeat_string(downcast_string(left), downcast_f64(right))
+    } else if one_type_check_f64(&left) && one_type_check_string(&right) {
+        repeat_string(downcast_string(right), downcast_f64(left))
+    } else {
+        Err(RuntimeError::TwoOperandType(left, right))
+    }
+}
+
+/// 文字列の繰り返し
+///
+/// # Arguments
+/// * `s` - 文字列
+/// * `count` - 繰り返し回数
+///
+/// # Return
+/// * EvalResult - 評価後の値（String）。繰り返し回数が0以上の整数でない場合はエラー
+fn repeat_string(s: String, count: f64) -> EvalResult {
+    if count.fract() != 0.0 || count < 0.0 {
+        return Err(RuntimeError::InvalidArgument(format!(
+            "repeat count must be a non-negative integer: {}",
+            format_number(count)
+        )));
+    }
+
+    Ok(ReturnType::String(s.repeat(count as usize)))
+}
+
+/// べき乗演算子評価
+///
+/// # Arguments
+/// * `left` - 左オペランド（底）
+/// * `right` - 右オペランド（指数）
+///
+/// # Return
+/// * EvalResult - 評価後の値（f64）
+pub(crate) fn pow(left: Operand, right: Operand) -> EvalResult {
+    if type_check_f64(&left, &right) {
+        Ok(ReturnType::F64(
+            downcast_f64(left).powf(downcast_f64(right)),
+        ))
     } else {
         Err(RuntimeError::TwoOperandType(left, right))
     }
@@ -179,15 +752,122 @@ fn mul(left: Operand, right: Operand) -> EvalResult {
 /// * `right` - 右オペランド
 ///
 /// # Return
-/// * EvalResult - 評価後の値（f64）
-fn div(left: Operand, right: Operand) -> EvalResult {
+/// * EvalResult - 評価後の値（f64）。0による除算はエラー
+pub(crate) fn div(left: Operand, right: Operand) -> EvalResult {
     if type_check_f64(&left, &right) {
-        Ok(ReturnType::F64(downcast_f64(left) / downcast_f64(right)))
+        let (l, r) = (downcast_f64(left), downcast_f64(right));
+        if r == 0.0 {
+            return Err(RuntimeError::DivisionByZero);
+        }
+        Ok(ReturnType::F64(l / r))
     } else {
         Err(RuntimeError::TwoOperandType(left, right))
     }
 }
 
+/// ビット演算用に整数へ変換
+///
+/// # Arguments
+/// * `operand` - オペランド
+///
+/// # Return
+/// * Result<i64, RuntimeError> - 数値以外の場合は型のエラー、i64で表せない数値の場合は範囲のエラー
+fn to_integer(operand: &Operand) -> Result<i64, RuntimeError> {
+    match operand {
+        ReturnType::Return(o) => to_integer(o),
+        // i64::MAX as f64は2^63に丸められるため、上限は2^63未満で判定する
+        ReturnType::F64(n)
+            if n.fract() == 0.0 && *n >= -9223372036854775808.0 && *n < 9223372036854775808.0 =>
+        {
+            Ok(*n as i64)
+        }
+        ReturnType::F64(_) => Err(RuntimeError::IntegerRange(operand.clone(), "[-2^63, 2^63)")),
+        _ => Err(RuntimeError::OperandType(operand.clone())),
+    }
+}
+
+/// シフト量へ変換
+///
+/// # Arguments
+/// * `operand` - オペランド
+///
+/// # Return
+/// * Result<u32, RuntimeError> - 0〜63以外の場合はエラー
+fn to_shift(operand: &Operand) -> Result<u32, RuntimeError> {
+    match to_integer(operand)? {
+        n @ 0..=63 => Ok(n as u32),
+        _ => Err(RuntimeError::IntegerRange(operand.clone(), "[0, 63]")),
+    }
+}
+
+/// |演算子評価
+///
+/// # Arguments
+/// * `left` - 左オペランド
+/// * `right` - 右オペランド
+///
+/// # Return
+/// * EvalResult - 評価後の値（f64）
+pub(crate) fn bit_or(left: Operand, right: Operand) -> EvalResult {
+    Ok(ReturnType::F64(
+        (to_integer(&left)? | to_integer(&right)?) as f64,
+    ))
+}
+
+/// ^演算子評価
+///
+/// # Arguments
+/// * `left` - 左オペランド
+/// * `right` - 右オペランド
+///
+/// # Return
+/// * EvalResult - 評価後の値（f64）
+pub(crate) fn bit_xor(left: Operand, right: Operand) -> EvalResult {
+    Ok(ReturnType::F64(
+        (to_integer(&left)? ^ to_integer(&right)?) as f64,
+    ))
+}
+
+/// &演算子評価
+///
+/// # Arguments
+/// * `left` - 左オペランド
+/// * `right` - 右オペランド
+///
+/// # Return
+/// * EvalResult - 評価後の値（f64）
+pub(crate) fn bit_and(left: Operand, right: Operand) -> EvalResult {
+    Ok(ReturnType::F64(
+        (to_integer(&left)? & to_integer(&right)?) as f64,
+    ))
+}
+
+/// <<演算子評価
+///
+/// # Arguments
+/// * `left` - 左オペランド
+/// * `right` - 右オペランド（0〜63）
+///
+/// # Return
+/// * EvalResult - 評価後の値（f64）
+pub(crate) fn shift_left(left: Operand, right: Operand) -> EvalResult {
+    let shift = to_shift(&right)?;
+    Ok(ReturnType::F64((to_integer(&left)? << shift) as f64))
+}
+
+/// >>演算子評価
+///
+/// # Arguments
+/// * `left` - 左オペランド
+/// * `right` - 右オペランド（0〜63）
+///
+/// # Return
+/// * EvalResult - 評価後の値（f64）
+pub(crate) fn shift_right(left: Operand, right: Operand) -> EvalResult {
+    let shift = to_shift(&right)?;
+    Ok(ReturnType::F64((to_integer(&left)? >> shift) as f64))
+}
+
 /// -演算子評価
 ///
 /// # Arguments
@@ -195,7 +875,7 @@ fn div(left: Operand, right: Operand) -> EvalResult {
 ///
 /// # Return
 /// * EvalResult - 評価後の値（f64）
-fn unary_minus(operand: Operand) -> EvalResult {
+pub(crate) fn unary_minus(operand: Operand) -> EvalResult {
     if one_type_check_f64(&operand) {
         Ok(ReturnType::F64(-(downcast_f64(operand))))
     } else {
@@ -203,6 +883,17 @@ fn unary_minus(operand: Operand) -> EvalResult {
     }
 }
 
+/// ~演算子評価
+///
+/// # Arguments
+/// * `operand` - オペランド
+///
+/// # Return
+/// * EvalResult - 評価後の値（f64）
+pub(crate) fn bit_not(operand: Operand) -> EvalResult {
+    Ok(ReturnType::F64(!to_integer(&operand)? as f64))
+}
+
 /// !演算子評価
 ///
 /// # Arguments
@@ -210,7 +901,7 @@ fn unary_minus(operand: Operand) -> EvalResult {
 ///
 /// # Return
 /// * EvalResult - 評価後の値（bool）
-fn bang(operand: Operand) -> EvalResult {
+pub(crate) fn bang(operand: Operand) -> EvalResult {
     if one_type_check_bool(&operand) {
         Ok(ReturnType::Bool(!downcast_bool(operand)))
     } else if one_type_check_void(&operand) {
@@ -222,13 +913,16 @@ fn bang(operand: Operand) -> EvalResult {
 
 /// ==演算子評価
 ///
+/// 数値はIEEE 754に従って比較する。`0 == -0`はtrue、NaNはNaN自身を含めどの値とも等しくない
+/// (リスト、マップの要素も同様)
+///
 /// # Arguments
 /// * `left` - 左オペランド
 /// * `right` - 右オペランド
 ///
 /// # Return
 /// * EvalResult - 評価後の値（bool）
-fn equal_equal(left: Operand, right: Operand) -> EvalResult {
+pub(crate) fn equal_equal(left: Operand, right: Operand) -> EvalResult {
     if type_check_f64(&left, &right) {
         Ok(ReturnType::Bool(downcast_f64(left) == downcast_f64(right)))
     } else if type_check_string(&left, &right) {
@@ -239,6 +933,15 @@ fn equal_equal(left: Operand, right: Operand) -> EvalResult {
         Ok(ReturnType::Bool(
             downcast_bool(left) == downcast_bool(right),
         ))
+    } else if type_check_list(&left, &right) {
+        Ok(ReturnType::Bool(
+            downcast_list(left) == downcast_list(right),
+        ))
+    } else if one_type_check_map(&left) && one_type_check_map(&right) {
+        Ok(ReturnType::Bool(left == right))
+    } else if one_type_check_func(&left) && one_type_check_func(&right) {
+        // 関数は同じ定義から生成されたものだけが等しい
+        Ok(ReturnType::Bool(left == right))
     } else {
         Err(RuntimeError::TwoOperandType(left, right))
     }
@@ -252,7 +955,7 @@ fn equal_equal(left: Operand, right: Operand) -> EvalResult {
 ///
 /// # Return
 /// * EvalResult - 評価後の値（bool）
-fn bang_equal(left: Operand, right: Operand) -> EvalResult {
+pub(crate) fn bang_equal(left: Operand, right: Operand) -> EvalResult {
     let ret = equal_equal(left, right)?;
 
     match ret {
@@ -269,19 +972,19 @@ fn bang_equal(left: Operand, right: Operand) -> EvalResult {
 ///
 /// # Return
 /// * EvalResult - 評価後の値（bool）
-fn greater(left: Operand, right: Operand) -> EvalResult {
+pub(crate) fn greater(left: Operand, right: Operand) -> EvalResult {
     if type_check_f64(&left, &right) {
         Ok(ReturnType::Bool(downcast_f64(left) > downcast_f64(right)))
     } else if type_check_string(&left, &right) {
         Ok(ReturnType::Bool(
             downcast_string(left) > downcast_string(right),
         ))
-    } else if type_check_bool(&left, &right) {
-        Ok(ReturnType::Bool(
-            downcast_bool(left) & !(downcast_bool(right)),
-        ))
     } else {
-        Err(RuntimeError::TwoOperandType(left, right))
+        Err(RuntimeError::ComparisonType {
+            op: ">",
+            left,
+            right,
+        })
     }
 }
 
@@ -293,19 +996,19 @@ fn greater(left: Operand, right: Operand) -> EvalResult {
 ///
 /// # Return
 /// * EvalResult - 評価後の値（bool）
-fn greater_equal(left: Operand, right: Operand) -> EvalResult {
+pub(crate) fn greater_equal(left: Operand, right: Operand) -> EvalResult {
     if type_check_f64(&left, &right) {
         Ok(ReturnType::Bool(downcast_f64(left) >= downcast_f64(right)))
     } else if type_check_string(&left, &right) {
         Ok(ReturnType::Bool(
             downcast_string(left) >= downcast_string(right),
         ))
-    } else if type_check_bool(&left, &right) {
-        Ok(ReturnType::Bool(
-            downcast_bool(left) >= downcast_bool(right),
-        ))
     } else {
-        Err(RuntimeError::TwoOperandType(left, right))
+        Err(RuntimeError::ComparisonType {
+            op: ">=",
+            left,
+            right,
+        })
     }
 }
 
@@ -317,19 +1020,19 @@ fn greater_equal(left: Operand, right: Operand) -> EvalResult {
 ///
 /// # Return
 /// * EvalResult - 評価後の値（bool）
-fn less(left: Operand, right: Operand) -> EvalResult {
+pub(crate) fn less(left: Operand, right: Operand) -> EvalResult {
     if type_check_f64(&left, &right) {
         Ok(ReturnType::Bool(downcast_f64(left) < downcast_f64(right)))
     } else if type_check_string(&left, &right) {
         Ok(ReturnType::Bool(
             downcast_string(left) < downcast_string(right),
         ))
-    } else if type_check_bool(&left, &right) {
-        Ok(ReturnType::Bool(
-            !downcast_bool(left) & downcast_bool(right),
-        ))
     } else {
-        Err(RuntimeError::TwoOperandType(left, right))
+        Err(RuntimeError::ComparisonType {
+            op: "<",
+            left,
+            right,
+        })
     }
 }
 
@@ -341,19 +1044,19 @@ fn less(left: Operand, right: Operand) -> EvalResult {
 ///
 /// # Return
 /// * EvalResult - 評価後の値（bool）
-fn less_equal(left: Operand, right: Operand) -> EvalResult {
+pub(crate) fn less_equal(left: Operand, right: Operand) -> EvalResult {
     if type_check_f64(&left, &right) {
         Ok(ReturnType::Bool(downcast_f64(left) <= downcast_f64(right)))
     } else if type_check_string(&left, &right) {
         Ok(ReturnType::Bool(
             downcast_string(left) <= downcast_string(right),
         ))
-    } else if type_check_bool(&left, &right) {
-        Ok(ReturnType::Bool(
-            downcast_bool(left) <= downcast_bool(right),
-        ))
     } else {
-        Err(RuntimeError::TwoOperandType(left, right))
+        Err(RuntimeError::ComparisonType {
+            op: "<=",
+            left,
+            right,
+        })
     }
 }
 
@@ -364,83 +1067,379 @@ fn less_equal(left: Operand, right: Operand) -> EvalResult {
 ///
 /// # Return
 /// * EvalResult - 評価後の値（f64）
-fn print_stmt(operand: Operand) -> EvalResult {
-    print(operand);
+pub(crate) fn print_stmt(operand: Operand) -> EvalResult {
+    output::println(&format_value(&operand));
 
     Ok(ReturnType::Void)
 }
 
-/// 変数定義評価
+/// 変数定義評価
+///
+/// # Arguments
+/// * `i` - 変数名
+/// * `right` - 初期化式
+///
+/// # Return
+/// * EvalResult - 評価後の値
+fn var_decl(i: &String, operand: &Option<Box<AstType>>, env: &mut Environment) -> EvalResult {
+    // 初期化式が省略された場合は、未初期化として定義する
+    let value = match operand {
+        Some(operand) => to_env_value(eval(operand, env)?)?,
+        None => Value::Uninitialized,
+    };
+    env.define(i.to_string(), value);
+
+    Ok(ReturnType::Void)
+}
+
+/// 分割代入による変数定義評価
+///
+/// # Arguments
+/// * `identifiers` - 変数名列
+/// * `operand` - 初期化式（リスト）
+///
+/// # Return
+/// * EvalResult - 評価後の値
+fn multi_var_decl(identifiers: &[String], operand: &AstType, env: &mut Environment) -> EvalResult {
+    let right = eval(operand, env)?;
+    if !one_type_check_list(&right) {
+        return Err(RuntimeError::OperandType(right));
+    }
+
+    let values = downcast_list(right);
+    if identifiers.len() != values.len() {
+        return Err(RuntimeError::NotMatchDestructureNum(
+            identifiers.len(),
+            values.len(),
+        ));
+    }
+    for (i, value) in identifiers.iter().zip(values) {
+        env.define(i.to_string(), to_env_value(value)?);
+    }
+
+    Ok(ReturnType::Void)
+}
+
+/// 変数参照評価
+///
+/// # Arguments
+/// * `i` - 変数名
+///
+/// # Return
+/// * EvalResult - 評価後の値（bool/f64/String）
+pub(crate) fn identifier(i: &String, env: &mut Environment) -> EvalResult {
+    let val = env.get(i);
+    if let Some(val) = val {
+        match val {
+            Value::F64(f) => Ok(ReturnType::F64(*f)),
+            Value::String(s) => Ok(ReturnType::String(s.to_string())),
+            Value::Bool(b) => Ok(ReturnType::Bool(*b)),
+            Value::List(l, frozen) => Ok(ReturnType::List(l.clone(), *frozen)),
+            Value::Map(m, frozen) => Ok(ReturnType::Map(m.clone(), *frozen)),
+            // 未初期化の変数はnilとして扱う。strictモードではエラー
+            Value::Uninitialized if config::get().strict => {
+                Err(RuntimeError::UninitializedRead(i.to_string()))
+            }
+            Value::Nil | Value::Uninitialized => Ok(ReturnType::Void),
+            Value::UserFunc(args, body) => Ok(ReturnType::UserFunc(args.clone(), body.clone())),
+            Value::EmbeddedFunc(f) => Ok(ReturnType::EmbeddedFunc(*f)),
+            Value::MemoFunc(m) => Ok(ReturnType::MemoFunc(m.clone())),
+        }
+    } else {
+        Err(not_found(RuntimeError::NotFoundVar(i.to_string()), env))
+    }
+}
+
+/// 代入式評価
+///
+/// # Arguments
+/// * `i` - 変数名
+/// * `right` - 初期化式
+///
+/// # Return
+/// * EvalResult - 代入した値
+pub(crate) fn assign(i: &String, right: Operand, env: &mut Environment) -> EvalResult {
+    let val = env.get(i);
+    if val.is_some() {
+        // 変数に対する値を更新し、代入した値を返す
+        let value = to_env_value(right.clone())?;
+        env.push(i.to_string(), value);
+
+        Ok(right)
+    } else {
+        Err(not_found(RuntimeError::NotFoundVar(i.to_string()), env))
+    }
+}
+
+/// 複数代入評価
+///
+/// 右辺を全て評価してから代入するため、`a, b = b, a;`で値を入れ替えられる
+/// 右辺が1つの場合は、複数戻り値として分割代入する
+///
+/// # Arguments
+/// * `identifiers` - 変数名列
+/// * `operands` - 代入式列
+///
+/// # Return
+/// * EvalResult - 評価結果
+fn multi_assign(identifiers: &[String], operands: &[AstType], env: &mut Environment) -> EvalResult {
+    let mut values = operands
+        .iter()
+        .map(|o| eval(o, env))
+        .collect::<Result<Vec<_>, _>>()?;
+    if values.len() == 1 && one_type_check_list(&values[0]) {
+        values = downcast_list(values.remove(0));
+    }
+    if identifiers.len() != values.len() {
+        return Err(RuntimeError::NotMatchDestructureNum(
+            identifiers.len(),
+            values.len(),
+        ));
+    }
+    if let Some(i) = identifiers.iter().find(|i| env.get(i).is_none()) {
+        return Err(not_found(RuntimeError::NotFoundVar(i.to_string()), env));
+    }
+
+    identifiers
+        .iter()
+        .zip(values)
+        .try_for_each(|(i, value)| assign(i, value, env).map(|_| ()))?;
+
+    Ok(ReturnType::Void)
+}
+
+/// 環境に格納する値を取得
+/// # Arguments
+/// * `operand` - オペランド
+///
+/// # Return
+/// * Result<Value, RuntimeError> - 変換後のValue。変数に格納できない値(break/continue)はエラー
+pub fn to_env_value(operand: Operand) -> Result<Value, RuntimeError> {
+    match operand {
+        ReturnType::String(s) => Ok(Value::String(s)),
+        ReturnType::F64(n) => Ok(Value::F64(n)),
+        ReturnType::Bool(b) => Ok(Value::Bool(b)),
+        ReturnType::Void => Ok(Value::Nil),
+        ReturnType::List(l, frozen) => Ok(Value::List(l, frozen)),
+        ReturnType::Map(m, frozen) => Ok(Value::Map(m, frozen)),
+        ReturnType::UserFunc(args, body) => Ok(Value::UserFunc(args, body)),
+        ReturnType::EmbeddedFunc(f) => Ok(Value::EmbeddedFunc(f)),
+        ReturnType::MemoFunc(m) => Ok(Value::MemoFunc(m)),
+        ReturnType::Return(o) => to_env_value(*o),
+        ReturnType::TailCall(_) | ReturnType::Break(_) | ReturnType::Continue(_) => {
+            Err(RuntimeError::OperandType(operand))
+        }
+    }
+}
+
+/// リスト評価
+///
+/// # Arguments
+/// * `elements` - 要素のAst配列
+///
+/// # Return
+/// * EvalResult - 評価後の値（List）
+fn list(elements: &[AstType], env: &mut Environment) -> EvalResult {
+    let values = elements
+        .iter()
+        .map(|e| eval(e, env))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(ReturnType::List(values, Frozen::default()))
+}
+
+/// マップ評価
+///
+/// 同じキーが複数ある場合は、後の値で上書きする
+///
+/// # Arguments
+/// * `entries` - キーと値のAst配列
+///
+/// # Return
+/// * EvalResult - 評価後の値（Map）
+fn map(entries: &[(AstType, AstType)], env: &mut Environment) -> EvalResult {
+    let mut values: Vec<(Operand, Operand)> = vec![];
+    for (key, value) in entries {
+        let key = eval(key, env)?;
+        let value = eval(value, env)?;
+        match values.iter_mut().find(|(k, _)| *k == key) {
+            Some(entry) => entry.1 = value,
+            None => values.push((key, value)),
+        }
+    }
+
+    Ok(ReturnType::Map(values, Frozen::default()))
+}
+
+/// インデックス参照評価
+///
+/// # Arguments
+/// * `container` - 参照先（List/Map/String）
+/// * `index` - インデックス（Mapの場合はキー）
+///
+/// # Return
+/// * EvalResult - 評価後の値
+fn index(container: Operand, index: Operand) -> EvalResult {
+    match container {
+        ReturnType::Return(o) => self::index(*o, index),
+        ReturnType::List(mut l, _) => {
+            let i = list_index(&index, l.len())?;
+            Ok(l.swap_remove(i))
+        }
+        ReturnType::Map(m, _) => m
+            .into_iter()
+            .find(|(k, _)| *k == index)
+            .map(|(_, v)| v)
+            .ok_or(RuntimeError::NotFoundKey(index)),
+        ReturnType::String(s) => {
+            let chars = s.chars().collect::<Vec<_>>();
+            let i = list_index(&index, chars.len())?;
+            Ok(ReturnType::String(chars[i].to_string()))
+        }
+        _ => Err(RuntimeError::OperandType(container)),
+    }
+}
+
+/// nil安全参照評価
+///
+/// オブジェクトはないため、マップのキー(文字列)を参照する
+///
+/// # Arguments
+/// * `container` - 参照先（nil/Map）
+/// * `name` - キー
+///
+/// # Return
+/// * EvalResult - 評価後の値。参照先がnilの場合はnil
+fn optional_get(container: Operand, name: &str) -> EvalResult {
+    match container {
+        ReturnType::Return(o) => optional_get(*o, name),
+        ReturnType::Void => Ok(ReturnType::Void),
+        ReturnType::Map(_, _) => index(container, ReturnType::String(name.to_string())),
+        _ => Err(RuntimeError::OperandType(container)),
+    }
+}
+
+/// インデックスを要素位置へ変換
 ///
 /// # Arguments
-/// * `i` - 変数名
-/// * `right` - 初期化式
+/// * `index` - インデックス
+/// * `len` - 要素数
 ///
 /// # Return
-/// * EvalResult - 評価後の値
-fn var_decl(i: &String, operand: &AstType, env: &mut Environment) -> EvalResult {
-    let right = eval(operand, env)?;
-    let value = to_env_value(right);
-    env.define(i.to_string(), value);
+/// * Result<usize, RuntimeError> - 要素位置。整数以外、範囲外の場合はエラー
+fn list_index(index: &Operand, len: usize) -> Result<usize, RuntimeError> {
+    let i = to_integer(index)?;
+    if i < 0 || i as usize >= len {
+        return Err(RuntimeError::IndexOutOfRange(i, len));
+    }
 
-    Ok(ReturnType::Void)
+    Ok(i as usize)
 }
 
-/// 変数参照評価
+/// インデックス代入評価
+///
+/// `grid[i][j] = v;`の場合、変数gridの値を取得し、[i][j]の要素を更新してから変数へ代入し直す
 ///
 /// # Arguments
-/// * `i` - 変数名
+/// * `target` - 代入先（変数、もしくはインデックス参照）
+/// * `index` - インデックス（Mapの場合はキー）
+/// * `value` - 代入式
 ///
 /// # Return
-/// * EvalResult - 評価後の値（bool/f64/String）
-fn identifier(i: &String, env: &mut Environment) -> EvalResult {
-    let val = env.get(i);
-    if let Some(val) = val {
-        match val {
-            Value::F64(f) => Ok(ReturnType::F64(*f)),
-            Value::String(s) => Ok(ReturnType::String(s.to_string())),
-            Value::Bool(b) => Ok(ReturnType::Bool(*b)),
-            _ => Err(RuntimeError::NotFoundVar(i.to_string())),
-        }
-    } else {
-        Err(RuntimeError::NotFoundVar(i.to_string()))
+/// * EvalResult - 評価結果
+fn index_assign(
+    target: &AstType,
+    index: &AstType,
+    value: &AstType,
+    env: &mut Environment,
+) -> EvalResult {
+    // 変数名と、変数から辿るインデックスの並びを求める
+    let mut path = vec![index];
+    let mut root = target;
+    while let AstType::Index(o, i) = root {
+        path.push(i);
+        root = o;
     }
+    let name = match root {
+        AstType::Identifier(name) => name,
+        _ => return Err(RuntimeError::OperandType(eval(root, env)?)),
+    };
+
+    let indices = path
+        .iter()
+        .rev()
+        .map(|i| eval(i, env))
+        .collect::<Result<Vec<_>, _>>()?;
+    let value = eval(value, env)?;
+    let container = identifier(name, env)?;
+    let updated = set_index(container, &indices, value)?;
+
+    assign(name, updated, env)
 }
 
-/// 代入式評価
+/// インデックスの並びが指す要素を更新
 ///
 /// # Arguments
-/// * `i` - 変数名
-/// * `right` - 初期化式
+/// * `container` - 更新対象（List/Map）
+/// * `indices` - インデックスの並び
+/// * `value` - 代入する値
 ///
 /// # Return
-/// * EvalResult - 評価後の値
-fn assign(i: &String, right: Operand, env: &mut Environment) -> EvalResult {
-    let val = env.get(i);
-    if val.is_some() {
-        // 変数に対する値を更新
-        let value = to_env_value(right);
-        env.push(i.to_string(), value);
-
-        Ok(ReturnType::Void)
-    } else {
-        Err(RuntimeError::NotFoundVar(i.to_string()))
+/// * EvalResult - 更新後の値
+fn set_index(container: Operand, indices: &[Operand], value: Operand) -> EvalResult {
+    let (index, rest) = match indices.split_first() {
+        Some(first) => first,
+        None => return Ok(value),
+    };
+    match container {
+        ReturnType::Return(o) => set_index(*o, indices, value),
+        ReturnType::List(_, Frozen(true)) | ReturnType::Map(_, Frozen(true)) => {
+            Err(RuntimeError::ModifyFrozen)
+        }
+        ReturnType::List(mut l, frozen) => {
+            let i = list_index(index, l.len())?;
+            let element = std::mem::replace(&mut l[i], ReturnType::Void);
+            l[i] = set_index(element, rest, value)?;
+            Ok(ReturnType::List(l, frozen))
+        }
+        ReturnType::Map(mut m, frozen) => {
+            match m.iter_mut().find(|(k, _)| k == index) {
+                Some(entry) => {
+                    let element = std::mem::replace(&mut entry.1, ReturnType::Void);
+                    entry.1 = set_index(element, rest, value)?;
+                }
+                // 存在しないキーへの代入は追加
+                None if rest.is_empty() => m.push((index.clone(), value)),
+                None => return Err(RuntimeError::NotFoundKey(index.clone())),
+            }
+            Ok(ReturnType::Map(m, frozen))
+        }
+        _ => Err(RuntimeError::OperandType(container)),
     }
 }
 
-/// 環境に格納する値を取得
+/// in演算子評価
+///
 /// # Arguments
-/// * `operand` - オペランド
+/// * `left` - 左オペランド（探す値）
+/// * `right` - 右オペランド（List/Map/String）
 ///
 /// # Return
-/// * Value - 変換後のValue
-fn to_env_value(operand: Operand) -> Value {
-    if one_type_check_string(&operand) {
-        Value::String(downcast_string(operand))
-    } else if one_type_check_f64(&operand) {
-        Value::F64(downcast_f64(operand))
-    } else {
-        Value::Bool(downcast_bool(operand))
+/// * EvalResult - 評価後の値（bool）
+pub(crate) fn in_eval(left: Operand, right: Operand) -> EvalResult {
+    let left = match left {
+        ReturnType::Return(o) => *o,
+        _ => left,
+    };
+    match right {
+        ReturnType::Return(o) => in_eval(left, *o),
+        ReturnType::List(l, _) => Ok(ReturnType::Bool(l.contains(&left))),
+        ReturnType::Map(m, _) => Ok(ReturnType::Bool(m.iter().any(|(k, _)| *k == left))),
+        ReturnType::String(s) => match left {
+            ReturnType::String(sub) => Ok(ReturnType::Bool(s.contains(&sub))),
+            _ => Err(RuntimeError::TwoOperandType(left, ReturnType::String(s))),
+        },
+        _ => Err(RuntimeError::OperandType(right)),
     }
 }
 
@@ -459,7 +1458,12 @@ fn block(ast_arr: &Vec<AstType>, env: &mut Environment) -> EvalResult {
     for ast in ast_arr {
         ret = eval(ast, &mut block_env);
         match ret {
-            Ok(ReturnType::Return(_)) => break,
+            // エラーの場合は以降の文を評価しない
+            Err(_)
+            | Ok(ReturnType::Return(_))
+            | Ok(ReturnType::TailCall(_))
+            | Ok(ReturnType::Break(_))
+            | Ok(ReturnType::Continue(_)) => break,
             _ => continue,
         }
     }
@@ -485,11 +1489,10 @@ fn if_eval(
     else_stmt: &AstType,
     env: &mut Environment,
 ) -> EvalResult {
-    let result = downcast_bool(eval(cond, env)?);
-    if result {
-        Ok(eval(if_stmt, env)?)
+    if condition(eval(cond, env)?)? {
+        eval(if_stmt, env)
     } else {
-        Ok(eval(else_stmt, env)?)
+        eval(else_stmt, env)
     }
 }
 
@@ -498,21 +1501,176 @@ fn if_eval(
 /// # Arguments
 /// * `cond` - 条件式
 /// * `stmt` - ブロック
+/// * `increment` - インクリメント(for文のみ)
+/// * `else_stmt` - 一度も繰り返さなかった場合に評価するブロック
+/// * `label` - ループに付与されたラベル
 ///
 /// # Return
 /// * EvalResult - 評価後の値
-fn while_eval(cond: &AstType, stmt: &AstType, env: &mut Environment) -> EvalResult {
+fn while_eval(
+    cond: &AstType,
+    stmt: &AstType,
+    increment: &AstType,
+    else_stmt: &AstType,
+    label: Option<&String>,
+    env: &mut Environment,
+) -> EvalResult {
+    let max_iterations = config::get().max_loop_iterations;
+    let mut iterations = 0;
     loop {
-        let cond_ret = downcast_bool(eval(cond, env)?);
+        let cond_ret = condition(eval(cond, env)?)?;
         if !cond_ret {
             break;
         }
-        eval(stmt, env)?;
+        iterations += 1;
+        check_loop_limit(iterations, max_iterations)?;
+
+        // break/continueはラベルなし、もしくは自身のラベルが指定されている場合のみ処理する
+        match eval(stmt, env)? {
+            ReturnType::Break(l) if l.is_none() || l.as_ref() == label => break,
+            ReturnType::Continue(l) if l.is_none() || l.as_ref() == label => {}
+            ret @ (ReturnType::Return(_)
+            | ReturnType::TailCall(_)
+            | ReturnType::Break(_)
+            | ReturnType::Continue(_)) => return Ok(ret),
+            _ => {}
+        }
+        eval(increment, env)?;
+    }
+
+    if iterations == 0 {
+        return eval(else_stmt, env);
+    }
+
+    Ok(ReturnType::Void)
+}
+
+/// try文評価
+///
+/// tryブロックで実行時エラーが発生した場合は、エラーを変数に格納してcatchブロックを評価する。
+/// throw文の場合は渡された値、それ以外はエラーメッセージを格納する
+///
+/// # Arguments
+/// * `try_block` - tryブロック
+/// * `name` - エラーを格納する変数名
+/// * `catch_block` - catchブロック
+///
+/// # Return
+/// * EvalResult - 評価後の値
+fn try_eval(
+    try_block: &AstType,
+    name: &str,
+    catch_block: &AstType,
+    env: &mut Environment,
+) -> EvalResult {
+    // tryブロック内のエラーを捕捉できるよう、tryブロック内では末尾呼び出しを行わない
+    let tail_call_base = TAIL_CALL_BASE.with(|b| b.replace(None));
+    let ret = eval(try_block, env);
+    TAIL_CALL_BASE.with(|b| b.set(tail_call_base));
+    match ret {
+        Err(err) => {
+            let value = match err {
+                RuntimeError::Thrown(value) => to_env_value(value)?,
+                err => Value::String(err.to_string()),
+            };
+            let mut catch_env = Environment::with_enclosing(env.clone());
+            catch_env.define(name.to_string(), value);
+            let ret = eval(catch_block, &mut catch_env);
+            *env = *catch_env.enclosing.unwrap();
+
+            ret
+        }
+        ret => ret,
+    }
+}
+
+/// repeat文評価
+///
+/// # Arguments
+/// * `count` - 繰り返し回数(最初に1度だけ評価する)
+/// * `stmt` - ブロック
+///
+/// # Return
+/// * EvalResult - 評価後の値
+fn repeat_eval(count: &AstType, stmt: &AstType, env: &mut Environment) -> EvalResult {
+    let count = match eval(count, env)? {
+        ReturnType::F64(n) if n.fract() == 0.0 && n >= 0.0 => n as usize,
+        ReturnType::F64(n) => {
+            return Err(RuntimeError::InvalidArgument(format!(
+                "repeat count must be a non-negative integer: {}",
+                format_number(n)
+            )))
+        }
+        o => return Err(RuntimeError::OperandType(o)),
+    };
+    let max_iterations = config::get().max_loop_iterations;
+
+    for iterations in 1..=count {
+        check_loop_limit(iterations, max_iterations)?;
+        match eval(stmt, env)? {
+            ReturnType::Break(None) => break,
+            ReturnType::Continue(None) => {}
+            ret @ (ReturnType::Return(_)
+            | ReturnType::TailCall(_)
+            | ReturnType::Break(_)
+            | ReturnType::Continue(_)) => return Ok(ret),
+            _ => {}
+        }
     }
 
     Ok(ReturnType::Void)
 }
 
+/// ループの繰り返し回数チェック
+///
+/// # Arguments
+/// * `iterations` - 繰り返し回数
+/// * `max_iterations` - 繰り返し回数の上限
+///
+/// # Return
+/// * Result<(), RuntimeError> - 上限を超えた場合はエラー
+pub(crate) fn check_loop_limit(
+    iterations: usize,
+    max_iterations: Option<usize>,
+) -> Result<(), RuntimeError> {
+    match max_iterations {
+        Some(max) if iterations > max => Err(RuntimeError::LoopLimitExceeded(max)),
+        _ => Ok(()),
+    }
+}
+
+/// 条件式の評価結果を取得
+///
+/// # Arguments
+/// * `operand` - 条件式の評価結果
+///
+/// # Return
+/// * Result<bool, RuntimeError> - bool以外の場合はエラー
+fn condition(operand: Operand) -> Result<bool, RuntimeError> {
+    if one_type_check_bool(&operand) {
+        Ok(downcast_bool(operand))
+    } else {
+        Err(RuntimeError::OperandType(operand))
+    }
+}
+
+/// ラベル付きループ評価
+///
+/// # Arguments
+/// * `label` - ラベル
+/// * `stmt` - ループ
+///
+/// # Return
+/// * EvalResult - 評価後の値
+fn labeled_eval(label: &String, stmt: &AstType, env: &mut Environment) -> EvalResult {
+    match stmt {
+        AstType::While(cond, stmt, increment, else_stmt) => {
+            while_eval(cond, stmt, increment, else_stmt, Some(label), env)
+        }
+        _ => eval(stmt, env),
+    }
+}
+
 /// call評価
 ///
 /// # Arguments
@@ -522,29 +1680,103 @@ fn while_eval(cond: &AstType, stmt: &AstType, env: &mut Environment) -> EvalResu
 /// # Return
 /// * EvalResult - 評価後の値
 fn call_eval(callee: &String, arguments: &[AstType], env: &mut Environment) -> EvalResult {
-    let args_val: Vec<_> = arguments
+    match config::get().max_arguments {
+        Some(max) if arguments.len() > max => return Err(RuntimeError::ArgumentLimitExceeded(max)),
+        _ => {}
+    }
+    let args_val = arguments
         .iter()
         .map(|arg| eval(arg, env))
-        .map(Result::unwrap)
-        .collect();
+        .collect::<Result<Vec<_>, _>>()?;
 
-    if let Some(func) = env.clone().get(callee) {
-        match func {
-            Value::UserFunc(args, body) => call_func(body, args, &args_val, env),
-            Value::EmbeddedFunc(f) => {
-                f();
+    call_by_name(callee, &args_val, env)
+}
 
-                Ok(ReturnType::Void)
-            }
-            _ => Err(RuntimeError::NotFoundFunc(callee.to_string())),
+/// 関数名を指定して関数呼び出し
+///
+/// # Arguments
+/// * `callee` - 関数名
+/// * `args_val` - 引数値
+/// * `env` - 環境
+///
+/// # Return
+/// * EvalResult - 関数の戻り値。関数が定義されていない場合はエラー
+pub(crate) fn call_by_name(
+    callee: &String,
+    args_val: &[Operand],
+    env: &mut Environment,
+) -> EvalResult {
+    // 環境全体は複製せず、見つかった関数の内容のみ複製する
+    match env.get(callee) {
+        Some(Value::UserFunc(args, body)) => {
+            let (args, body) = (args.clone(), body.clone());
+            call_func(&body, &args, args_val, env)
         }
-    } else {
-        Err(RuntimeError::NotFoundFunc(callee.to_string()))
+        Some(Value::EmbeddedFunc(f)) => {
+            let f = *f;
+            f(args_val, env)
+        }
+        Some(Value::MemoFunc(m)) => m.clone().call(args_val, env),
+        _ => Err(not_found(
+            RuntimeError::NotFoundFunc(callee.to_string()),
+            env,
+        )),
+    }
+}
+
+/// 変数、関数が見つからないエラーを生成
+///
+/// debug_envが有効な場合、エラー発生箇所で参照できる変数名を標準エラー出力へ表示する
+///
+/// # Arguments
+/// * `err` - エラー
+/// * `env` - エラー発生箇所の環境
+///
+/// # Return
+/// * RuntimeError - 引数のエラー
+pub(crate) fn not_found(err: RuntimeError, env: &Environment) -> RuntimeError {
+    if config::get().debug_env {
+        // 組み込み関数は常に定義されているため除く
+        let names = env
+            .names()
+            .into_iter()
+            .filter(|n| !matches!(env.get(n), Some(Value::EmbeddedFunc(_))))
+            .collect::<Vec<_>>();
+        output::eprintln(&format!("defined variables: {}", names.join(", ")));
+    }
+
+    err
+}
+
+/// 関数値の呼び出し
+///
+/// 組み込み関数から、引数で受け取った関数を呼び出す場合に使用する
+///
+/// # Arguments
+/// * `func` - 関数（ユーザー定義関数/組み込み関数）
+/// * `args_val` - 引数値
+/// * `env` - 環境
+///
+/// # Return
+/// * EvalResult - 評価後の値。関数以外の場合はエラー
+pub(crate) fn call_value(
+    func: &Operand,
+    args_val: &Vec<Operand>,
+    env: &mut Environment,
+) -> EvalResult {
+    match func {
+        ReturnType::Return(o) => call_value(o, args_val, env),
+        ReturnType::UserFunc(args, body) => call_func(body, args, args_val, env),
+        ReturnType::EmbeddedFunc(f) => f(args_val, env),
+        ReturnType::MemoFunc(m) => m.call(args_val, env),
+        _ => Err(RuntimeError::OperandType(func.clone())),
     }
 }
 
 /// call function
 ///
+/// 末尾呼び出し(`return f(n - 1);`)は、再帰せずにループで評価する
+///
 /// # Arguments
 /// * `func` - 関数内容
 /// * `args` - 引数列定義
@@ -553,51 +1785,146 @@ fn call_eval(callee: &String, arguments: &[AstType], env: &mut Environment) -> E
 ///
 /// # Return
 /// * EvalResult - 評価後の値
-fn call_func(
-    body: &AstType,
+pub(crate) fn call_func(
+    body: &FuncBody,
     args: &[AstType],
-    args_val: &Vec<Operand>,
+    args_val: &[Operand],
     env: &mut Environment,
 ) -> EvalResult {
-    if args.len() != args_val.len() {
+    let mut call = Box::new(TailCall {
+        args: args.to_vec(),
+        body: body.clone(),
+        args_val: args_val.to_vec(),
+        frame: None,
+    });
+    let base = env.depth();
+    loop {
+        let mut block_env = function_env(&mut call, env)?;
+
+        // 関数評価
+        let tail_call_base = enter_call(base)?;
+        let result = eval(&call.body, &mut block_env);
+        leave_call(tail_call_base);
+
+        // ブロック内で更新された環境で上書き
+        *env = block_env.truncate(base);
+
+        // return文の値を関数の戻り値とする。呼び出し元のブロックを抜けないようにReturnは外す
+        match result? {
+            ReturnType::TailCall(next) => call = next,
+            ReturnType::Return(o) => return Ok(*o),
+            _ => return Ok(ReturnType::Void),
+        }
+    }
+}
+
+/// 関数を評価する環境の生成
+///
+/// 呼び出し元の環境(末尾呼び出しの場合は、呼び出し元の関数内の変数を含む)に、引数の内容を設定する
+///
+/// # Arguments
+/// * `call` - 呼び出す関数と引数値
+/// * `env` - 呼び出し元の環境
+///
+/// # Return
+/// * Result<Environment, RuntimeError> - 関数を評価する環境
+fn function_env(call: &mut TailCall, env: &Environment) -> Result<Environment, RuntimeError> {
+    if call.args.len() != call.args_val.len() {
         return Err(RuntimeError::NotMatchArgsNum);
     }
 
-    // 引数の内容を環境に設定
-    let mut block_env = Environment::with_enclosing(env.clone());
-    args.iter().zip(args_val).for_each(|(var_name, value)| {
+    let mut caller_env = env.clone();
+    if let Some(mut frame) = call.frame.take() {
+        frame.enclosing = Some(Box::new(caller_env));
+        caller_env = frame;
+    }
+    let mut block_env = Environment::with_enclosing(caller_env);
+    for (var_name, value) in call.args.iter().zip(&call.args_val) {
         if let AstType::Identifier(key) = var_name {
-            let arg = if one_type_check_string(value) {
-                Value::String(downcast_string(value.clone()))
-            } else if one_type_check_f64(value) {
-                Value::F64(downcast_f64(value.clone()))
-            } else {
-                Value::Bool(downcast_bool(value.clone()))
-            };
-            block_env.define(key.to_string(), arg);
+            block_env.define(key.to_string(), to_env_value(value.clone())?);
         }
-    });
+    }
 
-    // 関数評価
-    let result = eval(body, &mut block_env)?;
+    Ok(block_env)
+}
 
-    // ブロック内で更新された環境で上書き
-    *env = *block_env.enclosing.unwrap().clone();
+/// 関数呼び出しの開始
+///
+/// # Arguments
+/// * `base` - 呼び出し元の環境の深さ
+///
+/// # Return
+/// * Result<Option<usize>, RuntimeError> - 呼び出し前の末尾呼び出しの基準。深さの上限を超える場合はエラー
+fn enter_call(base: usize) -> Result<Option<usize>, RuntimeError> {
+    let depth = CALL_DEPTH.with(|d| d.get());
+    match config::get().max_call_depth {
+        Some(max) if depth >= max => return Err(RuntimeError::CallDepthLimitExceeded(max)),
+        _ => {}
+    }
+    CALL_DEPTH.with(|d| d.set(depth + 1));
+
+    Ok(TAIL_CALL_BASE.with(|b| b.replace(Some(base))))
+}
 
-    Ok(result)
+/// 関数呼び出しの終了
+///
+/// # Arguments
+/// * `tail_call_base` - 呼び出し前の末尾呼び出しの基準
+fn leave_call(tail_call_base: Option<usize>) {
+    TAIL_CALL_BASE.with(|b| b.set(tail_call_base));
+    CALL_DEPTH.with(|d| d.set(d.get() - 1));
 }
 
 /// return評価
 ///
+/// 関数内でユーザー定義関数を呼び出す場合は、呼び出さずに関数と引数値を返し、呼び出し元(call_func)で評価する
+///
 /// # Arguments
 /// * `operand` - オペランド
 ///
 /// # Return
 /// * EvalResult - 評価後の値
 fn return_eval(operand: &AstType, env: &mut Environment) -> EvalResult {
-    let ret = eval(operand, env)?;
+    match (operand, TAIL_CALL_BASE.with(|b| b.get())) {
+        (AstType::Call(callee, arguments), Some(base))
+            if matches!(env.get(callee), Some(Value::UserFunc(_, _))) =>
+        {
+            tail_call(callee, arguments, base, env)
+        }
+        _ => Ok(ReturnType::Return(Box::new(eval(operand, env)?))),
+    }
+}
 
-    Ok(ReturnType::Return(Box::new(ret)))
+/// 末尾呼び出しの生成
+///
+/// # Arguments
+/// * `callee` - 関数名
+/// * `arguments` - 引数列
+/// * `base` - 呼び出し元の関数の環境の深さ
+///
+/// # Return
+/// * EvalResult - 呼び出す関数と引数値
+fn tail_call(
+    callee: &String,
+    arguments: &[AstType],
+    base: usize,
+    env: &mut Environment,
+) -> EvalResult {
+    let Some(Value::UserFunc(args, body)) = env.get(callee) else {
+        return Err(RuntimeError::NotFoundFunc(callee.to_string()));
+    };
+    let (args, body) = (args.clone(), body.clone());
+    let args_val = arguments
+        .iter()
+        .map(|arg| eval(arg, env))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(ReturnType::TailCall(Box::new(TailCall {
+        args,
+        body,
+        args_val,
+        frame: Some(env.flatten(base)),
+    })))
 }
 
 /// fun評価
@@ -619,7 +1946,7 @@ fn fun_eval(
     // 関数定義を環境へ追加
     env.define(
         fun_name.to_string(),
-        Value::UserFunc(arguments.to_owned(), block.clone()),
+        Value::UserFunc(arguments.to_owned(), FuncBody::new(block.clone())),
     );
 
     Ok(ReturnType::Void)
@@ -633,7 +1960,7 @@ fn fun_eval(
 ///
 /// # Return
 /// * EvalResult - 評価後の値
-fn or_eval(left: Operand, right: Operand) -> EvalResult {
+pub(crate) fn or_eval(left: Operand, right: Operand) -> EvalResult {
     if type_check_bool(&left, &right) {
         let (l, r) = (downcast_bool(left), downcast_bool(right));
         Ok(ReturnType::Bool(l || r))
@@ -650,7 +1977,7 @@ fn or_eval(left: Operand, right: Operand) -> EvalResult {
 ///
 /// # Return
 /// * EvalResult - 評価後の値
-fn and_eval(left: Operand, right: Operand) -> EvalResult {
+pub(crate) fn and_eval(left: Operand, right: Operand) -> EvalResult {
     if type_check_bool(&left, &right) {
         let (l, r) = (downcast_bool(left), downcast_bool(right));
         Ok(ReturnType::Bool(l && r))
@@ -676,6 +2003,9 @@ fn type_check_f64(left: &Operand, right: &Operand) -> bool {
 fn type_check_bool(left: &Operand, right: &Operand) -> bool {
     one_type_check_bool(left) && one_type_check_bool(right)
 }
+fn type_check_list(left: &Operand, right: &Operand) -> bool {
+    one_type_check_list(left) && one_type_check_list(right)
+}
 
 /// オペランド型チェック
 ///
@@ -702,6 +2032,27 @@ fn one_type_check_bool(operand: &Operand) -> bool {
         _ => matches!(*operand, ReturnType::Bool(_)),
     }
 }
+fn one_type_check_list(operand: &Operand) -> bool {
+    match operand {
+        ReturnType::Return(o) => one_type_check_list(o),
+        _ => matches!(*operand, ReturnType::List(_, _)),
+    }
+}
+fn one_type_check_map(operand: &Operand) -> bool {
+    match operand {
+        ReturnType::Return(o) => one_type_check_map(o),
+        _ => matches!(*operand, ReturnType::Map(_, _)),
+    }
+}
+fn one_type_check_func(operand: &Operand) -> bool {
+    match operand {
+        ReturnType::Return(o) => one_type_check_func(o),
+        _ => matches!(
+            *operand,
+            ReturnType::UserFunc(_, _) | ReturnType::EmbeddedFunc(_) | ReturnType::MemoFunc(_)
+        ),
+    }
+}
 fn one_type_check_void(operand: &Operand) -> bool {
     match operand {
         ReturnType::Return(o) => one_type_check_void(o),
@@ -746,10 +2097,34 @@ fn downcast_bool(operand: Operand) -> bool {
         _ => panic!("[downcast_bool] support only bool"),
     }
 }
+fn downcast_list(operand: Operand) -> Vec<Operand> {
+    match operand {
+        ReturnType::List(l, _) => l,
+        ReturnType::Return(l) => match *l {
+            ReturnType::List(l, _) => l,
+            _ => panic!("[downcast_list] support only List"),
+        },
+        _ => panic!("[downcast_list] support only List"),
+    }
+}
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::ast::Parser;
+    use crate::scanner::Scanner;
+
+    /// ソースコードをスキャン、パースして評価し、最後の文の評価結果を返す
+    fn eval_source(source: &str, env: &mut Environment) -> EvalResult {
+        let source = source.to_string();
+        let tokens = Scanner::new(&source).scan().unwrap();
+        let mut ret = ReturnType::Void;
+        for ast in Parser::new(&tokens).program() {
+            ret = eval(&ast, env)?;
+        }
+
+        Ok(ret)
+    }
 
     #[test]
     fn リテラル_eval() {
@@ -770,6 +2145,22 @@ mod test {
         assert!(!downcast_bool(eval(&ast, &mut env).unwrap()));
     }
 
+    #[test]
+    fn 数値表示_eval() {
+        assert_eq!("0", format_number(-0.0));
+        assert_eq!("0", format_number(0.0));
+        assert_eq!("-4", format_number(-4.0));
+        assert_eq!("-4.5", format_number(-4.5));
+        assert_eq!("12", format_number(12.0));
+        assert_eq!("NaN", format_number(f64::NAN));
+
+        let mut env = Environment::new();
+        assert_eq!(
+            "0",
+            format_value(&eval_source("0 * -1;", &mut env).unwrap())
+        );
+    }
+
     #[test]
     fn 加算_eval() {
         let ast = AstType::Plus(
@@ -790,6 +2181,35 @@ mod test {
         assert_eq!(6.0, downcast_f64(eval(&ast, &mut env).unwrap()));
     }
 
+    #[test]
+    fn リスト連結_eval() {
+        let mut env = Environment::new();
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("[1, 2] + [3] == [1, 2, 3];", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("[] + [] == [];", &mut env).unwrap()
+        );
+
+        // 元のリストは変更しない
+        eval_source("var a = [1]; var b = a + [2];", &mut env).unwrap();
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("a == [1] and b == [1, 2];", &mut env).unwrap()
+        );
+
+        assert!(matches!(
+            eval_source("[1] + 2;", &mut env),
+            Err(RuntimeError::TwoOperandType(_, _))
+        ));
+        assert!(matches!(
+            eval_source("\"a\" + [1];", &mut env),
+            Err(RuntimeError::TwoOperandType(_, _))
+        ));
+    }
+
     #[test]
     fn 減算_eval() {
         let ast = AstType::Minus(
@@ -840,6 +2260,44 @@ mod test {
         assert_eq!(60.0, downcast_f64(eval(&ast, &mut env).unwrap()));
     }
 
+    #[test]
+    fn 文字列繰り返し_eval() {
+        let mut env = Environment::new();
+        assert_eq!(
+            ReturnType::String("ababc".to_string()),
+            eval_source("\"ab\" * 2 + \"c\";", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::String("cxxx".to_string()),
+            eval_source("\"c\" + 3 * \"x\";", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::String("abab".to_string()),
+            eval_source("var n = 2; (\"a\" + \"b\") * n;", &mut env).unwrap()
+        );
+        // 左結合のため、数値同士の積を先に評価する
+        assert_eq!(
+            ReturnType::String("xxxxxx".to_string()),
+            eval_source("2 * 3 * \"x\";", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::String("".to_string()),
+            eval_source("\"x\" * 0;", &mut env).unwrap()
+        );
+        assert!(matches!(
+            eval_source("\"x\" * -1;", &mut env),
+            Err(RuntimeError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            eval_source("\"x\" * 1.5;", &mut env),
+            Err(RuntimeError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            eval_source("\"x\" * \"y\";", &mut env),
+            Err(RuntimeError::TwoOperandType(_, _))
+        ));
+    }
+
     #[test]
     fn 除算_eval() {
         let ast = AstType::Div(
@@ -858,6 +2316,41 @@ mod test {
         );
         let mut env = Environment::new();
         assert_eq!(5.0, downcast_f64(eval(&ast, &mut env).unwrap()));
+
+        // 0による除算
+        assert!(matches!(
+            eval_source("var z = 0; 1 / z;", &mut env),
+            Err(RuntimeError::DivisionByZero)
+        ));
+        assert!(matches!(
+            eval_source("1 / 0;", &mut env),
+            Err(RuntimeError::DivisionByZero)
+        ));
+    }
+
+    #[test]
+    fn べき乗_eval() {
+        let mut env = Environment::new();
+        assert_eq!(
+            ReturnType::F64(-4.0),
+            eval_source("-2 ** 2;", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::F64(4.0),
+            eval_source("(-2) ** 2;", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::F64(512.0),
+            eval_source("2 ** 3 ** 2;", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::F64(0.5),
+            eval_source("2 ** -1;", &mut env).unwrap()
+        );
+        assert!(matches!(
+            eval_source("\"a\" ** 2;", &mut env),
+            Err(RuntimeError::TwoOperandType(_, _))
+        ));
     }
 
     #[test]
@@ -939,6 +2432,49 @@ mod test {
         assert!(!downcast_bool(eval(&ast, &mut env).unwrap()));
     }
 
+    #[test]
+    fn 関数の比較_eval() {
+        let mut env = crate::embedded::func::register_func(&Environment::new());
+        let ret = eval_source(
+            "fun some() { return 1; } fun other() { return 1; } var f = some;",
+            &mut env,
+        );
+        assert!(ret.is_ok());
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("f == some;", &mut env).unwrap()
+        );
+        // 本体が同じでも、別に定義した関数は等しくない
+        assert_eq!(
+            ReturnType::Bool(false),
+            eval_source("some == other;", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("some != other;", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(false),
+            eval_source("fun () {} == fun () {};", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("[f] == [some];", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("clock == clock;", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(false),
+            eval_source("clock == some;", &mut env).unwrap()
+        );
+        assert!(matches!(
+            eval_source("some == 1;", &mut env),
+            Err(RuntimeError::TwoOperandType(_, _))
+        ));
+    }
+
     #[test]
     fn bang_equal_eval() {
         let ast = AstType::BangEqual(
@@ -1026,21 +2562,13 @@ mod test {
         let mut env = Environment::new();
         assert!(!downcast_bool(eval(&ast, &mut env).unwrap()));
 
-        let ast = AstType::Greater(Box::new(AstType::True), Box::new(AstType::True));
-        let mut env = Environment::new();
-        assert!(!downcast_bool(eval(&ast, &mut env).unwrap()));
-
-        let ast = AstType::Greater(Box::new(AstType::False), Box::new(AstType::False));
-        let mut env = Environment::new();
-        assert!(!downcast_bool(eval(&ast, &mut env).unwrap()));
-
-        let ast = AstType::Greater(Box::new(AstType::False), Box::new(AstType::True));
-        let mut env = Environment::new();
-        assert!(!downcast_bool(eval(&ast, &mut env).unwrap()));
-
+        // boolは大小比較できない
         let ast = AstType::Greater(Box::new(AstType::True), Box::new(AstType::False));
         let mut env = Environment::new();
-        assert!(downcast_bool(eval(&ast, &mut env).unwrap()));
+        assert!(matches!(
+            eval(&ast, &mut env),
+            Err(RuntimeError::ComparisonType { .. })
+        ));
     }
 
     #[test]
@@ -1087,21 +2615,13 @@ mod test {
         let mut env = Environment::new();
         assert!(downcast_bool(eval(&ast, &mut env).unwrap()));
 
-        let ast = AstType::Less(Box::new(AstType::True), Box::new(AstType::True));
-        let mut env = Environment::new();
-        assert!(!downcast_bool(eval(&ast, &mut env).unwrap()));
-
-        let ast = AstType::Less(Box::new(AstType::False), Box::new(AstType::False));
-        let mut env = Environment::new();
-        assert!(!downcast_bool(eval(&ast, &mut env).unwrap()));
-
-        let ast = AstType::Less(Box::new(AstType::False), Box::new(AstType::True));
-        let mut env = Environment::new();
-        assert!(downcast_bool(eval(&ast, &mut env).unwrap()));
-
+        // boolは大小比較できない
         let ast = AstType::Less(Box::new(AstType::True), Box::new(AstType::False));
         let mut env = Environment::new();
-        assert!(!downcast_bool(eval(&ast, &mut env).unwrap()));
+        assert!(matches!(
+            eval(&ast, &mut env),
+            Err(RuntimeError::ComparisonType { .. })
+        ));
     }
 
     #[test]
@@ -1162,21 +2682,13 @@ mod test {
         let mut env = Environment::new();
         assert!(downcast_bool(eval(&ast, &mut env).unwrap()));
 
-        let ast = AstType::GreaterEqual(Box::new(AstType::True), Box::new(AstType::True));
-        let mut env = Environment::new();
-        assert!(downcast_bool(eval(&ast, &mut env).unwrap()));
-
-        let ast = AstType::GreaterEqual(Box::new(AstType::False), Box::new(AstType::False));
-        let mut env = Environment::new();
-        assert!(downcast_bool(eval(&ast, &mut env).unwrap()));
-
-        let ast = AstType::GreaterEqual(Box::new(AstType::False), Box::new(AstType::True));
-        let mut env = Environment::new();
-        assert!(!downcast_bool(eval(&ast, &mut env).unwrap()));
-
+        // boolは大小比較できない
         let ast = AstType::GreaterEqual(Box::new(AstType::True), Box::new(AstType::False));
         let mut env = Environment::new();
-        assert!(downcast_bool(eval(&ast, &mut env).unwrap()));
+        assert!(matches!(
+            eval(&ast, &mut env),
+            Err(RuntimeError::ComparisonType { .. })
+        ));
     }
 
     #[test]
@@ -1237,21 +2749,13 @@ mod test {
         let mut env = Environment::new();
         assert!(downcast_bool(eval(&ast, &mut env).unwrap()));
 
-        let ast = AstType::LessEqual(Box::new(AstType::True), Box::new(AstType::True));
-        let mut env = Environment::new();
-        assert!(downcast_bool(eval(&ast, &mut env).unwrap()));
-
-        let ast = AstType::LessEqual(Box::new(AstType::False), Box::new(AstType::False));
-        let mut env = Environment::new();
-        assert!(downcast_bool(eval(&ast, &mut env).unwrap()));
-
-        let ast = AstType::LessEqual(Box::new(AstType::False), Box::new(AstType::True));
-        let mut env = Environment::new();
-        assert!(downcast_bool(eval(&ast, &mut env).unwrap()));
-
+        // boolは大小比較できない
         let ast = AstType::LessEqual(Box::new(AstType::True), Box::new(AstType::False));
         let mut env = Environment::new();
-        assert!(!downcast_bool(eval(&ast, &mut env).unwrap()));
+        assert!(matches!(
+            eval(&ast, &mut env),
+            Err(RuntimeError::ComparisonType { .. })
+        ));
     }
 
     #[test]
@@ -1279,6 +2783,22 @@ mod test {
         assert_eq!(4.0, downcast_f64(eval(&ast, &mut env).unwrap()));
     }
 
+    #[test]
+    fn if式_eval() {
+        let mut env = Environment::new();
+        let ret = eval_source(
+            "fun max(a, b) { return if (a > b) a else b; }
+            var m = max(3, 7) + max(5, 2);
+            m;",
+            &mut env,
+        );
+        assert_eq!(ReturnType::F64(12.0), ret.unwrap());
+        assert!(matches!(
+            eval_source("var x = if (1) 2 else 3;", &mut env),
+            Err(RuntimeError::OperandType(_))
+        ));
+    }
+
     #[test]
     fn or_eval() {
         let ast = AstType::Or(Box::new(AstType::True), Box::new(AstType::True));
@@ -1309,6 +2829,43 @@ mod test {
         assert!(!downcast_bool(eval(&ast, &mut env).unwrap()));
     }
 
+    #[test]
+    fn 短絡評価_eval() {
+        let source = "var count = 0; fun inc() { count = count + 1; return true; }";
+        let cases = [
+            ("false and inc();", false, 0.0),
+            ("true or inc();", true, 0.0),
+            ("false and inc() or true;", true, 0.0),
+            ("true or false and inc();", true, 0.0),
+            ("false and (inc() or inc());", false, 0.0),
+            ("(true or inc()) and inc();", true, 1.0),
+            ("inc() and false or inc();", true, 2.0),
+            ("inc() or inc() and inc();", true, 1.0),
+            ("(false or false) and inc();", false, 0.0),
+        ];
+        for (expr, expect, count) in cases {
+            let mut env = Environment::new();
+            eval_source(source, &mut env).unwrap();
+            assert_eq!(
+                ReturnType::Bool(expect),
+                eval_source(expr, &mut env).unwrap(),
+                "{}",
+                expr
+            );
+            assert_eq!(
+                ReturnType::F64(count),
+                eval_source("count;", &mut env).unwrap(),
+                "{}",
+                expr
+            );
+        }
+
+        // 右辺を評価する場合は型をチェックする
+        let mut env = Environment::new();
+        assert!(eval_source("false or 1;", &mut env).is_err());
+        assert!(eval_source("true and 1;", &mut env).is_err());
+    }
+
     #[test]
     fn call_eval() {
         let ast = AstType::Call("clock".to_string(), vec![]);
@@ -1317,4 +2874,854 @@ mod test {
 
         assert_eq!(ReturnType::Void, eval(&ast, &mut env).unwrap());
     }
+
+    #[test]
+    fn 関数呼び出し_eval() {
+        // 大きな値を持つ環境で関数を繰り返し呼び出しても、結果が変わらないこと
+        let mut env = Environment::new();
+        let ret = eval_source(
+            "var big = \"\"; for (var i = 0; i < 1000; i = i + 1) { big = big + \"x\"; }
+             fun fib(n) { if (n < 2) { return n; } return fib(n - 1) + fib(n - 2); }
+             fib(15);",
+            &mut env,
+        );
+        assert_eq!(ReturnType::F64(610.0), ret.unwrap());
+        assert!(matches!(
+            eval_source("big(1);", &mut env),
+            Err(RuntimeError::NotFoundFunc(_))
+        ));
+        assert!(matches!(
+            eval_source("undefined(1);", &mut env),
+            Err(RuntimeError::NotFoundFunc(_))
+        ));
+    }
+
+    #[test]
+    fn 末尾呼び出し_eval() {
+        // 自身の末尾呼び出しはスタックを消費しない
+        let mut env = Environment::new();
+        let ret = eval_source(
+            "fun countdown(n) { if (n == 0) { return \"done\"; } return countdown(n - 1); }
+             countdown(100000);",
+            &mut env,
+        );
+        assert_eq!(ReturnType::String("done".to_string()), ret.unwrap());
+        let ret = eval_source(
+            "fun sum(n, acc) { if (n == 0) return acc; return sum(n - 1, acc + n); }
+             sum(100000, 0);",
+            &mut env,
+        );
+        assert_eq!(ReturnType::F64(5000050000.0), ret.unwrap());
+
+        // 自身以外の関数の末尾呼び出し、末尾以外の再帰呼び出し
+        let ret = eval_source(
+            "var calls = 0;
+             fun twice(n) { calls = calls + 1; return n * 2; }
+             fun f(n) { if (n == 0) return 0; return f(n - 1) + 1; }
+             fun g(n) { return twice(f(n)); }
+             g(10) + calls;",
+            &mut env,
+        );
+        assert_eq!(ReturnType::F64(21.0), ret.unwrap());
+        assert!(matches!(
+            eval_source("fun h(n) { return countdown(n, 1); } h(1);", &mut env),
+            Err(RuntimeError::NotMatchArgsNum)
+        ));
+
+        // ローカル変数に格納した関数、ブロック内で定義した関数の末尾呼び出し
+        let ret = eval_source(
+            "fun seven() { return 7; }
+             fun local() { var g = seven; return g(); }
+             fun nested() { { fun inner() { return 8; } return inner(); } }
+             local() + nested();",
+            &mut env,
+        );
+        assert_eq!(ReturnType::F64(15.0), ret.unwrap());
+
+        // 呼び出し元の関数内の変数は、末尾呼び出し以外と同様に参照できる
+        let ret = eval_source(
+            "fun read_y() { return y; }
+             fun tail() { var y = 3; return read_y(); }
+             fun non_tail() { var y = 4; var r = read_y(); return r; }
+             tail() * 10 + non_tail();",
+            &mut env,
+        );
+        assert_eq!(ReturnType::F64(34.0), ret.unwrap());
+
+        // 相互の末尾呼び出しもスタックを消費しない
+        let ret = eval_source(
+            "fun is_even(n) { if (n == 0) return true; return is_odd(n - 1); }
+             fun is_odd(n) { if (n == 0) return false; return is_even(n - 1); }
+             is_even(5000);",
+            &mut env,
+        );
+        assert_eq!(ReturnType::Bool(true), ret.unwrap());
+    }
+
+    #[test]
+    fn 長い式_eval() {
+        // 項の多い式もスタックを使い切らずに評価できる
+        let mut env = Environment::new();
+        let source = format!("var x = 1; {}x;", "x + ".repeat(299));
+        assert_eq!(
+            ReturnType::F64(300.0),
+            eval_source(&source, &mut env).unwrap()
+        );
+    }
+
+    #[test]
+    fn 複数戻り値_eval() {
+        let mut env = Environment::new();
+        let ret = eval_source(
+            "fun pair(a) { return (a, a * 2); } var (x, y) = pair(3); x + y;",
+            &mut env,
+        );
+        assert_eq!(ReturnType::F64(9.0), ret.unwrap());
+        assert_eq!(Some(&Value::F64(3.0)), env.get(&"x".to_string()));
+        assert_eq!(Some(&Value::F64(6.0)), env.get(&"y".to_string()));
+
+        let mut env = Environment::new();
+        let ret = eval_source(
+            "fun pair() { return (1, 2); } var (x, y, z) = pair();",
+            &mut env,
+        );
+        assert!(matches!(
+            ret,
+            Err(RuntimeError::NotMatchDestructureNum(3, 2))
+        ));
+    }
+
+    #[test]
+    fn ビット演算_eval() {
+        let mut env = Environment::new();
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("0xFF & 0x0F == 0x0F;", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::F64(255.0),
+            eval_source("0xF0 | 0x0F;", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::F64(0xF0 as f64),
+            eval_source("0xFF ^ 0x0F;", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::F64(0xFF00 as f64),
+            eval_source("0xFF << 8;", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::F64(0x0F as f64),
+            eval_source("0xF0 >> 4;", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::F64(0x1F0 as f64),
+            eval_source("0x0F << 4 | 0x100;", &mut env).unwrap()
+        );
+        // 整数でない、i64の範囲外の数値は範囲のエラー
+        let err = eval_source("1.5 & 1;", &mut env).unwrap_err();
+        assert!(matches!(err, RuntimeError::IntegerRange(_, _)));
+        assert_eq!("Expected an integer in [-2^63, 2^63): 1.5", err.to_string());
+        assert!(matches!(
+            eval_source("9223372036854775808 | 0;", &mut env),
+            Err(RuntimeError::IntegerRange(_, _))
+        ));
+        assert_eq!(
+            ReturnType::F64(-9223372036854775808.0),
+            eval_source("-9223372036854775808 | 0;", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("~0 == -1;", &mut env).unwrap()
+        );
+        assert_eq!(ReturnType::F64(-6.0), eval_source("~5;", &mut env).unwrap());
+        assert!(matches!(
+            eval_source("~1.5;", &mut env),
+            Err(RuntimeError::IntegerRange(_, _))
+        ));
+        assert!(matches!(
+            eval_source("~\"a\";", &mut env),
+            Err(RuntimeError::OperandType(_))
+        ));
+        let err = eval_source("1 << 64;", &mut env).unwrap_err();
+        assert!(matches!(err, RuntimeError::IntegerRange(_, "[0, 63]")));
+        assert_eq!("Expected an integer in [0, 63]: 64", err.to_string());
+        assert!(matches!(
+            eval_source("1 >> -1;", &mut env),
+            Err(RuntimeError::IntegerRange(_, _))
+        ));
+        assert!(matches!(
+            eval_source("\"a\" << 1;", &mut env),
+            Err(RuntimeError::OperandType(_))
+        ));
+    }
+
+    #[test]
+    fn in演算子_eval() {
+        let mut env = Environment::new();
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("2 in [1,2,3];", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(false),
+            eval_source("4 in [1,2,3];", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("\"a\" in {\"a\":1};", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(false),
+            eval_source("1 in {\"a\":1};", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("\"ell\" in \"hello\";", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::Bool(false),
+            eval_source("1 in [1] == false;", &mut env).unwrap()
+        );
+        assert!(matches!(
+            eval_source("1 in 2;", &mut env),
+            Err(RuntimeError::OperandType(_))
+        ));
+        assert!(matches!(
+            eval_source("1 in \"1\";", &mut env),
+            Err(RuntimeError::TwoOperandType(_, _))
+        ));
+    }
+
+    #[test]
+    fn マップ_eval() {
+        let mut env = Environment::new();
+        let ret = eval_source("var m = {\"a\": 1, \"b\": [true], \"a\": 2}; m;", &mut env);
+        assert_eq!(
+            ReturnType::Map(
+                vec![
+                    (ReturnType::String("a".to_string()), ReturnType::F64(2.0)),
+                    (
+                        ReturnType::String("b".to_string()),
+                        ReturnType::List(vec![ReturnType::Bool(true)], Frozen::default())
+                    ),
+                ],
+                Frozen::default()
+            ),
+            ret.unwrap()
+        );
+        assert_eq!(
+            "{\"a\": 2, \"b\": [true]}",
+            format_value(&eval_source("m;", &mut env).unwrap())
+        );
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("({} == {});", &mut env).unwrap()
+        );
+    }
+
+    #[test]
+    fn インデックス_eval() {
+        let mut env = Environment::new();
+        eval_source("var a = [1, [2, 3]]; var m = {\"k\": 1};", &mut env).unwrap();
+        assert_eq!(
+            ReturnType::F64(1.0),
+            eval_source("a[0];", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::F64(3.0),
+            eval_source("a[1][1];", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::F64(1.0),
+            eval_source("m[\"k\"];", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::String("e".to_string()),
+            eval_source("\"hello\"[1];", &mut env).unwrap()
+        );
+
+        eval_source("a[0] = 10; a[1][0] = 20;", &mut env).unwrap();
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("a == [10, [20, 3]];", &mut env).unwrap()
+        );
+        eval_source("m[\"k\"] = 2; m[\"new\"] = 3;", &mut env).unwrap();
+        assert_eq!(
+            "{\"k\": 2, \"new\": 3}",
+            format_value(&eval_source("m;", &mut env).unwrap())
+        );
+
+        // リストは値として代入されるため、コピー元は変わらない
+        eval_source("var b = a; b[0] = 0;", &mut env).unwrap();
+        assert_eq!(
+            ReturnType::F64(10.0),
+            eval_source("a[0];", &mut env).unwrap()
+        );
+
+        assert!(matches!(
+            eval_source("a[2];", &mut env),
+            Err(RuntimeError::IndexOutOfRange(2, 2))
+        ));
+        assert!(matches!(
+            eval_source("a[-1] = 1;", &mut env),
+            Err(RuntimeError::IndexOutOfRange(-1, 2))
+        ));
+        assert!(matches!(
+            eval_source("m[\"x\"];", &mut env),
+            Err(RuntimeError::NotFoundKey(_))
+        ));
+        assert!(matches!(
+            eval_source("var s = \"abc\"; s[0] = \"x\";", &mut env),
+            Err(RuntimeError::OperandType(_))
+        ));
+        assert!(matches!(
+            eval_source("undefined[0] = 1;", &mut env),
+            Err(RuntimeError::NotFoundVar(_))
+        ));
+    }
+
+    #[test]
+    fn nil安全参照_eval() {
+        let mut env = Environment::new();
+        assert_eq!(ReturnType::Void, eval_source("nil?.x;", &mut env).unwrap());
+        assert_eq!(
+            ReturnType::Void,
+            eval_source("var n; n?.x?.y;", &mut env).unwrap()
+        );
+
+        eval_source("var m = {\"x\": {\"y\": 1}, \"n\": nil};", &mut env).unwrap();
+        assert_eq!(
+            ReturnType::Bool(true),
+            eval_source("m?.x?.y == 1;", &mut env).unwrap()
+        );
+        assert_eq!(ReturnType::Void, eval_source("m?.n?.y;", &mut env).unwrap());
+        assert!(matches!(
+            eval_source("m?.z;", &mut env),
+            Err(RuntimeError::NotFoundKey(_))
+        ));
+        assert!(matches!(
+            eval_source("1?.x;", &mut env),
+            Err(RuntimeError::OperandType(_))
+        ));
+    }
+
+    #[test]
+    fn 環境への格納_eval() {
+        let mut env = Environment::new();
+        eval_source("var a = nil; var f = fun(x) { return x; };", &mut env).unwrap();
+        assert_eq!(Some(&Value::Nil), env.get(&"a".to_string()));
+        assert!(matches!(
+            env.get(&"f".to_string()),
+            Some(Value::UserFunc(_, _))
+        ));
+        assert_eq!(
+            ReturnType::F64(2.0),
+            eval_source("f(2);", &mut env).unwrap()
+        );
+
+        eval_source("a = f; f = nil;", &mut env).unwrap();
+        assert_eq!(
+            ReturnType::F64(3.0),
+            eval_source("a(3);", &mut env).unwrap()
+        );
+        assert_eq!(Some(&Value::Nil), env.get(&"f".to_string()));
+
+        assert_eq!(
+            Value::Bool(true),
+            to_env_value(ReturnType::Return(Box::new(ReturnType::Bool(true)))).unwrap()
+        );
+        assert!(matches!(
+            to_env_value(ReturnType::Break(None)),
+            Err(RuntimeError::OperandType(_))
+        ));
+    }
+
+    #[test]
+    fn 複合代入_eval() {
+        let mut env = Environment::new();
+        eval_source("var t = 0; print (t += 3);", &mut env).unwrap();
+        assert_eq!(Some(&Value::F64(3.0)), env.get(&"t".to_string()));
+
+        // 代入式は代入した値を返す
+        assert_eq!(
+            ReturnType::F64(8.0),
+            eval_source("t = 8;", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::F64(6.0),
+            eval_source("t -= 2;", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::F64(12.0),
+            eval_source("t *= 2;", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::F64(4.0),
+            eval_source("t /= 3;", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::F64(5.0),
+            eval_source("var u = t += 1; u;", &mut env).unwrap()
+        );
+        assert_eq!(
+            ReturnType::String("ab".to_string()),
+            eval_source("var s = \"a\"; s += \"b\";", &mut env).unwrap()
+        );
+        assert!(matches!(
+            eval_source("undefined += 1;", &mut env),
+            Err(RuntimeError::NotFoundVar(_))
+        ));
+    }
+
+    #[test]
+    fn global文_eval() {
+        let mut env = Environment::new();
+        let source = "var count = 0;
+            fun inc() { global count; count = count + 1; }
+            inc(); inc();
+            { var count = 100; inc(); }";
+        eval_source(source, &mut env).unwrap();
+        // 呼び出し元のブロックの変数ではなく、グローバル変数を更新する
+        assert_eq!(Some(&Value::F64(3.0)), env.get(&"count".to_string()));
+
+        // global文のない関数は呼び出し元のブロックの変数を更新する
+        eval_source(
+            "fun add() { count = count + 1; } { var count = 100; add(); }",
+            &mut env,
+        )
+        .unwrap();
+        assert_eq!(Some(&Value::F64(3.0)), env.get(&"count".to_string()));
+
+        // 未定義のグローバル変数には代入できない
+        assert!(matches!(
+            eval_source(
+                "fun f() { global undefined; undefined = 1; } f();",
+                &mut env
+            ),
+            Err(RuntimeError::NotFoundVar(_))
+        ));
+    }
+
+    #[test]
+    fn try_catch_eval() {
+        let mut env = Environment::new();
+        let source = "var caught = nil; var after = false;
+            try { var x = 1 / 0; after = true; } catch (e) { caught = e; }";
+        eval_source(source, &mut env).unwrap();
+        assert_eq!(
+            Some(&Value::String("Division by zero".to_string())),
+            env.get(&"caught".to_string())
+        );
+        // エラー以降の文は評価しない
+        assert_eq!(Some(&Value::Bool(false)), env.get(&"after".to_string()));
+        // catchの変数はcatchブロックの外からは参照できない
+        assert!(env.get(&"e".to_string()).is_none());
+
+        // エラーがない場合はcatchブロックを評価しない
+        eval_source("try { caught = 1; } catch (e) { caught = 2; }", &mut env).unwrap();
+        assert_eq!(Some(&Value::F64(1.0)), env.get(&"caught".to_string()));
+
+        // 関数内のエラーも捕捉する
+        let source = "fun f() { return undefined; } try { f(); } catch (e) { caught = e; }";
+        eval_source(source, &mut env).unwrap();
+        assert_eq!(
+            Some(&Value::String(
+                "Could not found variable: \"undefined\"".to_string()
+            )),
+            env.get(&"caught".to_string())
+        );
+
+        // catchブロック内のエラーは伝播する
+        assert!(matches!(
+            eval_source("try { 1 / 0; } catch (e) { e + 1; }", &mut env),
+            Err(RuntimeError::TwoOperandType(_, _))
+        ));
+        // try内のreturnは関数の戻り値となる
+        assert_eq!(
+            ReturnType::F64(1.0),
+            eval_source(
+                "fun g() { try { return 1; } catch (e) {} return 2; } g();",
+                &mut env
+            )
+            .unwrap()
+        );
+        // try内のreturnでの関数呼び出しのエラーも捕捉する
+        assert_eq!(
+            ReturnType::String("Division by zero".to_string()),
+            eval_source(
+                "fun bad() { return 1 / 0; }
+                 fun h() { try { return bad(); } catch (e) { return e; } }
+                 h();",
+                &mut env
+            )
+            .unwrap()
+        );
+
+        // エラーまでに関数内で更新した変数は反映する
+        let source = "var c = 0; fun inc() { c = c + 1; return 1 / 0; }
+            try { inc(); } catch (e) {}";
+        eval_source(source, &mut env).unwrap();
+        assert_eq!(Some(&Value::F64(1.0)), env.get(&"c".to_string()));
+    }
+
+    #[test]
+    fn throw_eval() {
+        let mut env = Environment::new();
+        eval_source(
+            "var caught; try { throw \"custom\"; } catch (e) { caught = e; }",
+            &mut env,
+        )
+        .unwrap();
+        assert_eq!(
+            Some(&Value::String("custom".to_string())),
+            env.get(&"caught".to_string())
+        );
+
+        // 文字列以外の値もそのまま渡す
+        eval_source("try { throw 40 + 2; } catch (e) { caught = e; }", &mut env).unwrap();
+        assert_eq!(Some(&Value::F64(42.0)), env.get(&"caught".to_string()));
+        let source = "fun check(n) { if (n < 0) throw [\"negative\", n]; return n; }
+            try { check(-1); } catch (e) { caught = e[1]; }";
+        eval_source(source, &mut env).unwrap();
+        assert_eq!(Some(&Value::F64(-1.0)), env.get(&"caught".to_string()));
+        // try内のreturnで呼び出した関数からのthrowも捕捉する
+        let source = "fun t() { throw \"x\"; }
+            fun f() { try { return t(); } catch (e) { return e; } }
+            f();";
+        assert_eq!(
+            ReturnType::String("x".to_string()),
+            eval_source(source, &mut env).unwrap()
+        );
+
+        // 捕捉されない場合は実行時エラー
+        let err = eval_source("throw \"custom\";", &mut env).unwrap_err();
+        assert!(matches!(&err, RuntimeError::Thrown(ReturnType::String(s)) if s == "custom"));
+        assert_eq!("Uncaught exception: \"custom\"", err.to_string());
+    }
+
+    #[test]
+    fn 複数代入_eval() {
+        let mut env = Environment::new();
+        let ret = eval_source("var a = 1; var b = 2; a, b = b, a;", &mut env);
+        assert_eq!(ReturnType::Void, ret.unwrap());
+        assert_eq!(Some(&Value::F64(2.0)), env.get(&"a".to_string()));
+        assert_eq!(Some(&Value::F64(1.0)), env.get(&"b".to_string()));
+
+        let mut env = Environment::new();
+        let ret = eval_source(
+            "fun pair() { return (3, 4); } var a; var b; a, b = pair(); a * b;",
+            &mut env,
+        );
+        assert_eq!(ReturnType::F64(12.0), ret.unwrap());
+
+        // 未定義の変数には代入できない
+        let mut env = Environment::new();
+        let ret = eval_source("var a = 1; a, c = 2, 3;", &mut env);
+        assert!(matches!(ret, Err(RuntimeError::NotFoundVar(_))));
+        assert_eq!(Some(&Value::F64(1.0)), env.get(&"a".to_string()));
+
+        let mut env = Environment::new();
+        let ret = eval_source("var a; var b; a, b = 1, 2, 3;", &mut env);
+        assert!(matches!(
+            ret,
+            Err(RuntimeError::NotMatchDestructureNum(2, 3))
+        ));
+    }
+
+    #[test]
+    fn break_continue_eval() {
+        let mut env = Environment::new();
+        let ret = eval_source(
+            "var sum = 0;
+            for (var i = 0; i < 10; i = i + 1) {
+                if (i == 5) break;
+                if (i == 1) continue;
+                sum = sum + i;
+            }
+            sum;",
+            &mut env,
+        );
+        assert_eq!(ReturnType::F64(9.0), ret.unwrap());
+    }
+
+    #[test]
+    fn pass_eval() {
+        let mut env = Environment::new();
+        let ret = eval_source("fun todo() { pass } todo();", &mut env);
+        assert_eq!(ReturnType::Void, ret.unwrap());
+
+        let ret = eval_source("var n = 0; if (n > 0) pass; else n = 1; n;", &mut env);
+        assert_eq!(ReturnType::F64(1.0), ret.unwrap());
+    }
+
+    #[test]
+    fn repeat_eval() {
+        let mut env = Environment::new();
+        let ret = eval_source(
+            "var count = 0; repeat (3) { count = count + 1; } count;",
+            &mut env,
+        );
+        assert_eq!(ReturnType::F64(3.0), ret.unwrap());
+
+        // 繰り返し回数は最初に1度だけ評価する
+        let ret = eval_source(
+            "var n = 2; var count = 0; repeat (n) { n = n + 1; count = count + 1; } count;",
+            &mut env,
+        );
+        assert_eq!(ReturnType::F64(2.0), ret.unwrap());
+
+        let ret = eval_source(
+            "var count = 0; repeat (0) count = count + 1; repeat (5) { if (count == 2) break; count = count + 1; } count;",
+            &mut env,
+        );
+        assert_eq!(ReturnType::F64(2.0), ret.unwrap());
+
+        assert!(matches!(
+            eval_source("repeat (-1) {}", &mut env),
+            Err(RuntimeError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            eval_source("repeat (1.5) {}", &mut env),
+            Err(RuntimeError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            eval_source("repeat (\"3\") {}", &mut env),
+            Err(RuntimeError::OperandType(_))
+        ));
+    }
+
+    #[test]
+    fn while_else_eval() {
+        // 条件が最初から偽の場合はelseを評価する
+        let mut env = Environment::new();
+        let ret = eval_source(
+            "var ran = \"\"; while (false) { ran = \"body\"; } else { ran = \"else\"; } ran;",
+            &mut env,
+        );
+        assert_eq!(ReturnType::String("else".to_string()), ret.unwrap());
+
+        // 一度でも繰り返した場合はelseを評価しない
+        let mut env = Environment::new();
+        let ret = eval_source(
+            "var n = 0; var ran = false; while (n < 3) n = n + 1; else ran = true; ran;",
+            &mut env,
+        );
+        assert_eq!(ReturnType::Bool(false), ret.unwrap());
+        let ret = eval_source(
+            "var ran = false; while (true) break; else ran = true; ran;",
+            &mut env,
+        );
+        assert_eq!(ReturnType::Bool(false), ret.unwrap());
+
+        // elseでのreturnは関数から抜ける
+        let ret = eval_source(
+            "fun f() { while (false) {} else { return 1; } return 2; } f();",
+            &mut env,
+        );
+        assert_eq!(ReturnType::F64(1.0), ret.unwrap());
+    }
+
+    #[test]
+    fn ループ上限_eval() {
+        let _config = config::scoped(config::Config {
+            max_loop_iterations: Some(100),
+            ..Default::default()
+        });
+
+        let mut env = Environment::new();
+        assert!(matches!(
+            eval_source("while (true) {}", &mut env),
+            Err(RuntimeError::LoopLimitExceeded(100))
+        ));
+        let ret = eval_source(
+            "var n = 0; for (var i = 0; i < 100; i = i + 1) { n = n + 1; } n;",
+            &mut env,
+        );
+        assert_eq!(ReturnType::F64(100.0), ret.unwrap());
+
+        // 上限はループごとに数える
+        let ret = eval_source(
+            "var n = 0; for (var i = 0; i < 10; i = i + 1) { for (var j = 0; j < 50; j = j + 1) { n = n + 1; } } n;",
+            &mut env,
+        );
+        assert_eq!(ReturnType::F64(500.0), ret.unwrap());
+    }
+
+    #[test]
+    fn ラベル付きループ_eval() {
+        let mut env = Environment::new();
+        let ret = eval_source(
+            "var count = 0;
+            var i = 0;
+            outer: while (i < 3) {
+                i = i + 1;
+                var j = 0;
+                while (true) {
+                    j = j + 1;
+                    count = count + 1;
+                    if (i == 2) break outer;
+                    if (j == 2) continue outer;
+                }
+            }
+            (i, count);",
+            &mut env,
+        );
+        assert_eq!(
+            ReturnType::List(
+                vec![ReturnType::F64(2.0), ReturnType::F64(3.0)],
+                Frozen::default()
+            ),
+            ret.unwrap()
+        );
+
+        let mut env = Environment::new();
+        let ret = eval_source(
+            "var count = 0;
+            outer: for (var i = 0; i < 3; i = i + 1) {
+                for (var j = 0; j < 3; j = j + 1) {
+                    if (j == 1) continue outer;
+                    count = count + 1;
+                }
+            }
+            count;",
+            &mut env,
+        );
+        assert_eq!(ReturnType::F64(3.0), ret.unwrap());
+    }
+
+    #[test]
+    fn 比較の型エラー_eval() {
+        let mut env = Environment::new();
+        let err = eval_source("true < 2;", &mut env).unwrap_err();
+        assert!(matches!(err, RuntimeError::ComparisonType { op: "<", .. }));
+        assert_eq!(
+            "'<' requires two numbers or two strings: left=bool right=number",
+            err.to_string()
+        );
+        let err = eval_source("\"a\" < 3;", &mut env).unwrap_err();
+        assert!(matches!(err, RuntimeError::ComparisonType { op: "<", .. }));
+        assert!(err.to_string().starts_with("'<' requires"));
+
+        // boolは大小比較できない
+        assert_eq!(
+            "'>' requires two numbers or two strings: left=bool right=bool",
+            eval_source("true > false;", &mut env)
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "'<=' requires two numbers or two strings: left=number right=string",
+            eval_source("1 <= \"a\";", &mut env)
+                .unwrap_err()
+                .to_string()
+        );
+
+        for (source, expect) in [
+            ("1 > \"a\";", ">"),
+            ("[1] >= [1];", ">="),
+            ("nil <= 1;", "<="),
+        ] {
+            match eval_source(source, &mut env) {
+                Err(RuntimeError::ComparisonType { op, .. }) => assert_eq!(expect, op),
+                ret => panic!("unexpected result: {:?}", ret),
+            }
+        }
+    }
+
+    #[test]
+    fn 未初期化変数_比較_eval() {
+        let mut env = Environment::new();
+        let ret = eval_source("for (var i; i < 3; i = i + 1) { print i; }", &mut env);
+        match ret {
+            Err(RuntimeError::ComparisonType { op, left, right }) => {
+                assert_eq!("<", op);
+                assert_eq!(ReturnType::Void, left);
+                assert_eq!(ReturnType::F64(3.0), right);
+            }
+            _ => panic!("unexpected result: {:?}", ret),
+        }
+        assert_eq!(
+            "'<' requires two numbers or two strings: left=nil right=number",
+            eval_source("nil < 3;", &mut env).unwrap_err().to_string()
+        );
+
+        // 条件式がbool以外の場合もエラー
+        assert!(matches!(
+            eval_source("if (nil) print 1;", &mut env),
+            Err(RuntimeError::OperandType(ReturnType::Void))
+        ));
+        assert!(matches!(
+            eval_source("while (1) print 1;", &mut env),
+            Err(RuntimeError::OperandType(ReturnType::F64(_)))
+        ));
+    }
+
+    #[test]
+    fn 未定義_未初期化変数_eval() {
+        // 定義されていない変数の参照はエラー
+        let mut env = Environment::new();
+        let ret = eval_source("print x;", &mut env);
+        assert!(matches!(ret, Err(RuntimeError::NotFoundVar(v)) if v == "x"));
+
+        // 初期化式なしで定義された変数はnil
+        let mut env = Environment::new();
+        let ret = eval_source("var x; print x; x;", &mut env);
+        assert_eq!(ReturnType::Void, ret.unwrap());
+        assert_eq!("nil", format_value(&ReturnType::Void));
+        assert_eq!(Some(&Value::Uninitialized), env.get(&"x".to_string()));
+
+        // nilを代入した変数は初期化済み
+        let mut env = Environment::new();
+        let ret = eval_source("var x; x = nil; x;", &mut env);
+        assert_eq!(ReturnType::Void, ret.unwrap());
+        assert_eq!(Some(&Value::Nil), env.get(&"x".to_string()));
+    }
+
+    #[test]
+    fn strictモード_未初期化変数_eval() {
+        let config = config::scoped(config::Config {
+            strict: true,
+            ..Default::default()
+        });
+
+        let mut env = Environment::new();
+        let ret = eval_source("var x; print x;", &mut env);
+        assert!(matches!(ret, Err(RuntimeError::UninitializedRead(v)) if v == "x"));
+        // 代入後は参照できる
+        let ret = eval_source("x = nil; x;", &mut env);
+        assert_eq!(ReturnType::Void, ret.unwrap());
+
+        drop(config);
+        let mut env = Environment::new();
+        let ret = eval_source("var x; print x; x;", &mut env);
+        assert_eq!(ReturnType::Void, ret.unwrap());
+    }
+
+    #[test]
+    fn 関数の巻き上げ_eval() {
+        let source = "
+            var result = is_even(10);
+            fun is_even(n) { if (n == 0) return true; return is_odd(n - 1); }
+            fun is_odd(n) { if (n == 0) return false; return is_even(n - 1); }
+            result;
+        "
+        .to_string();
+        let tokens = Scanner::new(&source).scan().unwrap();
+        let asts = Parser::new(&tokens).program();
+        let mut env = Environment::new();
+        hoist(&asts, &mut env);
+        let mut ret = ReturnType::Void;
+        for ast in &asts {
+            ret = eval(ast, &mut env).unwrap();
+        }
+        assert_eq!(ReturnType::Bool(true), ret);
+
+        // 巻き上げない場合は定義前の関数を参照できない
+        let mut env = Environment::new();
+        assert!(matches!(
+            eval(&asts[0], &mut env),
+            Err(RuntimeError::NotFoundFunc(_))
+        ));
+    }
 }
@@ -0,0 +1,291 @@
+use crate::ast::{self, Parser};
+use crate::config::{self, Config};
+use crate::diagnostic::Diagnostic;
+use crate::embedded::func;
+use crate::environment::Environment;
+use crate::eval::{self, EvalResult, Operand, ReturnType, RuntimeError};
+use crate::scanner::{ScanError, Scanner};
+use std::error;
+use std::fmt;
+
+/// スクリプト実行時のエラー
+pub enum InterpretError {
+    Scan(ScanError),
+    Parse(Vec<Diagnostic>),
+    Runtime(RuntimeError),
+}
+impl InterpretError {
+    fn print(&self) -> String {
+        match self {
+            Self::Scan(err) => err.to_string(),
+            Self::Parse(errors) => errors
+                .iter()
+                .map(|e| format!("{}:{}: {}", e.line + 1, e.col + 1, e.message))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Self::Runtime(err) => err.to_string(),
+        }
+    }
+}
+impl fmt::Debug for InterpretError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.print())
+    }
+}
+impl fmt::Display for InterpretError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.print())
+    }
+}
+impl error::Error for InterpretError {
+    fn description(&self) -> &str {
+        "Interpret Error"
+    }
+}
+
+/// Rustのプログラムから利用するインタプリタ
+///
+/// 組み込み関数を登録した環境を保持し、スクリプトの評価や、スクリプトで定義した関数の呼び出しを行う
+pub struct Interpreter {
+    env: Environment,
+    config: Config,
+}
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Interpreter {
+    pub fn new() -> Self {
+        InterpreterBuilder::new().build()
+    }
+
+    /// スクリプト評価
+    ///
+    /// 定義した変数、関数は以降の評価、呼び出しでも参照できる
+    ///
+    /// # Arguments
+    /// * `source` - スクリプト
+    ///
+    /// # Return
+    /// * Result<Operand, InterpretError> - 最後の文の評価結果
+    pub fn run(&mut self, source: &str) -> Result<Operand, InterpretError> {
+        let _config = config::scoped(self.config.clone());
+        let tokens = Scanner::new(&source.to_string())
+            .scan()
+            .map_err(InterpretError::Scan)?;
+        let mut parser = Parser::new(&tokens);
+        let asts = parser.program();
+        if !parser.errors().is_empty() {
+            return Err(InterpretError::Parse(parser.errors().clone()));
+        }
+
+        let asts = if self.config.fold {
+            asts.into_iter().map(ast::fold).collect()
+        } else {
+            asts
+        };
+        eval::hoist(&asts, &mut self.env);
+        let mut ret = ReturnType::Void;
+        for ast in &asts {
+            ret = eval::eval(ast, &mut self.env).map_err(InterpretError::Runtime)?;
+        }
+
+        Ok(ret)
+    }
+
+    /// スクリプトで定義した関数の呼び出し
+    ///
+    /// # Arguments
+    /// * `name` - 関数名
+    /// * `args` - 引数値
+    ///
+    /// # Return
+    /// * EvalResult - 関数の戻り値。関数が定義されていない場合はエラー
+    pub fn call_function(&mut self, name: &str, args: Vec<ReturnType>) -> EvalResult {
+        let _config = config::scoped(self.config.clone());
+        eval::call_by_name(&name.to_string(), &args, &mut self.env)
+    }
+}
+
+/// インタプリタの生成
+///
+/// 信頼できないスクリプトを実行する場合に、評価時の上限を設定する。上限を超えた場合は実行時エラーとなる
+#[derive(Default)]
+pub struct InterpreterBuilder {
+    config: Config,
+}
+impl InterpreterBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// ユーザー定義関数の呼び出しの深さの上限を設定
+    ///
+    /// 末尾呼び出しは深さに含めない
+    pub fn max_call_depth(mut self, max: usize) -> Self {
+        self.config.max_call_depth = Some(max);
+        self
+    }
+
+    /// 1つのループで繰り返せる回数の上限を設定
+    pub fn max_loop_iterations(mut self, max: usize) -> Self {
+        self.config.max_loop_iterations = Some(max);
+        self
+    }
+
+    /// 関数呼び出しの引数の数の上限を設定
+    pub fn max_arguments(mut self, max: usize) -> Self {
+        self.config.max_arguments = Some(max);
+        self
+    }
+
+    /// 評価前に定数畳み込みを行う
+    pub fn fold(mut self) -> Self {
+        self.config.fold = true;
+        self
+    }
+
+    pub fn build(self) -> Interpreter {
+        Interpreter {
+            env: func::register_func(&Environment::new()),
+            config: self.config,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn 関数呼び出し_interpreter() {
+        let mut interpreter = Interpreter::new();
+        interpreter
+            .run("fun add(a, b) { return a + b; } var base = 10;")
+            .unwrap();
+
+        assert_eq!(
+            ReturnType::F64(3.0),
+            interpreter
+                .call_function("add", vec![ReturnType::F64(1.0), ReturnType::F64(2.0)])
+                .unwrap()
+        );
+        assert_eq!(
+            ReturnType::String("ab".to_string()),
+            interpreter
+                .call_function(
+                    "add",
+                    vec![
+                        ReturnType::String("a".to_string()),
+                        ReturnType::String("b".to_string())
+                    ]
+                )
+                .unwrap()
+        );
+        // 呼び出し後もスクリプトの評価を続けられる
+        assert_eq!(
+            ReturnType::F64(13.0),
+            interpreter.run("add(base, 3);").unwrap()
+        );
+
+        assert!(matches!(
+            interpreter.call_function("add", vec![ReturnType::F64(1.0)]),
+            Err(RuntimeError::NotMatchArgsNum)
+        ));
+        assert!(matches!(
+            interpreter.call_function("base", vec![]),
+            Err(RuntimeError::NotFoundFunc(_))
+        ));
+        assert!(matches!(
+            interpreter.call_function("is_number", vec![ReturnType::F64(1.0)]),
+            Ok(ReturnType::Bool(true))
+        ));
+    }
+
+    #[test]
+    fn エラー_interpreter() {
+        let mut interpreter = Interpreter::new();
+        assert!(matches!(
+            interpreter.run("print (1;"),
+            Err(InterpretError::Parse(_))
+        ));
+        assert!(matches!(
+            interpreter.run("print undefined;"),
+            Err(InterpretError::Runtime(_))
+        ));
+    }
+
+    #[test]
+    fn 上限設定_interpreter() {
+        let source = "fun depth(n) { if (n == 0) return 0; return depth(n - 1) + 1; }";
+        let mut interpreter = InterpreterBuilder::new().max_call_depth(5).build();
+        interpreter.run(source).unwrap();
+        assert_eq!(ReturnType::F64(4.0), interpreter.run("depth(4);").unwrap());
+        assert!(matches!(
+            interpreter.run("depth(10);"),
+            Err(InterpretError::Runtime(
+                RuntimeError::CallDepthLimitExceeded(5)
+            ))
+        ));
+        assert!(matches!(
+            interpreter.call_function("depth", vec![ReturnType::F64(10.0)]),
+            Err(RuntimeError::CallDepthLimitExceeded(5))
+        ));
+        // エラー後も上限内の呼び出しはできる
+        assert_eq!(ReturnType::F64(3.0), interpreter.run("depth(3);").unwrap());
+
+        let mut interpreter = InterpreterBuilder::new()
+            .max_loop_iterations(10)
+            .max_arguments(2)
+            .build();
+        assert!(matches!(
+            interpreter.run("while (true) {}"),
+            Err(InterpretError::Runtime(RuntimeError::LoopLimitExceeded(10)))
+        ));
+        assert!(matches!(
+            interpreter.run("fun f(a, b, c) {} f(1, 2, 3);"),
+            Err(InterpretError::Runtime(
+                RuntimeError::ArgumentLimitExceeded(2)
+            ))
+        ));
+        assert_eq!(
+            ReturnType::F64(3.0),
+            interpreter
+                .run("fun add(a, b) { return a + b; } add(1, 2);")
+                .unwrap()
+        );
+
+        // 上限を設定しない場合は深い再帰も評価できる
+        let mut interpreter = Interpreter::new();
+        interpreter.run(source).unwrap();
+        assert_eq!(
+            ReturnType::F64(100.0),
+            interpreter.run("depth(100);").unwrap()
+        );
+    }
+
+    #[test]
+    fn 定数畳み込み_interpreter() {
+        // 畳み込むかどうかで評価結果は変わらない
+        let source = "var a = 1 + 2 * 3; a - -1;";
+        let mut interpreter = Interpreter::new();
+        assert_eq!(ReturnType::F64(8.0), interpreter.run(source).unwrap());
+        let mut interpreter = InterpreterBuilder::new().fold().build();
+        assert_eq!(ReturnType::F64(8.0), interpreter.run(source).unwrap());
+        assert!(matches!(
+            interpreter.run("1 / 0;"),
+            Err(InterpretError::Runtime(_))
+        ));
+    }
+
+    #[test]
+    fn 設定の復元_interpreter() {
+        let mut interpreter = InterpreterBuilder::new().max_loop_iterations(10).build();
+        interpreter.run("var n = 0;").unwrap();
+        assert!(interpreter.call_function("is_number", vec![]).is_err());
+
+        // 評価後は評価前の設定に戻す
+        assert_eq!(Config::default(), config::get());
+    }
+}
@@ -0,0 +1,17 @@
+//! Loxインタプリタ
+//!
+//! Rustのプログラムへ組み込む場合は、interpreter::Interpreterを使用する
+pub mod ast;
+pub mod config;
+pub mod diagnostic;
+pub mod embedded;
+pub mod environment;
+pub mod eval;
+pub mod interpreter;
+pub mod lint;
+pub mod output;
+pub mod profile;
+pub mod scanner;
+pub mod token;
+#[cfg(feature = "vm")]
+pub mod vm;
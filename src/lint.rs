@@ -0,0 +1,206 @@
+use crate::ast::{self, AstType};
+use crate::diagnostic::Diagnostic;
+use std::collections::HashMap;
+
+/// 未使用のローカル変数の検出
+///
+/// ブロック内でvar定義したが、そのスコープ内で参照していない変数を警告とする。
+/// トップレベルの変数、関数の引数、`_`で始まる変数は対象外
+///
+/// # Arguments
+/// * `ast` - 文(定数畳み込み前)
+/// * `spans` - 文のノードの位置(帰りがけ順)
+///
+/// # Return
+/// * Vec<Diagnostic> - 未使用の変数の定義位置を指す警告
+pub fn unused_variables(ast: &AstType, spans: &[(usize, usize)]) -> Vec<Diagnostic> {
+    let mut checker = UnusedChecker {
+        nodes: ast::node_spans(ast, spans)
+            .into_iter()
+            .map(|(node, span)| (node as *const AstType, span))
+            .collect(),
+        scopes: vec![],
+        warnings: vec![],
+    };
+    checker.walk(ast);
+
+    checker.warnings
+}
+
+// スコープごとの変数(変数名、定義位置、参照したか)
+type Scope = Vec<(String, (usize, usize), bool)>;
+
+// 未使用の変数の検出
+struct UnusedChecker {
+    nodes: HashMap<*const AstType, (usize, usize)>, // ノードのアドレスと位置
+    scopes: Vec<Scope>,
+    warnings: Vec<Diagnostic>,
+}
+impl UnusedChecker {
+    /// ノードの走査
+    ///
+    /// # Arguments
+    /// * `ast` - ノード
+    fn walk(&mut self, ast: &AstType) {
+        match ast {
+            AstType::Block(stmts) => {
+                self.scopes.push(vec![]);
+                stmts.iter().for_each(|s| self.walk(s));
+                self.end_scope();
+            }
+            // 初期化式は定義前のスコープで評価する
+            AstType::Var(name, init) => {
+                if let Some(init) = init {
+                    self.walk(init);
+                }
+                self.declare(std::slice::from_ref(name), ast);
+            }
+            AstType::MultiVar(names, init) => {
+                self.walk(init);
+                self.declare(names, ast);
+            }
+            // 引数は参照済みとして登録し、外側の同名の変数を隠す
+            AstType::Fun(_, args, body) | AstType::Lambda(args, body) => {
+                let params = args
+                    .iter()
+                    .filter_map(|a| match a {
+                        AstType::Identifier(name) => Some((name.clone(), (0, 0), true)),
+                        _ => None,
+                    })
+                    .collect();
+                self.scopes.push(params);
+                self.walk(body);
+                self.scopes.pop();
+            }
+            // catchの変数は参照済みとして登録し、外側の同名の変数を隠す
+            AstType::Try(try_block, name, catch_block) => {
+                self.walk(try_block);
+                self.scopes.push(vec![(name.clone(), (0, 0), true)]);
+                self.walk(catch_block);
+                self.scopes.pop();
+            }
+            AstType::Identifier(name) => self.read(name),
+            AstType::Call(name, args) => {
+                self.read(name);
+                args.iter().for_each(|a| self.walk(a));
+            }
+            // 代入先の変数は参照としない
+            AstType::Assign(_, o) => self.walk(o),
+            AstType::MultiAssign(_, o) => o.iter().for_each(|a| self.walk(a)),
+            _ => ast::children(ast).into_iter().for_each(|c| self.walk(c)),
+        }
+    }
+
+    /// ローカル変数の定義
+    ///
+    /// トップレベル(スコープの外)の定義は対象外
+    ///
+    /// # Arguments
+    /// * `names` - 変数名列
+    /// * `ast` - 定義のノード
+    fn declare(&mut self, names: &[String], ast: &AstType) {
+        let span = self
+            .nodes
+            .get(&(ast as *const AstType))
+            .copied()
+            .unwrap_or_default();
+        if let Some(scope) = self.scopes.last_mut() {
+            names
+                .iter()
+                .for_each(|name| scope.push((name.clone(), span, name.starts_with('_'))));
+        }
+    }
+
+    /// 変数の参照
+    ///
+    /// 内側のスコープから探し、最も近い定義を参照済みとする
+    ///
+    /// # Arguments
+    /// * `name` - 変数名
+    fn read(&mut self, name: &str) {
+        if let Some(var) = self
+            .scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.iter_mut().rev().find(|(n, _, _)| n == name))
+        {
+            var.2 = true;
+        }
+    }
+
+    /// スコープを抜け、参照していない変数を警告とする
+    fn end_scope(&mut self) {
+        if let Some(scope) = self.scopes.pop() {
+            scope
+                .into_iter()
+                .filter(|(_, _, used)| !used)
+                .for_each(|(name, (line, col), _)| {
+                    self.warnings.push(Diagnostic::new(
+                        format!("unused variable '{}'", name),
+                        line,
+                        col,
+                    ))
+                });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::Parser;
+    use crate::scanner::Scanner;
+
+    fn unused(source: &str) -> Vec<Diagnostic> {
+        let tokens = Scanner::new(&source.to_string()).scan().unwrap();
+        let mut parser = Parser::new(&tokens);
+        parser
+            .program()
+            .iter()
+            .zip(parser.spans())
+            .flat_map(|(ast, spans)| unused_variables(ast, spans))
+            .collect()
+    }
+
+    #[test]
+    fn 未使用の変数_lint() {
+        let w = unused("{\n  var x = 1;\n  var y = 2;\n  print y;\n}");
+        assert_eq!(1, w.len());
+        assert_eq!("unused variable 'x'", w[0].message);
+        assert_eq!((1, 2), (w[0].line, w[0].col));
+
+        // 代入のみは参照としない
+        assert_eq!(1, unused("{ var x; x = 1; }").len());
+        assert_eq!(2, unused("fun f() { var (a, b) = (1, 2); }").len());
+        // 内側の同名の変数の参照は外側の変数の参照としない
+        assert_eq!(1, unused("{ var x = 1; { var x = 2; print x; } }").len());
+        assert_eq!(
+            1,
+            unused("{ var x = 1; fun f(x) { return x; } f(2); }").len()
+        );
+        assert_eq!(
+            1,
+            unused("{ var e = 1; try {} catch (e) { print e; } }").len()
+        );
+
+        // パースのみでは検出しない
+        let tokens = Scanner::new(&"{ var x = 1; }".to_string()).scan().unwrap();
+        let mut parser = Parser::new(&tokens);
+        parser.program();
+        assert!(parser.warnings().is_empty());
+    }
+
+    #[test]
+    fn 参照済みの変数_lint() {
+        assert!(unused("{ var x = 1; print x; }").is_empty());
+        assert!(unused("{ var x = 1; x += 1; }").is_empty());
+        assert!(unused("{ var f = fun() { return 1; }; f(); }").is_empty());
+        // 内側の関数からの参照
+        assert!(unused("fun f() { var n = 0; return fun() { return n; }; }").is_empty());
+        assert!(unused("for (var i = 0; i < 3; i = i + 1) {}").is_empty());
+        // トップレベルの変数、引数、`_`で始まる変数は対象外
+        assert!(unused("var x = 1;").is_empty());
+        assert!(unused("fun f(a) {}").is_empty());
+        assert!(unused("{ var _x = 1; }").is_empty());
+    }
+}
@@ -1,63 +1,481 @@
-mod ast;
-mod embedded;
-mod environment;
-mod eval;
-mod scanner;
-mod token;
-
-use crate::embedded::func;
-use crate::environment::Environment;
-use crate::scanner::Scanner;
+use r_lox::diagnostic::{self, Diagnostic};
+use r_lox::embedded::func;
+use r_lox::environment::{Environment, Value};
+use r_lox::scanner::Scanner;
+#[cfg(feature = "vm")]
+use r_lox::vm;
+use r_lox::{ast, config, eval, lint, profile, token};
 use std::env;
 use std::fs::File;
 use std::io;
-use std::io::Read;
+use std::io::{IsTerminal, Read};
+use std::process;
 use std::vec::Vec;
 
+// REPLのプロンプト
+const PROMPT: &str = "> ";
+// 複数行の入力途中のプロンプト
+const CONTINUATION_PROMPT: &str = "... ";
+
+const USAGE: &str = "Usage: r-lox [--encoding utf8|latin1] [--vm] [--dump-bytecode] [--ast-json] [--max-loop-iterations N] [--lint] [--debug-env] [--strict] [--warnings-as-errors] [--fold] [--profile] [-e|--eval <code> [args...]] [script filename [args...]]";
+
+// ファイルの文字コード
+#[derive(Debug, PartialEq)]
+enum Encoding {
+    Utf8,
+    Latin1,
+}
+
+// コマンドラインオプション
+#[derive(Debug)]
+struct Options {
+    file: Option<String>,
+    args: Vec<String>, // スクリプトファイル名より後ろの引数
+    eval: Option<String>,
+    encoding: Encoding,
+    vm: bool,
+    dump_bytecode: bool, // 実行せずにバイトコードを表示
+    ast_json: bool,      // 実行せずにASTをJSONで表示
+    profile: bool,       // 実行後にASTノードの評価回数を表示
+    config: config::Config,
+}
+
 fn main() {
     let args: Vec<String> = env::args().collect();
-    match args.len() {
-        0..=1 => repl(),
-        2 => run(&args[1]),
-        _ => println!("Usage: r-lox [script filename]"),
+    let options = match parse_args(&args[1..]) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}", err);
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    config::set(options.config);
+    if options.profile {
+        profile::enable();
+    }
+    let success = match (options.eval, options.file) {
+        // -e指定時はスクリプトファイルを受け付けない(位置引数はargsとなる)
+        (Some(code), _) if options.dump_bytecode => dump_bytecode(&code),
+        (Some(code), _) if options.ast_json => ast_json(&code),
+        (None, Some(file)) if options.dump_bytecode || options.ast_json => {
+            match read_file(&file, &options.encoding) {
+                Ok(content) if options.dump_bytecode => dump_bytecode(&content),
+                Ok(content) => ast_json(&content),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    false
+                }
+            }
+        }
+        (Some(code), _) => run_script(&code, &mut new_env(&options.args), options.vm).0,
+        (None, Some(file)) => run(&file, &options.args, &options.encoding, options.vm),
+        (None, None) => {
+            repl(options.vm);
+            true
+        }
+    };
+    if options.profile {
+        eprint!("{}", profile::report(&profile::take()));
+    }
+    if !success {
+        process::exit(1);
+    }
+}
+
+/// コマンドライン引数解析
+///
+/// # Arguments
+/// * `args` - コマンドライン引数（プログラム名は除く）
+///
+/// # Return
+/// * Result<Options, String> - 解析結果
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        file: None,
+        args: vec![],
+        eval: None,
+        encoding: Encoding::Utf8,
+        vm: false,
+        dump_bytecode: false,
+        ast_json: false,
+        profile: false,
+        config: config::Config::default(),
     };
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--encoding" => {
+                options.encoding = match iter.next().map(|e| e.as_str()) {
+                    Some("utf8") | Some("utf-8") => Encoding::Utf8,
+                    Some("latin1") => Encoding::Latin1,
+                    Some(e) => return Err(format!("unsupported encoding: {}", e)),
+                    None => return Err("--encoding requires a value".to_string()),
+                }
+            }
+            "--vm" if cfg!(feature = "vm") => options.vm = true,
+            "--vm" => return Err("--vm requires the vm feature".to_string()),
+            "--dump-bytecode" if cfg!(feature = "vm") => options.dump_bytecode = true,
+            "--dump-bytecode" => return Err("--dump-bytecode requires the vm feature".to_string()),
+            "--ast-json" => options.ast_json = true,
+            "--max-loop-iterations" => {
+                let max = iter
+                    .next()
+                    .and_then(|n| n.parse::<usize>().ok())
+                    .ok_or("--max-loop-iterations requires a number")?;
+                options.config.max_loop_iterations = Some(max);
+            }
+            "--lint" => options.config.lint = true,
+            "--debug-env" => options.config.debug_env = true,
+            "--strict" => options.config.strict = true,
+            "--warnings-as-errors" => options.config.warnings_as_errors = true,
+            "--fold" => options.config.fold = true,
+            "--profile" => options.profile = true,
+            "-e" | "--eval" => match iter.next() {
+                Some(code) => options.eval = Some(code.clone()),
+                None => return Err(format!("{} requires a value", arg)),
+            },
+            // -e指定時は、位置引数をすべてスクリプトへ渡す引数とする
+            // (スクリプトファイルは指定できない)
+            _ if options.eval.is_some() => {
+                options.args = std::iter::once(arg).chain(iter).cloned().collect();
+                break;
+            }
+            // スクリプトファイル名より後ろは、スクリプトへ渡す引数とする
+            _ => {
+                options.file = Some(arg.clone());
+                options.args = iter.cloned().collect();
+                break;
+            }
+        }
+    }
+
+    if options.eval.is_none() && options.file.is_none() {
+        if options.dump_bytecode {
+            return Err("--dump-bytecode requires a script".to_string());
+        }
+        if options.ast_json {
+            return Err("--ast-json requires a script".to_string());
+        }
+    }
+    // 実行しないモードでは評価回数がないため、併用できない
+    if options.profile && (options.dump_bytecode || options.ast_json) {
+        return Err("--profile cannot be used with --dump-bytecode or --ast-json".to_string());
+    }
+
+    Ok(options)
 }
 
 // スクリプトファイル実行
-fn run(file: &String) {
-    let mut f = File::open(file).expect("can not found file: {:file?}");
-    let mut content = String::new();
-    f.read_to_string(&mut content)
-        .expect("can not read file {:file?}");
-    run_script(&content);
+//
+// エラーなく実行できた場合はtrueを返す
+fn run(file: &String, args: &[String], encoding: &Encoding, use_vm: bool) -> bool {
+    match read_file(file, encoding) {
+        Ok(content) => run_script(&content, &mut new_env(args), use_vm).0,
+        Err(err) => {
+            eprintln!("{}", err);
+            false
+        }
+    }
+}
+
+/// スクリプトファイル読み込み
+///
+/// # Arguments
+/// * `file` - ファイルパス
+/// * `encoding` - ファイルの文字コード
+///
+/// # Return
+/// * Result<String, String> - ファイル内容
+fn read_file(file: &String, encoding: &Encoding) -> Result<String, String> {
+    let mut bytes = vec![];
+    File::open(file)
+        .and_then(|mut f| f.read_to_end(&mut bytes))
+        .map_err(|e| format!("can not read file {}: {}", file, e))?;
+
+    match encoding {
+        Encoding::Utf8 => {
+            String::from_utf8(bytes).map_err(|_| "file is not valid UTF-8".to_string())
+        }
+        // Latin-1は1バイトがそのままUnicodeのコードポイントに対応する
+        Encoding::Latin1 => Ok(bytes.iter().map(|b| *b as char).collect()),
+    }
+}
+
+// 組み込み関数と、スクリプトへ渡す引数(変数args)を登録した環境を生成
+fn new_env(args: &[String]) -> Environment {
+    let mut env = func::register_func(&Environment::new());
+    let args = args.iter().map(|a| eval::ReturnType::String(a.clone()));
+    env.define(
+        "args".to_string(),
+        Value::List(args.collect(), eval::Frozen::default()),
+    );
+
+    env
 }
 
 // REPL実行
 //
-// Ctrl+cで抜ける
-fn repl() {
+// 空行が入力されるまでの行をまとめて評価する(文字列リテラル内の空行は除く)。
+// 環境は入力をまたいで保持する。直前の式の評価結果は変数`_`で参照できる。Ctrl+c、またはEOFで抜ける。
+// 入力の先頭行が`:`で始まる場合はREPLコマンドとして実行する。
+// 標準入力が端末の場合のみ、標準エラー出力へプロンプトを表示する
+fn repl(use_vm: bool) {
+    let mut env = new_env(&[]);
     let mut buffer = String::new();
+    let interactive = io::stdin().is_terminal();
     loop {
-        io::stdin()
-            .read_line(&mut buffer)
+        if interactive {
+            eprint!("{}", prompt(&buffer));
+        }
+        let mut line = String::new();
+        let read = io::stdin()
+            .read_line(&mut line)
             .expect("can not read stdin");
-        run_script(&buffer);
+        let eof = read == 0;
+        let blank = line.trim().is_empty();
+        if buffer.is_empty() && line.trim_start().starts_with(':') {
+            repl_command(line.trim(), &mut env, use_vm);
+            continue;
+        }
+        buffer += &line;
+
+        if eof || (blank && !in_string(&buffer)) {
+            if !buffer.trim().is_empty() {
+                if let (_, Some(last)) = run_script(&buffer, &mut env, use_vm) {
+                    if let Ok(value) = eval::to_env_value(last) {
+                        env.define("_".to_string(), value);
+                    }
+                }
+            }
+            buffer.clear();
+        }
+        if eof {
+            break;
+        }
+    }
+}
+
+// REPLコマンド実行
+//
+// `:load <file>` ファイルを読み込み、REPLの環境で評価する
+fn repl_command(command: &str, env: &mut Environment, use_vm: bool) {
+    match command.split_once(char::is_whitespace) {
+        Some((":load", file)) => match read_file(&file.trim().to_string(), &Encoding::Utf8) {
+            Ok(content) => {
+                run_script(&content, env, use_vm);
+            }
+            Err(err) => eprintln!("{}", err),
+        },
+        _ => eprintln!("unknown command: {}", command),
+    }
+}
+
+// 入力途中の内容に応じたプロンプトを取得
+fn prompt(buffer: &str) -> &'static str {
+    if buffer.is_empty() {
+        PROMPT
+    } else {
+        CONTINUATION_PROMPT
+    }
+}
+
+// 文字列リテラルが閉じられていないか判定
+//
+// 文字列内のエスケープされたダブルクォーテーション(`\"`)は文字列の終わりとしない
+fn in_string(buffer: &str) -> bool {
+    let mut in_string = false;
+    let mut chars = buffer.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => in_string = !in_string,
+            '\\' if in_string => {
+                chars.next();
+            }
+            _ => {}
+        }
     }
+
+    in_string
 }
 
 // スクリプト実行
-fn run_script(scripts: &String) {
-    let scanner = Scanner::new(scripts);
-    let tokens = scanner.scan();
-    let ast = ast::Parser::new(&tokens).program();
-    let env = Environment::new();
-    let mut env = func::register_func(&env);
-
-    ast.into_iter().for_each(|a| {
-        let eval_ret = eval::eval(&a, &mut env);
-        match eval_ret {
-            Ok(result) => eval::print(result),
-            Err(err) => println!("{:?}", err),
-        };
-    });
+//
+// use_vmが指定された場合はバイトコードVMで実行する。
+// エラーなく実行できたか、と最後に評価した式文の値(nil以外)を返す
+fn run_script(
+    scripts: &String,
+    env: &mut Environment,
+    use_vm: bool,
+) -> (bool, Option<eval::Operand>) {
+    let Some(tokens) = scan(scripts) else {
+        return (false, None);
+    };
+    let mut parser = ast::Parser::new(&tokens);
+    let ast = parser.program();
+    parser
+        .errors()
+        .iter()
+        .for_each(|e| eprint!("{}", diagnostic::render(scripts, e)));
+    // 警告をエラーとして扱う場合は、警告があれば実行しない
+    if config::get().warnings_as_errors {
+        let warnings = lint_warnings(&parser, &ast);
+        warnings
+            .iter()
+            .for_each(|w| eprint!("{}", diagnostic::render(scripts, w)));
+        if !warnings.is_empty() {
+            return (false, None);
+        }
+    } else if config::get().lint {
+        lint_warnings(&parser, &ast)
+            .iter()
+            .for_each(|w| eprint!("{}", diagnostic::render_warning(scripts, w)));
+    }
+    let ast = ast.into_iter().map(fold).collect::<Vec<_>>();
+    let mut success = parser.errors().is_empty();
+    let mut last = None;
+
+    eval::hoist(&ast, env);
+    ast.iter()
+        .zip(parser.positions())
+        .for_each(|(a, (line, col))| {
+            let eval_ret = if use_vm {
+                run_vm(a, env)
+            } else {
+                eval::eval(a, env)
+            };
+            match eval_ret {
+                // 式文の評価結果のみ表示する(print、var、ifなどの文は表示しない)
+                Ok(result) if ast::is_expression_stmt(a) && result != eval::ReturnType::Void => {
+                    last = Some(result.clone());
+                    eval::print(result)
+                }
+                Ok(_) => {}
+                // 実行時エラーは文の開始位置を指す
+                Err(err) => {
+                    success = false;
+                    eprint!(
+                        "{}",
+                        diagnostic::render(scripts, &Diagnostic::new(err.to_string(), *line, *col))
+                    )
+                }
+            };
+        });
+
+    (success, last)
+}
+
+// パース時の警告と、未使用の変数の警告を返す
+//
+// 未使用の変数の検出はノードの位置を使うため、定数畳み込み前の文を渡す
+fn lint_warnings(parser: &ast::Parser, asts: &[ast::AstType]) -> Vec<Diagnostic> {
+    let mut warnings = parser.warnings().clone();
+    asts.iter()
+        .zip(parser.spans())
+        .for_each(|(a, spans)| warnings.extend(lint::unused_variables(a, spans)));
+
+    warnings
+}
+
+// --foldが指定された場合のみ定数畳み込みを行う
+fn fold(ast: ast::AstType) -> ast::AstType {
+    if config::get().fold {
+        ast::fold(ast)
+    } else {
+        ast
+    }
+}
+
+// スクリプトをスキャン
+//
+// スキャンエラーの場合はエラーを表示し、Noneを返す
+fn scan(scripts: &String) -> Option<Vec<token::Token>> {
+    match Scanner::new(scripts).scan() {
+        Ok(tokens) => Some(tokens),
+        Err(err) => {
+            let diagnostic = Diagnostic::new(err.to_string(), err.line(), err.col());
+            eprint!("{}", diagnostic::render(scripts, &diagnostic));
+            None
+        }
+    }
+}
+
+// スクリプトをコンパイルし、実行せずにバイトコードの逆アセンブル結果を表示
+//
+// エラーなくパースできた場合はtrueを返す
+fn dump_bytecode(scripts: &String) -> bool {
+    let Some(tokens) = scan(scripts) else {
+        return false;
+    };
+    let mut parser = ast::Parser::new(&tokens);
+    let asts = parser.program().into_iter().map(fold).collect::<Vec<_>>();
+    parser
+        .errors()
+        .iter()
+        .for_each(|e| eprint!("{}", diagnostic::render(scripts, e)));
+    print!("{}", disassemble(&asts));
+
+    parser.errors().is_empty()
+}
+
+// スクリプトをパースし、実行せずにASTをJSONで表示
+//
+// 文ごとのJSONを配列として出力する。エラーなくパースできた場合はtrueを返す
+fn ast_json(scripts: &String) -> bool {
+    let Some(tokens) = scan(scripts) else {
+        return false;
+    };
+    let mut parser = ast::Parser::new(&tokens);
+    let asts = parser.program();
+    parser
+        .errors()
+        .iter()
+        .for_each(|e| eprint!("{}", diagnostic::render(scripts, e)));
+    let json = asts
+        .iter()
+        .zip(parser.spans())
+        .map(|(a, spans)| ast::to_json(a, spans))
+        .collect::<Vec<_>>();
+    println!("[{}]", json.join(","));
+
+    parser.errors().is_empty()
+}
+
+#[cfg(feature = "vm")]
+fn disassemble(asts: &[ast::AstType]) -> String {
+    vm::disassemble(asts)
+}
+
+// vm featureが無効な場合、--dump-bytecodeはオプション解析でエラーとなる
+#[cfg(not(feature = "vm"))]
+fn disassemble(_asts: &[ast::AstType]) -> String {
+    String::new()
+}
+
+#[cfg(feature = "vm")]
+fn run_vm(ast: &ast::AstType, env: &mut Environment) -> eval::EvalResult {
+    vm::run(ast, env)
+}
+
+#[cfg(not(feature = "vm"))]
+fn run_vm(ast: &ast::AstType, env: &mut Environment) -> eval::EvalResult {
+    eval::eval(ast, env)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn プロンプト選択() {
+        assert_eq!("> ", prompt(""));
+        assert_eq!("... ", prompt("fun f() {\n"));
+        // 閉じていない文字列リテラルの途中
+        assert_eq!("... ", prompt("\"a\n"));
+        assert_eq!("... ", prompt("\"a\\\"\n"));
+        assert!(!in_string("\"a\\\"\";"));
+        assert!(!in_string("\"a\\\\\";"));
+    }
 }
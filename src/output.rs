@@ -0,0 +1,40 @@
+use std::cell::RefCell;
+use std::io::{self, Write};
+
+thread_local! {
+    static STDOUT: RefCell<Box<dyn Write>> = RefCell::new(Box::new(io::stdout()));
+    static STDERR: RefCell<Box<dyn Write>> = RefCell::new(Box::new(io::stderr()));
+}
+
+/// 出力先を変更
+///
+/// # Arguments
+/// * `out` - 標準出力の代わりに書き込む先
+/// * `err` - 標準エラー出力の代わりに書き込む先
+#[cfg(test)]
+pub fn set_writers(out: Box<dyn Write>, err: Box<dyn Write>) {
+    STDOUT.with(|w| *w.borrow_mut() = out);
+    STDERR.with(|w| *w.borrow_mut() = err);
+}
+
+/// 標準出力へ1行書き込む
+///
+/// # Arguments
+/// * `s` - 出力する文字列
+pub fn println(s: &str) {
+    STDOUT.with(|w| writeln!(w.borrow_mut(), "{}", s).expect("can not write stdout"));
+}
+
+/// 標準エラー出力へ1行書き込む
+///
+/// # Arguments
+/// * `s` - 出力する文字列
+pub fn eprintln(s: &str) {
+    STDERR.with(|w| writeln!(w.borrow_mut(), "{}", s).expect("can not write stderr"));
+}
+
+/// 標準出力、標準エラー出力へ書き込んだ内容を出力
+pub fn flush() {
+    STDOUT.with(|w| w.borrow_mut().flush().expect("can not write stdout"));
+    STDERR.with(|w| w.borrow_mut().flush().expect("can not write stderr"));
+}
@@ -0,0 +1,153 @@
+use crate::ast::{self, AstType};
+#[cfg(feature = "vm")]
+use crate::vm::chunk::OpCode;
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+
+thread_local! {
+    // プロファイルが有効か(無効な場合に評価回数の借用をしないよう、先に判定する)
+    static ENABLED: Cell<bool> = const { Cell::new(false) };
+    // ノードの種類、命令ごとの評価回数
+    static COUNTS: RefCell<BTreeMap<&'static str, usize>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+/// プロファイルを開始
+///
+/// 以降のツリーウォーカーでの評価回数をASTノードの種類ごとに、VMでの実行回数を命令ごとに数える
+pub fn enable() {
+    COUNTS.with(|c| c.borrow_mut().clear());
+    ENABLED.with(|e| e.set(true));
+}
+
+/// ノードの評価を記録
+///
+/// # Arguments
+/// * `ast` - 評価するAST
+pub(crate) fn record(ast: &AstType) {
+    if ENABLED.with(Cell::get) {
+        count(ast::node_type(ast));
+    }
+}
+
+/// 命令の実行を記録
+///
+/// # Arguments
+/// * `op` - 実行する命令
+#[cfg(feature = "vm")]
+pub(crate) fn record_op(op: &OpCode) {
+    if ENABLED.with(Cell::get) {
+        count(op.name());
+    }
+}
+
+/// 評価回数を加算
+fn count(name: &'static str) {
+    COUNTS.with(|c| *c.borrow_mut().entry(name).or_insert(0) += 1);
+}
+
+/// プロファイルを終了し、評価回数を取得
+///
+/// # Return
+/// * Vec<(&'static str, usize)> - ノードの種類と評価回数(評価回数の降順)
+pub fn take() -> Vec<(&'static str, usize)> {
+    ENABLED.with(|e| e.set(false));
+    let mut counts = COUNTS
+        .with(|c| std::mem::take(&mut *c.borrow_mut()))
+        .into_iter()
+        .collect::<Vec<_>>();
+    counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+
+    counts
+}
+
+/// 評価回数の表を作成
+///
+/// # Arguments
+/// * `counts` - ノードの種類と評価回数
+///
+/// # Return
+/// * String - 表示用の表
+pub fn report(counts: &[(&'static str, usize)]) -> String {
+    let width = counts
+        .iter()
+        .map(|(n, _)| n.len())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    let mut table = format!("{:<width$}  count\n", "node");
+    counts
+        .iter()
+        .for_each(|(name, count)| table += &format!("{:<width$}  {}\n", name, count));
+
+    table
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::Parser;
+    use crate::environment::Environment;
+    use crate::eval;
+    use crate::scanner::Scanner;
+
+    /// ソースコードを評価し、評価回数を取得
+    fn profile_source(source: &str) -> Vec<(&'static str, usize)> {
+        let tokens = Scanner::new(&source.to_string()).scan().unwrap();
+        let mut env = Environment::new();
+        enable();
+        for ast in Parser::new(&tokens).program() {
+            eval::eval(&ast, &mut env).unwrap();
+        }
+
+        take()
+    }
+
+    fn count(counts: &[(&'static str, usize)], name: &str) -> usize {
+        counts
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map_or(0, |(_, c)| *c)
+    }
+
+    #[test]
+    fn 評価回数_profile() {
+        let counts = profile_source("var s = 0; for (var i = 0; i < 5; i = i + 1) { s = s + i; }");
+
+        // ループ本体は5回、条件式は終了判定を含めて6回評価される
+        // (forはループ変数のスコープのブロックを含む)
+        assert_eq!(1, count(&counts, "While"));
+        assert_eq!(1 + 5, count(&counts, "Block"));
+        assert_eq!(6, count(&counts, "Less"));
+        assert_eq!(10, count(&counts, "Assign"));
+        assert_eq!(10, count(&counts, "Plus"));
+        // 評価回数の降順
+        assert!(counts.windows(2).all(|w| w[0].1 >= w[1].1));
+
+        // 終了後は記録しない
+        assert!(take().is_empty());
+    }
+
+    #[cfg(feature = "vm")]
+    #[test]
+    fn 命令の実行回数_profile() {
+        let source = "var s = 0; for (var i = 0; i < 5; i = i + 1) { s = s + i; }".to_string();
+        let tokens = Scanner::new(&source).scan().unwrap();
+        let mut env = Environment::new();
+        enable();
+        for ast in Parser::new(&tokens).program() {
+            crate::vm::run(&ast, &mut env).unwrap();
+        }
+        let counts = take();
+
+        // ループはVMで実行され、命令ごとに数える
+        assert_eq!(6, count(&counts, "Less"));
+        assert_eq!(10, count(&counts, "Add"));
+        assert_eq!(0, count(&counts, "While"));
+    }
+
+    #[test]
+    fn 表示_profile() {
+        let table = report(&[("Identifier", 12), ("Plus", 3)]);
+        assert_eq!("node        count\nIdentifier  12\nPlus        3\n", table);
+    }
+}
@@ -1,109 +1,280 @@
 use crate::token::{Token, TokenType};
 use std::collections::HashMap;
+use std::error;
+use std::fmt;
 use std::vec::Vec;
 
+/// タブ文字で進めるカラム数のデフォルト値
+pub const DEFAULT_TAB_WIDTH: usize = 8;
+
+/// スキャンエラー
+pub enum ScanError {
+    NumberOutOfRange(usize, usize),          // 行数、カラム
+    TrailingBackslash(usize, usize),         // 行数、カラム
+    UnterminatedComment(usize, usize),       // 行数、カラム(コメントの開始位置)
+    InvalidEscape(usize, usize),             // 行数、カラム(エスケープの開始位置)
+    UnexpectedCharacter(char, usize, usize), // 文字、行数、カラム
+    MalformedNumber(usize, usize),           // 行数、カラム
+}
+impl ScanError {
+    fn print(&self) -> String {
+        match self {
+            Self::NumberOutOfRange(_, _) => "numeric literal out of range".to_string(),
+            Self::TrailingBackslash(_, _) => "unexpected end of file after '\\'".to_string(),
+            Self::UnterminatedComment(_, _) => "unterminated block comment".to_string(),
+            Self::InvalidEscape(_, _) => "invalid escape sequence".to_string(),
+            Self::UnexpectedCharacter(c, _, _) => format!("unexpected character '{}'", c),
+            Self::MalformedNumber(_, _) => "malformed numeric literal".to_string(),
+        }
+    }
+
+    /// エラー発生行(0始まり)
+    pub fn line(&self) -> usize {
+        match self {
+            Self::NumberOutOfRange(line, _)
+            | Self::TrailingBackslash(line, _)
+            | Self::UnterminatedComment(line, _)
+            | Self::InvalidEscape(line, _)
+            | Self::UnexpectedCharacter(_, line, _)
+            | Self::MalformedNumber(line, _) => *line,
+        }
+    }
+
+    /// エラー発生カラム(0始まり)
+    pub fn col(&self) -> usize {
+        match self {
+            Self::NumberOutOfRange(_, col)
+            | Self::TrailingBackslash(_, col)
+            | Self::UnterminatedComment(_, col)
+            | Self::InvalidEscape(_, col)
+            | Self::UnexpectedCharacter(_, _, col)
+            | Self::MalformedNumber(_, col) => *col,
+        }
+    }
+}
+impl fmt::Debug for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.print())
+    }
+}
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.print())
+    }
+}
+impl error::Error for ScanError {
+    fn description(&self) -> &str {
+        "Scan Error"
+    }
+}
+
+type ScanResult = Result<(Token, usize), ScanError>;
+
 #[derive(Debug)]
 pub struct Scanner<'a> {
     contents: &'a String,
     keywords: HashMap<String, TokenType>,
+    tab_width: usize,
+    len: usize, // 文字数
 }
 
 impl<'a> Scanner<'a> {
     pub fn new(contents: &'a String) -> Self {
+        Self::with_tab_width(contents, DEFAULT_TAB_WIDTH)
+    }
+
+    /// タブ幅を指定してScanner生成
+    ///
+    /// # Arguments
+    /// * `contents` - スキャン対象文字列
+    /// * `tab_width` - タブ文字で進めるカラム数
+    pub fn with_tab_width(contents: &'a String, tab_width: usize) -> Self {
         let mut keywords = HashMap::new();
         keywords.insert(String::from("and"), TokenType::And);
+        keywords.insert(String::from("break"), TokenType::Break);
+        keywords.insert(String::from("catch"), TokenType::Catch);
         keywords.insert(String::from("class"), TokenType::Class);
+        keywords.insert(String::from("continue"), TokenType::Continue);
         keywords.insert(String::from("else"), TokenType::Else);
         keywords.insert(String::from("false"), TokenType::False);
         keywords.insert(String::from("for"), TokenType::For);
         keywords.insert(String::from("fun"), TokenType::Fun);
+        keywords.insert(String::from("global"), TokenType::Global);
         keywords.insert(String::from("if"), TokenType::If);
+        keywords.insert(String::from("in"), TokenType::In);
         keywords.insert(String::from("nil"), TokenType::Nil);
         keywords.insert(String::from("or"), TokenType::Or);
+        keywords.insert(String::from("pass"), TokenType::Pass);
         keywords.insert(String::from("print"), TokenType::Print);
+        keywords.insert(String::from("repeat"), TokenType::Repeat);
         keywords.insert(String::from("return"), TokenType::Return);
         keywords.insert(String::from("super"), TokenType::Super);
         keywords.insert(String::from("this"), TokenType::This);
+        keywords.insert(String::from("throw"), TokenType::Throw);
         keywords.insert(String::from("true"), TokenType::True);
+        keywords.insert(String::from("try"), TokenType::Try);
         keywords.insert(String::from("var"), TokenType::Var);
         keywords.insert(String::from("while"), TokenType::While);
 
-        Scanner { contents, keywords }
+        Scanner {
+            contents,
+            keywords,
+            tab_width,
+            len: contents.chars().count(),
+        }
     }
 
     /// 文字列スキャン開始
     ///
     /// # Return
-    /// * Vec<Token> - TokenのVec
-    pub fn scan(&self) -> Vec<Token> {
+    /// * Result<Vec<Token>, ScanError> - TokenのVec
+    pub fn scan(&self) -> Result<Vec<Token>, ScanError> {
         let mut cur = 0;
         let mut line = 0;
+        let mut col = 0;
         let mut tokens: Vec<Token> = vec![];
         let chars = self.contents.chars().collect::<Vec<char>>();
-        loop {
+        while !self.end(cur) {
             let cur_char = chars[cur];
             cur = match cur_char {
                 '\n' | '\r' => {
                     line += 1;
+                    col = 0;
+                    cur + 1
+                }
+                // タブは文字数としては1文字、カラムはタブ幅分進める
+                '\t' => {
+                    col += self.tab_width;
                     cur + 1
                 }
-                '\t' | ' ' => cur + 1,
+                ' ' => {
+                    col += 1;
+                    cur + 1
+                }
+                // 行末のバックスラッシュは行継続として、改行と合わせてSKIP
+                '\\' if chars.get(cur + 1) == Some(&'\n') => {
+                    line += 1;
+                    col = 0;
+                    cur + 2
+                }
+                '\\' if chars.get(cur + 1..cur + 3) == Some(&['\r', '\n'][..]) => {
+                    line += 1;
+                    col = 0;
+                    cur + 3
+                }
+                '\\' if self.end(cur + 1) => return Err(ScanError::TrailingBackslash(line, col)),
+                // ブロックコメントは改行を含むため、行数、カラムを更新しながらSKIP
+                // 文字列リテラル内の`/*`は文字列として読み取るため、ここには来ない
+                '/' if chars.get(cur + 1) == Some(&'*') => {
+                    let (read_num, end_line, end_col) =
+                        self.skip_block_comment(&chars[cur..], line, col)?;
+                    line = end_line;
+                    col = end_col;
+                    cur + read_num
+                }
                 _ => {
-                    // コメントをSKIPするので、次の文字まで取得しておく
-                    let next_char: Option<char> = if self.end(cur + 1) {
-                        None
-                    } else {
-                        Some(chars[cur + 1])
-                    };
+                    // コメントをSKIPするので、次の文字まで取得しておく(終端の場合はNone)
+                    let next_char = chars.get(cur + 1).copied();
 
-                    if let Some(next_char) = next_char {
+                    let read_num = if cur_char == '/' && next_char == Some('/') {
                         // コメントのSKIP
-                        if next_char == '/' {
-                            let read_num = self.skip_line(&chars[cur..]);
-                            cur + read_num
-                        } else {
-                            let (t, read_num) = self.scan_token(&chars, cur, line);
-                            tokens.push(t);
-                            cur + read_num
-                        }
+                        self.skip_line(&chars[cur..])
                     } else {
-                        let (t, read_num) = self.scan_token(&chars, cur, line);
+                        let (t, read_num) = self.scan_token(&chars, cur, line, col)?;
                         tokens.push(t);
-                        cur + read_num
-                    }
+                        read_num
+                    };
+                    // 文字列リテラルは改行を含むため、読み取った文字から行数、カラムを更新
+                    (line, col) = self.advance(&chars[cur..cur + read_num], line, col);
+                    cur + read_num
                 }
             };
-
-            if self.end(cur) {
-                break;
-            }
         }
 
-        tokens.push(Token::new(TokenType::Eof, None, cur, line));
+        tokens.push(Token::new(TokenType::Eof, None, cur, line, col));
 
-        tokens
+        Ok(tokens)
     }
 
     /// 1行SKIP
     ///
+    /// 行数、カラムの更新を呼び出し元で行うため、改行文字は読み取らない
+    ///
     /// # Arguments
     /// * `s` - 読み取り対象文字列
     ///
     /// # Returns
     /// * usize - 読み取った文字数
     fn skip_line(&self, s: &[char]) -> usize {
-        let mut read_num = 0;
-        for (i, val) in s.iter().enumerate() {
-            if self.end(i) {
-                break;
-            }
-            read_num += 1;
-            if *val == '\n' {
-                break;
+        s.iter().take_while(|c| **c != '\n' && **c != '\r').count()
+    }
+
+    /// ブロックコメントSKIP
+    ///
+    /// コメント内のダブルクォーテーションは文字列の開始として扱わない。
+    /// 入れ子のコメント(`/* a /* b */ c */`)は、対応する`*/`までをコメントとする
+    ///
+    /// # Arguments
+    /// * `s` - 読み取り対象文字列(`/*`の位置からの配列)
+    /// * `line` - コメント開始位置の行数
+    /// * `col` - コメント開始位置のカラム
+    ///
+    /// # Returns
+    /// * Result<(usize, usize, usize), ScanError> - 読み取った文字数と、コメント終了後の行数、カラム
+    fn skip_block_comment(
+        &self,
+        s: &[char],
+        line: usize,
+        col: usize,
+    ) -> Result<(usize, usize, usize), ScanError> {
+        let (mut cur_line, mut cur_col) = (line, col + 2);
+        let mut depth = 1;
+        let mut i = 2;
+        while i < s.len() {
+            match s[i] {
+                '/' if s.get(i + 1) == Some(&'*') => {
+                    depth += 1;
+                    cur_col += 2;
+                    i += 2;
+                    continue;
+                }
+                '*' if s.get(i + 1) == Some(&'/') => {
+                    depth -= 1;
+                    cur_col += 2;
+                    i += 2;
+                    if depth == 0 {
+                        return Ok((i, cur_line, cur_col));
+                    }
+                    continue;
+                }
+                '\n' | '\r' => {
+                    cur_line += 1;
+                    cur_col = 0;
+                }
+                '\t' => cur_col += self.tab_width,
+                _ => cur_col += 1,
             }
+            i += 1;
         }
 
-        read_num
+        Err(ScanError::UnterminatedComment(line, col))
+    }
+
+    /// 読み取った文字列分、行数とカラムを進める
+    ///
+    /// # Arguments
+    /// * `s` - 読み取った文字列
+    /// * `line` - 開始行
+    /// * `col` - 開始カラム
+    ///
+    /// # Return
+    /// * (usize, usize) - 読み取り後の行数とカラムのタプル
+    fn advance(&self, s: &[char], line: usize, col: usize) -> (usize, usize) {
+        s.iter().fold((line, col), |(l, c), v| match v {
+            '\n' | '\r' => (l + 1, 0),
+            '\t' => (l, c + self.tab_width),
+            _ => (l, c + 1),
+        })
     }
 
     /// 読み込み文字列の終了判定
@@ -114,7 +285,7 @@ impl<'a> Scanner<'a> {
     /// # Return
     /// * bool - true: 終了 false: 未終了
     fn end(&self, num: usize) -> bool {
-        num >= self.contents.chars().collect::<Vec<char>>().len()
+        num >= self.len
     }
 
     /// TokenTypeのスキャン
@@ -122,41 +293,93 @@ impl<'a> Scanner<'a> {
     /// # Arguments
     /// * `s` - スキャンする文字列
     /// * `cur` - 読み取り位置
+    /// * `line` - 行数
+    /// * `col` - カラム
     ///
     /// # Return
-    /// * [Token, usize] - Tokenと読み取り文字数のタプル
-    fn scan_token(&self, s: &Vec<char>, cur: usize, line: usize) -> (Token, usize) {
+    /// * ScanResult - Tokenと読み取り文字数のタプル。対応していない文字はエラー
+    fn scan_token(&self, s: &[char], cur: usize, line: usize, col: usize) -> ScanResult {
         let c = s[cur];
         let mut read_num = 1;
         let t = match c {
             '"' => {
                 // ダブルクォーテーションの次の文字位置からサーチ
-                let (token, num) = self.string(cur, &s[(cur + 1)..], line);
+                let (token, num) = self.string(cur, &s[(cur + 1)..], line, col)?;
                 read_num = num + 1;
                 token
             }
             '0'..='9' => {
-                let (token, num) = self.number(cur, &s[cur..], line);
+                let (token, num) = self.number(cur, &s[cur..], line, col)?;
                 read_num = num;
                 token
             }
             'a'..='z' | 'A'..='Z' | '_' => {
                 // アルファベットもしくはアンダースコアから始まる
-                let (token, num) = self.identifier(cur, &s[cur..], line);
+                let (token, num) = self.identifier(cur, &s[cur..], line, col);
                 read_num = num;
                 token
             }
-            '(' => Token::new(TokenType::LeftParen, None, cur, line),
-            ')' => Token::new(TokenType::RightParen, None, cur, line),
-            '{' => Token::new(TokenType::LeftBrace, None, cur, line),
-            '}' => Token::new(TokenType::RightBrace, None, cur, line),
-            ',' => Token::new(TokenType::Comma, None, cur, line),
-            '.' => Token::new(TokenType::Dot, None, cur, line),
-            '-' => Token::new(TokenType::Minus, None, cur, line),
-            '+' => Token::new(TokenType::Plus, None, cur, line),
-            ';' => Token::new(TokenType::SemiColon, None, cur, line),
-            '*' => Token::new(TokenType::Star, None, cur, line),
-            '/' => Token::new(TokenType::Slash, None, cur, line),
+            '(' => Token::new(TokenType::LeftParen, None, cur, line, col),
+            ')' => Token::new(TokenType::RightParen, None, cur, line, col),
+            '{' => Token::new(TokenType::LeftBrace, None, cur, line, col),
+            '}' => Token::new(TokenType::RightBrace, None, cur, line, col),
+            '[' => Token::new(TokenType::LeftBracket, None, cur, line, col),
+            ']' => Token::new(TokenType::RightBracket, None, cur, line, col),
+            ',' => Token::new(TokenType::Comma, None, cur, line, col),
+            ':' => Token::new(TokenType::Colon, None, cur, line, col),
+            '.' => Token::new(TokenType::Dot, None, cur, line, col),
+            '-' => Token::new(
+                if self.next_match(s, cur + 1, '=') {
+                    read_num += 1;
+                    TokenType::MinusEqual
+                } else {
+                    TokenType::Minus
+                },
+                None,
+                cur,
+                line,
+                col,
+            ),
+            '+' => Token::new(
+                if self.next_match(s, cur + 1, '=') {
+                    read_num += 1;
+                    TokenType::PlusEqual
+                } else {
+                    TokenType::Plus
+                },
+                None,
+                cur,
+                line,
+                col,
+            ),
+            ';' => Token::new(TokenType::SemiColon, None, cur, line, col),
+            '*' => Token::new(
+                if self.next_match(s, cur + 1, '*') {
+                    read_num += 1;
+                    TokenType::StarStar
+                } else if self.next_match(s, cur + 1, '=') {
+                    read_num += 1;
+                    TokenType::StarEqual
+                } else {
+                    TokenType::Star
+                },
+                None,
+                cur,
+                line,
+                col,
+            ),
+            '/' => Token::new(
+                if self.next_match(s, cur + 1, '=') {
+                    read_num += 1;
+                    TokenType::SlashEqual
+                } else {
+                    TokenType::Slash
+                },
+                None,
+                cur,
+                line,
+                col,
+            ),
             '!' => Token::new(
                 if self.next_match(s, cur + 1, '=') {
                     read_num += 1;
@@ -167,6 +390,7 @@ impl<'a> Scanner<'a> {
                 None,
                 cur,
                 line,
+                col,
             ),
             '=' => Token::new(
                 if self.next_match(s, cur + 1, '=') {
@@ -178,33 +402,50 @@ impl<'a> Scanner<'a> {
                 None,
                 cur,
                 line,
+                col,
             ),
+            '&' => Token::new(TokenType::Ampersand, None, cur, line, col),
+            '|' => Token::new(TokenType::Pipe, None, cur, line, col),
+            '^' => Token::new(TokenType::Caret, None, cur, line, col),
+            '~' => Token::new(TokenType::Tilde, None, cur, line, col),
+            '?' if self.next_match(s, cur + 1, '.') => {
+                read_num += 1;
+                Token::new(TokenType::QuestionDot, None, cur, line, col)
+            }
             '<' => Token::new(
                 if self.next_match(s, cur + 1, '=') {
                     read_num += 1;
                     TokenType::LessEqual
+                } else if self.next_match(s, cur + 1, '<') {
+                    read_num += 1;
+                    TokenType::LessLess
                 } else {
                     TokenType::Less
                 },
                 None,
                 cur,
                 line,
+                col,
             ),
             '>' => Token::new(
                 if self.next_match(s, cur + 1, '=') {
                     read_num += 1;
                     TokenType::GreaterEqual
+                } else if self.next_match(s, cur + 1, '>') {
+                    read_num += 1;
+                    TokenType::GreaterGreater
                 } else {
                     TokenType::Greater
                 },
                 None,
                 cur,
                 line,
+                col,
             ),
-            _ => panic!("Not Support Token: {:?}", c),
+            _ => return Err(ScanError::UnexpectedCharacter(c, line, col)),
         };
 
-        (t, read_num)
+        Ok((t, read_num))
     }
 
     /// 文字列リテラル取得
@@ -213,28 +454,53 @@ impl<'a> Scanner<'a> {
     /// * `s` - 読み取り対象文字列（ダブルクォーテーションの次の文字からの配列）
     /// * `cur` - 文字列の読み取り位置
     /// * `line` - 行数
+    /// * `col` - カラム
     ///
     /// # Return
-    /// * (Token, usize) - 文字列リテラルに対応するトークンと読み取り文字数のタプル
-    fn string(&self, cur: usize, s: &[char], line: usize) -> (Token, usize) {
+    /// * (Token, usize) - 文字列リテラルに対応するトークンと読み取り文字数のタプル。
+    ///   未対応のエスケープシーケンスはエラー
+    fn string(&self, cur: usize, s: &[char], line: usize, col: usize) -> ScanResult {
         // 次のダブルクォーテーションまで
         let mut literal = String::new();
         let mut read_num = 0;
-        for (i, val) in s.iter().enumerate() {
-            if *val != '"' && !self.end(i) {
-                literal.push_str(&val.to_string());
-                read_num += 1;
-            }
-            if *val == '"' {
-                read_num += 1;
-                break;
+        while read_num < s.len() {
+            match s[read_num] {
+                '"' => {
+                    read_num += 1;
+                    break;
+                }
+                '\\' => {
+                    let escaped = match s.get(read_num + 1) {
+                        Some('n') => Some(('\n', 2)),
+                        Some('t') => Some(('\t', 2)),
+                        Some('r') => Some(('\r', 2)),
+                        Some('\\') => Some(('\\', 2)),
+                        Some('"') => Some(('"', 2)),
+                        Some('0') => Some(('\0', 2)),
+                        Some('x') => hex_escape(&s[read_num..]),
+                        Some('u') => unicode_escape(&s[read_num..]),
+                        _ => None,
+                    };
+                    // read_numはエスケープ前の文字数で進め、以降のトークン位置を保つ
+                    let (c, num) = escaped.ok_or_else(|| {
+                        // エスケープの開始位置(ダブルクォーテーションの次の文字から数える)
+                        let (l, c) = self.advance(&s[..read_num], line, col + 1);
+                        ScanError::InvalidEscape(l, c)
+                    })?;
+                    literal.push(c);
+                    read_num += num;
+                }
+                c => {
+                    literal.push(c);
+                    read_num += 1;
+                }
             }
         }
 
-        (
-            Token::new(TokenType::String(literal.clone()), None, cur, line),
+        Ok((
+            Token::new(TokenType::String(literal), None, cur, line, col),
             read_num,
-        )
+        ))
     }
 
     /// 数値リテラル取得
@@ -243,36 +509,75 @@ impl<'a> Scanner<'a> {
     /// * `s` - 読み取り対象文字列（数値リテラルの開始時点からの配列）
     /// * `cur` - 文字列の読み取り位置
     /// * `line` - 行数
+    /// * `col` - カラム
     ///
     /// # Return
-    /// * (Token, usize) - 数値リテラルに対応するトークンと読み取り文字数のタプル
-    fn number(&self, cur: usize, s: &[char], line: usize) -> (Token, usize) {
-        let mut literal = String::new();
-        let mut read_num = 0;
-        for (i, val) in s.iter().enumerate() {
-            match *val {
-                // 小数点をカバー
-                '0'..='9' | '.' if !self.end(i) => {
-                    literal.push_str(&val.to_string());
-                    read_num += 1;
-                }
-                _ => break,
-            };
+    /// * ScanResult - 数値リテラルに対応するトークンと読み取り文字数のタプル。
+    ///   値が範囲外(f64で無限大となる)の場合、小数点が複数ある場合(`1.2.3`)、
+    ///   指数部に数字がない場合(`1e`)、数字の間以外に`_`がある場合(`1_`)はエラー
+    fn number(&self, cur: usize, s: &[char], line: usize, col: usize) -> ScanResult {
+        // 16進数リテラル(0xFF)
+        if s.len() > 2 && s[0] == '0' && matches!(s[1], 'x' | 'X') && s[2].is_ascii_hexdigit() {
+            let literal: String = s[2..]
+                .iter()
+                .take_while(|c| c.is_ascii_hexdigit())
+                .collect();
+            let num = u64::from_str_radix(&literal, 16)
+                .map_err(|_| ScanError::NumberOutOfRange(line, col))?;
+
+            return Ok((
+                Token::new(TokenType::Number(num as f64), None, cur, line, col),
+                literal.len() + 2,
+            ));
         }
 
-        (
-            Token::new(
-                TokenType::Number(
-                    literal
-                        .parse::<f64>()
-                        .expect("could not parse f64: {:literal?}"),
-                ),
-                None,
-                cur,
-                line,
-            ),
+        // 区切りの`_`を含む数字列の文字数。`_`は数字の間のみ(`1_000`)
+        let digits = |s: &[char]| {
+            let num = s
+                .iter()
+                .take_while(|c| c.is_ascii_digit() || **c == '_')
+                .count();
+            let separators_ok = s.first() != Some(&'_')
+                && s[..num].last() != Some(&'_')
+                && !s[..num].windows(2).any(|w| w == ['_', '_']);
+            separators_ok
+                .then_some(num)
+                .ok_or(ScanError::MalformedNumber(line, col))
+        };
+        let is_fraction =
+            |i: usize| s.get(i) == Some(&'.') && s.get(i + 1).is_some_and(char::is_ascii_digit);
+
+        // 小数点は後ろに数字が続く場合のみ数値リテラルに含める(`5.`は5と`.`とする)
+        let mut read_num = digits(s)?;
+        if is_fraction(read_num) {
+            read_num += 1 + digits(&s[read_num + 1..])?;
+        }
+        // 指数部(`6.02e23`、`1E-9`)
+        if matches!(s.get(read_num), Some('e' | 'E')) {
+            let start = read_num + 1 + usize::from(matches!(s.get(read_num + 1), Some('+' | '-')));
+            if !s.get(start).is_some_and(char::is_ascii_digit) {
+                return Err(ScanError::MalformedNumber(line, col));
+            }
+            read_num = start + digits(&s[start..])?;
+        }
+        if is_fraction(read_num) {
+            return Err(ScanError::MalformedNumber(line, col));
+        }
+
+        let num = s[..read_num]
+            .iter()
+            .filter(|c| **c != '_')
+            .collect::<String>()
+            .parse::<f64>()
+            .map_err(|_| ScanError::MalformedNumber(line, col))?;
+        if !num.is_finite() {
+            return Err(ScanError::NumberOutOfRange(line, col));
+        }
+
+        Ok((
+            Token::new(TokenType::Number(num), None, cur, line, col),
             read_num,
-        )
+        ))
     }
 
     /// 識別子、予約語リテラル取得
@@ -281,16 +586,17 @@ impl<'a> Scanner<'a> {
     /// * `s` - 読み取り対象文字列（識別子の開始時点からの配列）
     /// * `cur` - 文字列の読み取り位置
     /// * `line` - 行数
+    /// * `col` - カラム
     ///
     /// # Return
     /// * (Token, usize) - 識別子に対応するトークンと読み取り文字数のタプル
-    fn identifier(&self, cur: usize, s: &[char], line: usize) -> (Token, usize) {
+    fn identifier(&self, cur: usize, s: &[char], line: usize, col: usize) -> (Token, usize) {
         let mut literal = String::new();
         let mut read_num = 0;
         for (i, val) in s.iter().enumerate() {
             match *val {
                 'a'..='z' | 'A'..='Z' | '_' | '0'..='9' | '.' if !self.end(i) => {
-                    literal.push_str(&val.to_string());
+                    literal.push(*val);
                     read_num += 1;
                 }
                 _ => break,
@@ -303,11 +609,16 @@ impl<'a> Scanner<'a> {
         token_type.map_or_else(
             || {
                 (
-                    Token::new(TokenType::Identifier(literal.clone()), None, cur, line),
+                    Token::new(TokenType::Identifier(literal.clone()), None, cur, line, col),
+                    read_num,
+                )
+            },
+            |token_type| {
+                (
+                    Token::new(token_type.clone(), None, cur, line, col),
                     read_num,
                 )
             },
-            |token_type| (Token::new(token_type.clone(), None, cur, line), read_num),
         )
     }
 
@@ -320,15 +631,50 @@ impl<'a> Scanner<'a> {
     ///
     /// # Return
     /// * bool - true: 一致 false: 不一致
-    fn next_match(&self, s: &Vec<char>, cur: usize, e: char) -> bool {
-        // 文字列読み取り判定
-        if s.len() < 2 {
-            return false;
-        }
+    fn next_match(&self, s: &[char], cur: usize, e: char) -> bool {
+        // 末尾を超える場合は不一致
+        s.get(cur) == Some(&e)
+    }
+}
 
-        let c = s[cur];
-        c == e
+/// 16進数エスケープ(`\\x41`)の解釈
+///
+/// # Arguments
+/// * `s` - 読み取り対象文字列(バックスラッシュの位置からの配列)
+///
+/// # Return
+/// * Option<(char, usize)> - 文字と読み取った文字数。16進数2桁のASCII文字(0x00〜0x7F)以外はNone
+fn hex_escape(s: &[char]) -> Option<(char, usize)> {
+    let hex = s.get(2..4)?.iter().collect::<String>();
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
     }
+    let c = u8::from_str_radix(&hex, 16).ok().filter(u8::is_ascii)?;
+
+    Some((c as char, 4))
+}
+
+/// Unicodeエスケープ(`\\u{1F600}`)の解釈
+///
+/// # Arguments
+/// * `s` - 読み取り対象文字列(バックスラッシュの位置からの配列)
+///
+/// # Return
+/// * Option<(char, usize)> - 文字と読み取った文字数。16進数1〜6桁の有効なコードポイント以外はNone
+fn unicode_escape(s: &[char]) -> Option<(char, usize)> {
+    if s.get(2) != Some(&'{') {
+        return None;
+    }
+    let digits = s[3..].iter().take_while(|c| c.is_ascii_hexdigit()).count();
+    if !(1..=6).contains(&digits) || s.get(3 + digits) != Some(&'}') {
+        return None;
+    }
+    let hex = s[3..3 + digits].iter().collect::<String>();
+    let c = u32::from_str_radix(&hex, 16)
+        .ok()
+        .and_then(char::from_u32)?;
+
+    Some((c, digits + 4))
 }
 
 #[cfg(test)]
@@ -337,297 +683,728 @@ mod test {
 
     #[test]
     fn 記号_scan() {
-        let tokens = Scanner::new(&"(".to_string()).scan();
+        let tokens = Scanner::new(&"(".to_string()).scan().unwrap();
+        let expect = vec![
+            Token::new(TokenType::LeftParen, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 1, 0, 1),
+        ];
+        assert_eq!(expect, tokens);
+
+        let tokens = Scanner::new(&"()".to_string()).scan().unwrap();
+        let expect = vec![
+            Token::new(TokenType::LeftParen, None, 0, 0, 0),
+            Token::new(TokenType::RightParen, None, 1, 0, 1),
+            Token::new(TokenType::Eof, None, 2, 0, 2),
+        ];
+        assert_eq!(expect, tokens);
+
+        let tokens = Scanner::new(&">".to_string()).scan().unwrap();
+        let expect = vec![
+            Token::new(TokenType::Greater, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 1, 0, 1),
+        ];
+        assert_eq!(expect, tokens);
+
+        let tokens = Scanner::new(&"<".to_string()).scan().unwrap();
+        let expect = vec![
+            Token::new(TokenType::Less, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 1, 0, 1),
+        ];
+        assert_eq!(expect, tokens);
+
+        let tokens = Scanner::new(&"=".to_string()).scan().unwrap();
+        let expect = vec![
+            Token::new(TokenType::Equal, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 1, 0, 1),
+        ];
+        assert_eq!(expect, tokens);
+
+        let tokens = Scanner::new(&"==".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::LeftParen, None, 0, 0),
-            Token::new(TokenType::Eof, None, 1, 0),
+            Token::new(TokenType::EqualEqual, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 2, 0, 2),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"()".to_string()).scan();
+        let tokens = Scanner::new(&">=".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::LeftParen, None, 0, 0),
-            Token::new(TokenType::RightParen, None, 1, 0),
-            Token::new(TokenType::Eof, None, 2, 0),
+            Token::new(TokenType::GreaterEqual, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 2, 0, 2),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&">".to_string()).scan();
+        let tokens = Scanner::new(&"<=".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::Greater, None, 0, 0),
-            Token::new(TokenType::Eof, None, 1, 0),
+            Token::new(TokenType::LessEqual, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 2, 0, 2),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"<".to_string()).scan();
+        let tokens = Scanner::new(&"/".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::Less, None, 0, 0),
-            Token::new(TokenType::Eof, None, 1, 0),
+            Token::new(TokenType::Slash, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 1, 0, 1),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"=".to_string()).scan();
+        let tokens = Scanner::new(&"// comment\n/".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::Equal, None, 0, 0),
-            Token::new(TokenType::Eof, None, 1, 0),
+            Token::new(TokenType::Slash, None, 11, 1, 0),
+            Token::new(TokenType::Eof, None, 12, 1, 1),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"==".to_string()).scan();
+        let tokens = Scanner::new(&"!".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::EqualEqual, None, 0, 0),
-            Token::new(TokenType::Eof, None, 2, 0),
+            Token::new(TokenType::Bang, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 1, 0, 1),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&">=".to_string()).scan();
+        let tokens = Scanner::new(&"!=".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::GreaterEqual, None, 0, 0),
-            Token::new(TokenType::Eof, None, 2, 0),
+            Token::new(TokenType::BangEqual, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 2, 0, 2),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"<=".to_string()).scan();
+        let tokens = Scanner::new(&"?.".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::LessEqual, None, 0, 0),
-            Token::new(TokenType::Eof, None, 2, 0),
+            Token::new(TokenType::QuestionDot, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 2, 0, 2),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"/".to_string()).scan();
+        let tokens = Scanner::new(&"~".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::Slash, None, 0, 0),
-            Token::new(TokenType::Eof, None, 1, 0),
+            Token::new(TokenType::Tilde, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 1, 0, 1),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"// comment\n/".to_string()).scan();
+        let tokens = Scanner::new(&"**".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::Slash, None, 11, 0),
-            Token::new(TokenType::Eof, None, 12, 0),
+            Token::new(TokenType::StarStar, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 2, 0, 2),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"!".to_string()).scan();
+        let tokens = Scanner::new(&"<<".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::Bang, None, 0, 0),
-            Token::new(TokenType::Eof, None, 1, 0),
+            Token::new(TokenType::LessLess, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 2, 0, 2),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"!=".to_string()).scan();
+        let tokens = Scanner::new(&">>".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::BangEqual, None, 0, 0),
-            Token::new(TokenType::Eof, None, 2, 0),
+            Token::new(TokenType::GreaterGreater, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 2, 0, 2),
         ];
         assert_eq!(expect, tokens);
     }
 
+    #[test]
+    fn シフト演算子と比較演算子_scan() {
+        let tokens = Scanner::new(&"<< <= < >> >= >".to_string()).scan().unwrap();
+        let expect = vec![
+            TokenType::LessLess,
+            TokenType::LessEqual,
+            TokenType::Less,
+            TokenType::GreaterGreater,
+            TokenType::GreaterEqual,
+            TokenType::Greater,
+            TokenType::Eof,
+        ];
+        assert_eq!(
+            expect,
+            tokens
+                .iter()
+                .map(|t| t.token_type().clone())
+                .collect::<Vec<_>>()
+        );
+
+        // シフト演算子の後ろの=は別のトークンとする
+        let tokens = Scanner::new(&">>=<<=".to_string()).scan().unwrap();
+        let expect = vec![
+            TokenType::GreaterGreater,
+            TokenType::Equal,
+            TokenType::LessLess,
+            TokenType::Equal,
+            TokenType::Eof,
+        ];
+        assert_eq!(
+            expect,
+            tokens
+                .iter()
+                .map(|t| t.token_type().clone())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn 複合代入演算子_scan() {
+        let tokens = Scanner::new(&"+= -= *= /= **= + /".to_string())
+            .scan()
+            .unwrap();
+        let expect = vec![
+            TokenType::PlusEqual,
+            TokenType::MinusEqual,
+            TokenType::StarEqual,
+            TokenType::SlashEqual,
+            TokenType::StarStar,
+            TokenType::Equal,
+            TokenType::Plus,
+            TokenType::Slash,
+            TokenType::Eof,
+        ];
+        assert_eq!(
+            expect,
+            tokens
+                .iter()
+                .map(|t| t.token_type().clone())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn 空_コメントのみ_scan() {
+        let tokens = Scanner::new(&"".to_string()).scan().unwrap();
+        assert_eq!(vec![Token::new(TokenType::Eof, None, 0, 0, 0)], tokens);
+
+        let tokens = Scanner::new(&"// a comment\n".to_string()).scan().unwrap();
+        assert_eq!(vec![Token::new(TokenType::Eof, None, 13, 1, 0)], tokens);
+    }
+
     #[test]
     fn 文字列リテラル_scan() {
-        let tokens = Scanner::new(&"\"test\"".to_string()).scan();
+        let tokens = Scanner::new(&"\"test\"".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::String("test".to_string()), None, 0, 0),
-            Token::new(TokenType::Eof, None, 6, 0),
+            Token::new(TokenType::String("test".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 6, 0, 6),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"<=\"test\"".to_string()).scan();
+        let tokens = Scanner::new(&"<=\"test\"".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::LessEqual, None, 0, 0),
-            Token::new(TokenType::String("test".to_string()), None, 2, 0),
-            Token::new(TokenType::Eof, None, 8, 0),
+            Token::new(TokenType::LessEqual, None, 0, 0, 0),
+            Token::new(TokenType::String("test".to_string()), None, 2, 0, 2),
+            Token::new(TokenType::Eof, None, 8, 0, 8),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"=\"test\"".to_string()).scan();
+        let tokens = Scanner::new(&"=\"test\"".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::Equal, None, 0, 0),
-            Token::new(TokenType::String("test".to_string()), None, 1, 0),
-            Token::new(TokenType::Eof, None, 7, 0),
+            Token::new(TokenType::Equal, None, 0, 0, 0),
+            Token::new(TokenType::String("test".to_string()), None, 1, 0, 1),
+            Token::new(TokenType::Eof, None, 7, 0, 7),
         ];
         assert_eq!(expect, tokens);
     }
 
+    #[test]
+    fn 改行を含む文字列リテラル_scan() {
+        let tokens = Scanner::new(&"\"a\nb\" 1\n+".to_string()).scan().unwrap();
+        let expect = vec![
+            Token::new(TokenType::String("a\nb".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::Number(1.0), None, 6, 1, 3),
+            Token::new(TokenType::Plus, None, 8, 2, 0),
+            Token::new(TokenType::Eof, None, 9, 2, 1),
+        ];
+        assert_eq!(expect, tokens);
+
+        // 改行後のエスケープ位置
+        let err = Scanner::new(&"\"a\n\\q\"".to_string()).scan().unwrap_err();
+        assert!(matches!(err, ScanError::InvalidEscape(1, 0)));
+    }
+
     #[test]
     fn 数値リテラル_scan() {
-        let tokens = Scanner::new(&"123".to_string()).scan();
+        let tokens = Scanner::new(&"123".to_string()).scan().unwrap();
+        let expect = vec![
+            Token::new(TokenType::Number(123.0), None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 3, 0, 3),
+        ];
+        assert_eq!(expect, tokens);
+
+        let tokens = Scanner::new(&"123.123".to_string()).scan().unwrap();
+        let expect = vec![
+            Token::new(TokenType::Number(123.123), None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 7, 0, 7),
+        ];
+        assert_eq!(expect, tokens);
+
+        let tokens = Scanner::new(&"1 <= 2".to_string()).scan().unwrap();
+        let expect = vec![
+            Token::new(TokenType::Number(1.0), None, 0, 0, 0),
+            Token::new(TokenType::LessEqual, None, 2, 0, 2),
+            Token::new(TokenType::Number(2.0), None, 5, 0, 5),
+            Token::new(TokenType::Eof, None, 6, 0, 6),
+        ];
+        assert_eq!(expect, tokens);
+
+        let tokens = Scanner::new(&"0xFF & 0x0f".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::Number(123.0), None, 0, 0),
-            Token::new(TokenType::Eof, None, 3, 0),
+            Token::new(TokenType::Number(255.0), None, 0, 0, 0),
+            Token::new(TokenType::Ampersand, None, 5, 0, 5),
+            Token::new(TokenType::Number(15.0), None, 7, 0, 7),
+            Token::new(TokenType::Eof, None, 11, 0, 11),
         ];
         assert_eq!(expect, tokens);
+    }
+
+    #[test]
+    fn 数値リテラル_小数点_scan() {
+        let token_types = |source: &str| {
+            Scanner::new(&source.to_string())
+                .scan()
+                .unwrap()
+                .iter()
+                .map(|t| t.token_type().clone())
+                .collect::<Vec<_>>()
+        };
+        // 末尾の小数点、先頭の小数点は数値リテラルに含めない
+        assert_eq!(
+            vec![TokenType::Number(5.0), TokenType::Dot, TokenType::Eof],
+            token_types("5.")
+        );
+        assert_eq!(
+            vec![TokenType::Dot, TokenType::Number(5.0), TokenType::Eof],
+            token_types(".5")
+        );
+
+        // 小数点が複数ある場合はエラー
+        let err = Scanner::new(&"var a = 1;\nprint 1.2.3;".to_string())
+            .scan()
+            .unwrap_err();
+        assert!(matches!(err, ScanError::MalformedNumber(1, 6)));
+        assert_eq!("malformed numeric literal", err.to_string());
+    }
+
+    #[test]
+    fn 数値リテラル_指数_区切り_scan() {
+        let number = |source: &str| match Scanner::new(&source.to_string()).scan() {
+            Ok(tokens) => Ok(tokens[0].token_type().clone()),
+            Err(err) => Err(err),
+        };
+        assert_eq!(TokenType::Number(6.02e23), number("6.02e23").unwrap());
+        assert_eq!(TokenType::Number(1e-9), number("1E-9").unwrap());
+        assert_eq!(TokenType::Number(150.0), number("1.5e+2").unwrap());
+        assert_eq!(TokenType::Number(1000000.0), number("1_000_000").unwrap());
+        assert_eq!(TokenType::Number(1234.5678), number("1_234.567_8").unwrap());
+        assert_eq!(TokenType::Number(1e10), number("1e1_0").unwrap());
+
+        // 指数部の後ろのトークン位置
+        let tokens = Scanner::new(&"2e3 + 1".to_string()).scan().unwrap();
+        assert_eq!(Token::new(TokenType::Plus, None, 4, 0, 4), tokens[1]);
+
+        for source in [
+            "1e", "1e+", "2E-;", "1_", "1__0", "1_.5", "1_e5", "1e_1", "1.5e3.2",
+        ] {
+            assert!(
+                matches!(number(source), Err(ScanError::MalformedNumber(0, 0))),
+                "{}",
+                source
+            );
+        }
+        // 範囲外の指数
+        assert!(matches!(
+            number("1e400"),
+            Err(ScanError::NumberOutOfRange(0, 0))
+        ));
+    }
+
+    #[test]
+    fn 数値リテラル_範囲外_scan() {
+        let source = format!("var a = 1;\nprint {};", "9".repeat(400));
+        let err = Scanner::new(&source).scan().unwrap_err();
+        assert!(matches!(err, ScanError::NumberOutOfRange(1, 6)));
+        assert_eq!("numeric literal out of range", err.to_string());
+
+        let source = format!("0x{};", "F".repeat(17));
+        assert!(matches!(
+            Scanner::new(&source).scan(),
+            Err(ScanError::NumberOutOfRange(0, 0))
+        ));
+
+        // 有限の大きな値はスキャンできる
+        let source = format!("1{};", "0".repeat(300));
+        let tokens = Scanner::new(&source).scan().unwrap();
+        assert_eq!(&TokenType::Number(1e300), tokens[0].token_type());
+    }
+
+    #[test]
+    fn 終端のスラッシュ_scan() {
+        let tokens = Scanner::new(&"/".to_string()).scan().unwrap();
+        let expect = vec![
+            Token::new(TokenType::Slash, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 1, 0, 1),
+        ];
+        assert_eq!(expect, tokens);
+
+        let tokens = Scanner::new(&"a /".to_string()).scan().unwrap();
+        assert_eq!(3, tokens.len());
+        assert_eq!(Token::new(TokenType::Slash, None, 2, 0, 2), tokens[1]);
+        assert_eq!(Token::new(TokenType::Eof, None, 3, 0, 3), tokens[2]);
+
+        // 終端までのコメント
+        let tokens = Scanner::new(&"a //".to_string()).scan().unwrap();
+        assert_eq!(2, tokens.len());
+        assert_eq!(&TokenType::Eof, tokens[1].token_type());
+    }
+
+    #[test]
+    fn ブロックコメント_scan() {
+        let token_types = |source: &str| {
+            Scanner::new(&source.to_string())
+                .scan()
+                .unwrap()
+                .iter()
+                .map(|t| t.token_type().clone())
+                .collect::<Vec<_>>()
+        };
+
+        // 文字列リテラル内の`/*`はコメントにならない
+        assert_eq!(
+            vec![
+                TokenType::String("/* not a comment */".to_string()),
+                TokenType::Eof
+            ],
+            token_types("\"/* not a comment */\"")
+        );
+        // コメント内のダブルクォーテーションは文字列にならない
+        assert_eq!(
+            vec![TokenType::Number(1.0), TokenType::Eof],
+            token_types("/* \"not a string\" */ 1")
+        );
+        assert_eq!(
+            vec![
+                TokenType::Number(1.0),
+                TokenType::Slash,
+                TokenType::Number(2.0),
+                TokenType::Eof
+            ],
+            token_types("1 /* \" */ / 2")
+        );
 
-        let tokens = Scanner::new(&"123.123".to_string()).scan();
+        // コメント後の位置は改行を含めて数える
+        let tokens = Scanner::new(&"/* a\n bc */ x".to_string()).scan().unwrap();
+        assert_eq!((1, 7), (tokens[0].line(), tokens[0].col()));
+
+        // 入れ子のコメントは対応する`*/`まで
+        assert_eq!(
+            vec![TokenType::Number(1.0), TokenType::Eof],
+            token_types("/* a /* b */ c */ 1")
+        );
+        let tokens = Scanner::new(&"/* /*\n*/ */ x".to_string()).scan().unwrap();
+        assert_eq!((1, 6), (tokens[0].line(), tokens[0].col()));
+        let err = Scanner::new(&"/* a /* b */ c".to_string())
+            .scan()
+            .unwrap_err();
+        assert!(matches!(err, ScanError::UnterminatedComment(0, 0)));
+
+        let err = Scanner::new(&"1;\n /* \"".to_string()).scan().unwrap_err();
+        assert!(matches!(err, ScanError::UnterminatedComment(1, 1)));
+        assert_eq!("unterminated block comment", err.to_string());
+    }
+
+    #[test]
+    fn unicodeエスケープ_scan() {
+        let string = |source: &str| match Scanner::new(&source.to_string()).scan() {
+            Ok(tokens) => Ok(tokens[0].token_type().clone()),
+            Err(err) => Err(err),
+        };
+        assert_eq!(
+            TokenType::String("A".to_string()),
+            string("\"\\u{41}\"").unwrap()
+        );
+        assert_eq!(
+            TokenType::String("a😀b".to_string()),
+            string("\"a\\u{1F600}b\"").unwrap()
+        );
+
+        let err = string("print \"ab\\u{110000}\";").unwrap_err();
+        assert!(matches!(err, ScanError::InvalidEscape(0, 9)));
+        assert_eq!("invalid escape sequence", err.to_string());
+        for source in [
+            "\"\\u{D800}\"",
+            "\"\\u{}\"",
+            "\"\\u41\"",
+            "\"\\u{1234567}\"",
+            "\"\\u{41",
+        ] {
+            assert!(matches!(
+                string(source),
+                Err(ScanError::InvalidEscape(_, _))
+            ));
+        }
+    }
+
+    #[test]
+    fn hexエスケープ_scan() {
+        let string = |source: &str| match Scanner::new(&source.to_string()).scan() {
+            Ok(tokens) => Ok(tokens[0].token_type().clone()),
+            Err(err) => Err(err),
+        };
+        assert_eq!(
+            TokenType::String("A".to_string()),
+            string("\"\\x41\"").unwrap()
+        );
+        assert_eq!(
+            TokenType::String("a\tb~".to_string()),
+            string("\"a\\x09b\\x7e\"").unwrap()
+        );
+
+        let err = string("print \"ab\\x80\";").unwrap_err();
+        assert!(matches!(err, ScanError::InvalidEscape(0, 9)));
+        for source in ["\"\\x4\"", "\"\\xg1\"", "\"\\x+1\"", "\"\\x4"] {
+            assert!(matches!(
+                string(source),
+                Err(ScanError::InvalidEscape(_, _))
+            ));
+        }
+    }
+
+    #[test]
+    fn 未対応の文字_scan() {
+        let err = Scanner::new(&"var a = 1;\nprint a @ 2;".to_string())
+            .scan()
+            .unwrap_err();
+        assert!(matches!(err, ScanError::UnexpectedCharacter('@', 1, 8)));
+        assert_eq!("unexpected character '@'", err.to_string());
+        assert_eq!((1, 8), (err.line(), err.col()));
+
+        assert!(matches!(
+            Scanner::new(&"#".to_string()).scan(),
+            Err(ScanError::UnexpectedCharacter('#', 0, 0))
+        ));
+    }
+
+    #[test]
+    fn エスケープ_scan() {
+        let tokens = Scanner::new(&"\"a\\n\\t\\r\\\\\\\"\\0b\" 1".to_string())
+            .scan()
+            .unwrap();
+        assert_eq!(
+            TokenType::String("a\n\t\r\\\"\0b".to_string()),
+            *tokens[0].token_type()
+        );
+        // エスケープで文字数が減っても、後続のトークン位置はソース上の位置となる
+        assert_eq!(
+            Token::new(TokenType::Number(1.0), None, 17, 0, 17),
+            tokens[1]
+        );
+
+        let err = Scanner::new(&"\"a\\qb\"".to_string()).scan().unwrap_err();
+        assert!(matches!(err, ScanError::InvalidEscape(0, 2)));
+        assert_eq!("invalid escape sequence", err.to_string());
+    }
+
+    #[test]
+    fn 行継続_scan() {
+        let token_types = |source: &str| {
+            Scanner::new(&source.to_string())
+                .scan()
+                .unwrap()
+                .iter()
+                .map(|t| t.token_type().clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(token_types("1 + 2"), token_types("1 + \\\n 2"));
+        assert_eq!(token_types("1 + 2"), token_types("1 + \\\r\n 2"));
+
+        // 継続後の行数はカウントされる
+        let tokens = Scanner::new(&"1 + \\\n 2".to_string()).scan().unwrap();
+        assert_eq!(1, tokens[2].line());
+
+        let err = Scanner::new(&"1 + \\".to_string()).scan().unwrap_err();
+        assert!(matches!(err, ScanError::TrailingBackslash(0, 4)));
+        assert_eq!("unexpected end of file after '\\'", err.to_string());
+    }
+
+    #[test]
+    fn タブ_カラム_scan() {
+        let tokens = Scanner::new(&"\ta".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::Number(123.123), None, 0, 0),
-            Token::new(TokenType::Eof, None, 7, 0),
+            Token::new(TokenType::Identifier("a".to_string()), None, 1, 0, 8),
+            Token::new(TokenType::Eof, None, 2, 0, 9),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"1 <= 2".to_string()).scan();
+        let tokens = Scanner::with_tab_width(&"\t\t1 +\n\t2".to_string(), 4)
+            .scan()
+            .unwrap();
         let expect = vec![
-            Token::new(TokenType::Number(1.0), None, 0, 0),
-            Token::new(TokenType::LessEqual, None, 2, 0),
-            Token::new(TokenType::Number(2.0), None, 5, 0),
-            Token::new(TokenType::Eof, None, 6, 0),
+            Token::new(TokenType::Number(1.0), None, 2, 0, 8),
+            Token::new(TokenType::Plus, None, 4, 0, 10),
+            Token::new(TokenType::Number(2.0), None, 7, 1, 4),
+            Token::new(TokenType::Eof, None, 8, 1, 5),
         ];
         assert_eq!(expect, tokens);
+
+        // 文字列リテラル内のタブもタブ幅分カラムを進める
+        let tokens = Scanner::with_tab_width(&"\"\t\" 1".to_string(), 4)
+            .scan()
+            .unwrap();
+        assert_eq!(Token::new(TokenType::Number(1.0), None, 4, 0, 7), tokens[1]);
+
+        let err = Scanner::new(&"\"\t\\q\"".to_string()).scan().unwrap_err();
+        assert!(matches!(err, ScanError::InvalidEscape(0, 9)));
     }
 
     #[test]
     fn 識別子リテラル_scan() {
-        let tokens = Scanner::new(&"a".to_string()).scan();
+        let tokens = Scanner::new(&"a".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::Identifier("a".to_string()), None, 0, 0),
-            Token::new(TokenType::Eof, None, 1, 0),
+            Token::new(TokenType::Identifier("a".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 1, 0, 1),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"a_b".to_string()).scan();
+        let tokens = Scanner::new(&"a_b".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::Identifier("a_b".to_string()), None, 0, 0),
-            Token::new(TokenType::Eof, None, 3, 0),
+            Token::new(TokenType::Identifier("a_b".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 3, 0, 3),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"_a".to_string()).scan();
+        let tokens = Scanner::new(&"_a".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::Identifier("_a".to_string()), None, 0, 0),
-            Token::new(TokenType::Eof, None, 2, 0),
+            Token::new(TokenType::Identifier("_a".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 2, 0, 2),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"or_123".to_string()).scan();
+        let tokens = Scanner::new(&"or_123".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::Identifier("or_123".to_string()), None, 0, 0),
-            Token::new(TokenType::Eof, None, 6, 0),
+            Token::new(TokenType::Identifier("or_123".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 6, 0, 6),
         ];
         assert_eq!(expect, tokens);
     }
 
     #[test]
     fn 予約語_scan() {
-        let tokens = Scanner::new(&"and".to_string()).scan();
+        let tokens = Scanner::new(&"and".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::And, None, 0, 0),
-            Token::new(TokenType::Eof, None, 3, 0),
+            Token::new(TokenType::And, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 3, 0, 3),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"and123".to_string()).scan();
+        let tokens = Scanner::new(&"and123".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::Identifier("and123".to_string()), None, 0, 0),
-            Token::new(TokenType::Eof, None, 6, 0),
+            Token::new(TokenType::Identifier("and123".to_string()), None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 6, 0, 6),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"class".to_string()).scan();
+        let tokens = Scanner::new(&"class".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::Class, None, 0, 0),
-            Token::new(TokenType::Eof, None, 5, 0),
+            Token::new(TokenType::Class, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 5, 0, 5),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"else".to_string()).scan();
+        let tokens = Scanner::new(&"else".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::Else, None, 0, 0),
-            Token::new(TokenType::Eof, None, 4, 0),
+            Token::new(TokenType::Else, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 4, 0, 4),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"false".to_string()).scan();
+        let tokens = Scanner::new(&"false".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::False, None, 0, 0),
-            Token::new(TokenType::Eof, None, 5, 0),
+            Token::new(TokenType::False, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 5, 0, 5),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"for".to_string()).scan();
+        let tokens = Scanner::new(&"for".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::For, None, 0, 0),
-            Token::new(TokenType::Eof, None, 3, 0),
+            Token::new(TokenType::For, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 3, 0, 3),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"fun".to_string()).scan();
+        let tokens = Scanner::new(&"fun".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::Fun, None, 0, 0),
-            Token::new(TokenType::Eof, None, 3, 0),
+            Token::new(TokenType::Fun, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 3, 0, 3),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"if".to_string()).scan();
+        let tokens = Scanner::new(&"if".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::If, None, 0, 0),
-            Token::new(TokenType::Eof, None, 2, 0),
+            Token::new(TokenType::If, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 2, 0, 2),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"nil".to_string()).scan();
+        let tokens = Scanner::new(&"nil".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::Nil, None, 0, 0),
-            Token::new(TokenType::Eof, None, 3, 0),
+            Token::new(TokenType::Nil, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 3, 0, 3),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"or".to_string()).scan();
+        let tokens = Scanner::new(&"or".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::Or, None, 0, 0),
-            Token::new(TokenType::Eof, None, 2, 0),
+            Token::new(TokenType::Or, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 2, 0, 2),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"print".to_string()).scan();
+        let tokens = Scanner::new(&"print".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::Print, None, 0, 0),
-            Token::new(TokenType::Eof, None, 5, 0),
+            Token::new(TokenType::Print, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 5, 0, 5),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"super".to_string()).scan();
+        let tokens = Scanner::new(&"super".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::Super, None, 0, 0),
-            Token::new(TokenType::Eof, None, 5, 0),
+            Token::new(TokenType::Super, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 5, 0, 5),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"this".to_string()).scan();
+        let tokens = Scanner::new(&"this".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::This, None, 0, 0),
-            Token::new(TokenType::Eof, None, 4, 0),
+            Token::new(TokenType::This, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 4, 0, 4),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"true".to_string()).scan();
+        let tokens = Scanner::new(&"true".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::True, None, 0, 0),
-            Token::new(TokenType::Eof, None, 4, 0),
+            Token::new(TokenType::True, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 4, 0, 4),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"var".to_string()).scan();
+        let tokens = Scanner::new(&"var".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::Var, None, 0, 0),
-            Token::new(TokenType::Eof, None, 3, 0),
+            Token::new(TokenType::Var, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 3, 0, 3),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"var a = 1;".to_string()).scan();
+        let tokens = Scanner::new(&"var a = 1;".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::Var, None, 0, 0),
-            Token::new(TokenType::Identifier("a".to_string()), None, 4, 0),
-            Token::new(TokenType::Equal, None, 6, 0),
-            Token::new(TokenType::Number(1.0), None, 8, 0),
-            Token::new(TokenType::SemiColon, None, 9, 0),
-            Token::new(TokenType::Eof, None, 10, 0),
+            Token::new(TokenType::Var, None, 0, 0, 0),
+            Token::new(TokenType::Identifier("a".to_string()), None, 4, 0, 4),
+            Token::new(TokenType::Equal, None, 6, 0, 6),
+            Token::new(TokenType::Number(1.0), None, 8, 0, 8),
+            Token::new(TokenType::SemiColon, None, 9, 0, 9),
+            Token::new(TokenType::Eof, None, 10, 0, 10),
         ];
         assert_eq!(expect, tokens);
 
-        let tokens = Scanner::new(&"while".to_string()).scan();
+        let tokens = Scanner::new(&"while".to_string()).scan().unwrap();
         let expect = vec![
-            Token::new(TokenType::While, None, 0, 0),
-            Token::new(TokenType::Eof, None, 5, 0),
+            Token::new(TokenType::While, None, 0, 0, 0),
+            Token::new(TokenType::Eof, None, 5, 0, 5),
         ];
         assert_eq!(expect, tokens);
     }
@@ -7,13 +7,22 @@ pub enum TokenType {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
     Comma,
+    Colon,
     Dot,
+    QuestionDot,
     Minus,
+    MinusEqual,
     Plus,
+    PlusEqual,
     SemiColon,
     Slash,
+    SlashEqual,
     Star,
+    StarEqual,
+    StarStar,
     Bang,
     BangEqual,
     Equal,
@@ -22,48 +31,116 @@ pub enum TokenType {
     GreaterEqual,
     Less,
     LessEqual,
+    LessLess,
+    GreaterGreater,
+    Ampersand,
+    Pipe,
+    Caret,
+    Tilde,
     Identifier(String),
     String(String),
     Number(f64),
     And,
+    Break,
+    Catch,
     Class,
+    Continue,
     Else,
     False,
     Fun,
     For,
+    Global,
     If,
+    In,
     Nil,
     Or,
+    Pass,
     Print,
+    Repeat,
     Return,
     Super,
     This,
+    Throw,
     True,
+    Try,
     Var,
     While,
     Eof,
 }
 
+impl TokenType {
+    /// 予約語の文字列を取得
+    ///
+    /// # Return
+    /// * Option<&str> - 予約語の場合は文字列、それ以外はNone
+    pub fn keyword(&self) -> Option<&'static str> {
+        match self {
+            Self::And => Some("and"),
+            Self::Break => Some("break"),
+            Self::Catch => Some("catch"),
+            Self::Class => Some("class"),
+            Self::Continue => Some("continue"),
+            Self::Else => Some("else"),
+            Self::False => Some("false"),
+            Self::Fun => Some("fun"),
+            Self::For => Some("for"),
+            Self::Global => Some("global"),
+            Self::If => Some("if"),
+            Self::In => Some("in"),
+            Self::Nil => Some("nil"),
+            Self::Or => Some("or"),
+            Self::Pass => Some("pass"),
+            Self::Print => Some("print"),
+            Self::Repeat => Some("repeat"),
+            Self::Return => Some("return"),
+            Self::Super => Some("super"),
+            Self::This => Some("this"),
+            Self::Throw => Some("throw"),
+            Self::True => Some("true"),
+            Self::Try => Some("try"),
+            Self::Var => Some("var"),
+            Self::While => Some("while"),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Token {
     token: TokenType,
     lexeme: Option<String>,
     num: usize,
     line: usize,
+    col: usize,
 }
 impl Token {
-    pub fn new(token: TokenType, lexeme: Option<String>, num: usize, line: usize) -> Self {
+    pub fn new(
+        token: TokenType,
+        lexeme: Option<String>,
+        num: usize,
+        line: usize,
+        col: usize,
+    ) -> Self {
         Token {
             token,
             lexeme,
             num,
             line,
+            col,
         }
     }
 
     pub fn token_type(&self) -> &TokenType {
         &self.token
     }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn col(&self) -> usize {
+        self.col
+    }
 }
 impl Display for Token {
     fn fmt(&self, f: &mut Formatter) -> Result {
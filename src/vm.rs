@@ -0,0 +1,200 @@
+pub mod chunk;
+pub mod compiler;
+pub mod machine;
+
+use crate::ast::AstType;
+use crate::environment::Environment;
+use crate::eval::{self, EvalResult, ReturnType};
+
+/// 各文をコンパイルし、逆アセンブル結果を返す
+///
+/// 関数の定数は、関数本体のコンパイル結果も出力する。コンパイルに対応していない文はその旨を出力する
+///
+/// # Arguments
+/// * `asts` - 文のリスト
+///
+/// # Return
+/// * String - 逆アセンブル結果
+pub fn disassemble(asts: &[AstType]) -> String {
+    let mut out = String::new();
+    for (i, ast) in asts.iter().enumerate() {
+        let name = format!("statement {}", i);
+        match compiler::compile(ast) {
+            Ok(chunk) => out += &disassemble_chunk(&chunk, &name),
+            Err(err) => out += &format!("== {} ==\n{}\n", name, err),
+        }
+    }
+
+    out
+}
+
+/// チャンクと、定数に含まれる関数本体を逆アセンブル
+fn disassemble_chunk(chunk: &chunk::Chunk, name: &str) -> String {
+    let mut out = chunk.disassemble(name);
+    for (i, constant) in chunk.constants.iter().enumerate() {
+        if let ReturnType::UserFunc(_, body) = constant {
+            let name = format!("{} constant {}", name, i);
+            match compiler::compile_function(body) {
+                Ok(chunk) => out += &disassemble_chunk(&chunk, &name),
+                Err(err) => out += &format!("== {} ==\n{}\n", name, err),
+            }
+        }
+    }
+
+    out
+}
+
+/// バイトコードVMで評価
+///
+/// コンパイルに対応していない構文はツリーウォーカーで評価する
+///
+/// # Arguments
+/// * `ast` - AST
+/// * `env` - 環境
+///
+/// # Return
+/// * EvalResult - 評価後の値
+pub fn run(ast: &AstType, env: &mut Environment) -> EvalResult {
+    match compiler::compile(ast) {
+        Ok(chunk) => machine::Vm::new(&chunk).run(env),
+        Err(_) => eval::eval(ast, env),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::Parser;
+    use crate::embedded::func;
+    use crate::environment::Value;
+    use crate::scanner::Scanner;
+
+    /// VMとツリーウォーカーそれぞれで評価し、各文の評価結果を返す
+    fn run_both(source: &str) -> (Vec<ReturnType>, Vec<ReturnType>, Environment, Environment) {
+        let source = source.to_string();
+        let tokens = Scanner::new(&source).scan().unwrap();
+        let asts = Parser::new(&tokens).program();
+
+        let mut vm_env = func::register_func(&Environment::new());
+        let vm_ret = asts.iter().map(|a| run(a, &mut vm_env).unwrap()).collect();
+        let mut tree_env = func::register_func(&Environment::new());
+        let tree_ret = asts
+            .iter()
+            .map(|a| eval::eval(a, &mut tree_env).unwrap())
+            .collect();
+
+        (vm_ret, tree_ret, vm_env, tree_env)
+    }
+
+    #[test]
+    fn 四則演算_vm() {
+        let (vm_ret, tree_ret, _, _) =
+            run_both("1 + 2 * 3 - 4 / 2; -(1 + 2); \"a\" + \"b\"; 1 < 2 == true;");
+        assert_eq!(tree_ret, vm_ret);
+        assert_eq!(ReturnType::F64(5.0), vm_ret[0]);
+    }
+
+    #[test]
+    fn ループ_vm() {
+        let source = "var sum = 0; for (var i = 0; i < 10; i = i + 1) { sum = sum + i; } sum;";
+        assert!(compiler::compile(
+            &Parser::new(&Scanner::new(&source.to_string()).scan().unwrap()).program()[1]
+        )
+        .is_ok());
+
+        let (vm_ret, tree_ret, vm_env, tree_env) = run_both(source);
+        assert_eq!(tree_ret, vm_ret);
+        assert_eq!(ReturnType::F64(45.0), vm_ret[2]);
+        assert_eq!(
+            tree_env.get(&"sum".to_string()),
+            vm_env.get(&"sum".to_string())
+        );
+        assert_eq!(None, vm_env.get(&"i".to_string()));
+    }
+
+    #[test]
+    fn while_else_vm() {
+        let source =
+            "var a = 0; while (a > 0) {} else { a = 1; } while (a < 3) a = a + 1; else a = 10; a;";
+        let (vm_ret, tree_ret, vm_env, _) = run_both(source);
+        assert_eq!(tree_ret, vm_ret);
+        assert_eq!(Some(&Value::F64(3.0)), vm_env.get(&"a".to_string()));
+    }
+
+    #[test]
+    fn 関数呼び出し_vm() {
+        let source = "fun fib(n) { if (n < 2) return n; return fib(n - 1) + fib(n - 2); } var a = fib(10); fun noop() {} noop();";
+        let (vm_ret, tree_ret, vm_env, tree_env) = run_both(source);
+        assert_eq!(tree_ret, vm_ret);
+        assert_eq!(Some(&Value::F64(55.0)), vm_env.get(&"a".to_string()));
+        assert_eq!(tree_env.get(&"a".to_string()), vm_env.get(&"a".to_string()));
+    }
+
+    #[test]
+    fn 関数のコンパイル結果の再利用_vm() {
+        let source = "fun twice(n) { return n * 2; } twice(1); twice(2);";
+        let (vm_ret, _, vm_env, _) = run_both(source);
+        assert_eq!(ReturnType::F64(4.0), vm_ret[2]);
+
+        // 呼び出し済みの関数は再コンパイルしない
+        let Some(Value::UserFunc(_, body)) = vm_env.get(&"twice".to_string()) else {
+            panic!("twice is not defined");
+        };
+        assert!(body.chunk(|_| panic!("compiled twice")).is_some());
+    }
+
+    #[test]
+    fn 末尾呼び出し_vm() {
+        // 末尾呼び出しを含む関数はツリーウォーカーで評価し、スタックを消費しない
+        let source =
+            "fun c(n) { if (n == 0) return \"done\"; return c(n - 1); } c(100000);".to_string();
+        let tokens = Scanner::new(&source).scan().unwrap();
+        let mut env = func::register_func(&Environment::new());
+        let ret = Parser::new(&tokens)
+            .program()
+            .iter()
+            .map(|a| run(a, &mut env).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(ReturnType::String("done".to_string()), ret[1]);
+    }
+
+    #[test]
+    fn 短絡評価_vm() {
+        let source = "var count = 0; fun inc() { count = count + 1; return true; } false and inc(); true or inc(); inc() and false or inc(); count;";
+        let (vm_ret, tree_ret, _, _) = run_both(source);
+        assert_eq!(tree_ret, vm_ret);
+        assert_eq!(ReturnType::F64(2.0), vm_ret[5]);
+    }
+
+    #[test]
+    fn 逆アセンブル_vm() {
+        let source = "print 1 + 2; fun f(a) { return a; } break;".to_string();
+        let asts = Parser::new(&Scanner::new(&source).scan().unwrap()).program();
+        let out = disassemble(&asts);
+
+        assert!(out.contains("== statement 0 ==\n"));
+        assert!(out.contains("0000 Constant            0 1\n"));
+        assert!(out.contains("0001 Constant            1 2\n"));
+        assert!(out.contains("0002 Add\n"));
+        assert!(out.contains("0003 Print\n"));
+        // 関数本体
+        assert!(out.contains("== statement 1 constant 0 ==\n"));
+        assert!(out.contains("GetVar              0 \"a\"\n"));
+        // コンパイル対象外の文
+        assert!(out.contains("== statement 2 ==\nNot support ast:"));
+    }
+
+    #[test]
+    fn 未対応構文_vm() {
+        // break文はコンパイル対象外のため、ツリーウォーカーで評価される
+        let source = "var i = 0; while (true) { i = i + 1; if (i == 3) break; } i;";
+        assert!(compiler::compile(
+            &Parser::new(&Scanner::new(&source.to_string()).scan().unwrap()).program()[1]
+        )
+        .is_err());
+
+        let (vm_ret, tree_ret, _, _) = run_both(source);
+        assert_eq!(tree_ret, vm_ret);
+        assert_eq!(ReturnType::F64(3.0), vm_ret[2]);
+    }
+}
@@ -0,0 +1,229 @@
+use crate::eval::{self, Operand};
+
+// 命令
+#[derive(Debug, Clone, PartialEq)]
+pub enum OpCode {
+    Constant(usize), // 定数テーブルのインデックス
+    Nil,
+    True,
+    False,
+    Pop,
+
+    // 変数
+    DefineVar(usize),  // 変数名の定数インデックス
+    DeclareVar(usize), // 変数名の定数インデックス(初期化式なし)
+    GetVar(usize),     // 変数名の定数インデックス
+    SetVar(usize),     // 変数名の定数インデックス
+    EnterScope,
+    ExitScope,
+
+    // 演算
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+    Negate,
+    Not,
+    BitNot,
+    Equal,
+    NotEqual,
+    Greater,
+    GreaterEqual,
+    Less,
+    LessEqual,
+    And,
+    Or,
+    BitOr,
+    BitXor,
+    BitAnd,
+    ShiftLeft,
+    ShiftRight,
+    List(usize), // 要素数
+
+    // 制御
+    Print,
+    Jump(usize),            // ジャンプ先
+    CountLoop,              // スタック先頭の繰り返し回数を加算し、上限をチェック
+    JumpIfFalse(usize),     // ジャンプ先
+    JumpIfTrueKeep(usize),  // ジャンプ先。trueの場合はスタック先頭を残してジャンプ(or)
+    JumpIfFalseKeep(usize), // ジャンプ先。falseの場合はスタック先頭を残してジャンプ(and)
+    Call(usize, usize),     // 関数名の定数インデックス、引数の数
+    Return,
+}
+impl OpCode {
+    /// 命令名
+    ///
+    /// # Return
+    /// * &'static str - 命令名(オペランドは含まない)
+    pub fn name(&self) -> &'static str {
+        match self {
+            OpCode::Constant(_) => "Constant",
+            OpCode::Nil => "Nil",
+            OpCode::True => "True",
+            OpCode::False => "False",
+            OpCode::Pop => "Pop",
+            OpCode::DefineVar(_) => "DefineVar",
+            OpCode::DeclareVar(_) => "DeclareVar",
+            OpCode::GetVar(_) => "GetVar",
+            OpCode::SetVar(_) => "SetVar",
+            OpCode::EnterScope => "EnterScope",
+            OpCode::ExitScope => "ExitScope",
+            OpCode::Add => "Add",
+            OpCode::Sub => "Sub",
+            OpCode::Mul => "Mul",
+            OpCode::Div => "Div",
+            OpCode::Pow => "Pow",
+            OpCode::Negate => "Negate",
+            OpCode::Not => "Not",
+            OpCode::BitNot => "BitNot",
+            OpCode::Equal => "Equal",
+            OpCode::NotEqual => "NotEqual",
+            OpCode::Greater => "Greater",
+            OpCode::GreaterEqual => "GreaterEqual",
+            OpCode::Less => "Less",
+            OpCode::LessEqual => "LessEqual",
+            OpCode::And => "And",
+            OpCode::Or => "Or",
+            OpCode::BitOr => "BitOr",
+            OpCode::BitXor => "BitXor",
+            OpCode::BitAnd => "BitAnd",
+            OpCode::ShiftLeft => "ShiftLeft",
+            OpCode::ShiftRight => "ShiftRight",
+            OpCode::List(_) => "List",
+            OpCode::Print => "Print",
+            OpCode::Jump(_) => "Jump",
+            OpCode::CountLoop => "CountLoop",
+            OpCode::JumpIfFalse(_) => "JumpIfFalse",
+            OpCode::JumpIfTrueKeep(_) => "JumpIfTrueKeep",
+            OpCode::JumpIfFalseKeep(_) => "JumpIfFalseKeep",
+            OpCode::Call(_, _) => "Call",
+            OpCode::Return => "Return",
+        }
+    }
+}
+
+// バイトコード列と定数テーブル
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Chunk {
+    pub code: Vec<OpCode>,
+    pub constants: Vec<Operand>,
+}
+impl Chunk {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 命令追加
+    ///
+    /// # Arguments
+    /// * `op` - 命令
+    ///
+    /// # Return
+    /// * usize - 追加した命令の位置
+    pub fn write(&mut self, op: OpCode) -> usize {
+        self.code.push(op);
+        self.code.len() - 1
+    }
+
+    /// 定数追加
+    ///
+    /// # Arguments
+    /// * `value` - 定数
+    ///
+    /// # Return
+    /// * usize - 定数テーブルのインデックス
+    pub fn add_constant(&mut self, value: Operand) -> usize {
+        if let Some(i) = self.constants.iter().position(|c| *c == value) {
+            return i;
+        }
+        self.constants.push(value);
+        self.constants.len() - 1
+    }
+
+    /// 逆アセンブル
+    ///
+    /// 1命令1行で、位置、命令、オペランド(定数は値も。文字列はダブルクォーテーションで囲む)を出力する
+    ///
+    /// # Arguments
+    /// * `name` - 見出しに表示する名前
+    ///
+    /// # Return
+    /// * String - 逆アセンブル結果
+    pub fn disassemble(&self, name: &str) -> String {
+        let mut out = format!("== {} ==\n", name);
+        for (offset, op) in self.code.iter().enumerate() {
+            out += &format!("{:04} {}\n", offset, self.disassemble_op(op));
+        }
+
+        out
+    }
+
+    /// 1命令の逆アセンブル
+    fn disassemble_op(&self, op: &OpCode) -> String {
+        match op {
+            OpCode::Constant(i) => self.constant_op("Constant", *i),
+            OpCode::DefineVar(i) => self.constant_op("DefineVar", *i),
+            OpCode::DeclareVar(i) => self.constant_op("DeclareVar", *i),
+            OpCode::GetVar(i) => self.constant_op("GetVar", *i),
+            OpCode::SetVar(i) => self.constant_op("SetVar", *i),
+            OpCode::Call(i, n) => format!("{} args={}", self.constant_op("Call", *i), n),
+            OpCode::Jump(target) => format!("{:<16} -> {:04}", "Jump", target),
+            OpCode::JumpIfFalse(target) => format!("{:<16} -> {:04}", "JumpIfFalse", target),
+            OpCode::JumpIfTrueKeep(target) => {
+                format!("{:<16} -> {:04}", "JumpIfTrueKeep", target)
+            }
+            OpCode::JumpIfFalseKeep(target) => {
+                format!("{:<16} -> {:04}", "JumpIfFalseKeep", target)
+            }
+            OpCode::List(n) => format!("{:<16} {}", "List", n),
+            _ => op.name().to_string(),
+        }
+    }
+
+    /// 定数テーブルを参照する命令の逆アセンブル
+    fn constant_op(&self, name: &str, i: usize) -> String {
+        format!(
+            "{:<16} {:>4} {}",
+            name,
+            i,
+            eval::format_element(&self.constants[i])
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::eval::ReturnType;
+
+    #[test]
+    fn 逆アセンブル_chunk() {
+        // 1 + 2
+        let mut chunk = Chunk::new();
+        let one = chunk.add_constant(ReturnType::F64(1.0));
+        let two = chunk.add_constant(ReturnType::F64(2.0));
+        chunk.write(OpCode::Constant(one));
+        chunk.write(OpCode::Constant(two));
+        chunk.write(OpCode::Add);
+        chunk.write(OpCode::JumpIfFalse(5));
+        chunk.write(OpCode::Return);
+
+        let lines = chunk
+            .disassemble("test")
+            .lines()
+            .map(|l| l.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                "== test ==",
+                "0000 Constant            0 1",
+                "0001 Constant            1 2",
+                "0002 Add",
+                "0003 JumpIfFalse      -> 0005",
+                "0004 Return",
+            ],
+            lines
+        );
+    }
+}
@@ -0,0 +1,352 @@
+use crate::ast::AstType;
+use crate::eval::{FuncBody, ReturnType};
+use crate::vm::chunk::{Chunk, OpCode};
+use std::error;
+use std::fmt;
+
+/// コンパイルエラー
+pub enum CompileError {
+    NotSupport(String),
+}
+impl CompileError {
+    fn print(&self) -> String {
+        match self {
+            Self::NotSupport(ast) => format!("Not support ast: {}", ast),
+        }
+    }
+}
+impl fmt::Debug for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.print())
+    }
+}
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.print())
+    }
+}
+impl error::Error for CompileError {
+    fn description(&self) -> &str {
+        "Compile Error"
+    }
+}
+
+type CompileResult = Result<(), CompileError>;
+
+/// 文をバイトコードへコンパイル
+///
+/// # Arguments
+/// * `ast` - AST
+///
+/// # Return
+/// * Result<Chunk, CompileError> - コンパイル結果。実行すると文の評価結果を返す
+pub fn compile(ast: &AstType) -> Result<Chunk, CompileError> {
+    let mut compiler = Compiler::new(false);
+    compiler.node(ast)?;
+    compiler.chunk.write(OpCode::Return);
+
+    Ok(compiler.chunk)
+}
+
+/// 関数本体をバイトコードへコンパイル
+///
+/// # Arguments
+/// * `body` - 関数本体
+///
+/// # Return
+/// * Result<Chunk, CompileError> - コンパイル結果。return文がない場合はnilを返す
+pub fn compile_function(body: &AstType) -> Result<Chunk, CompileError> {
+    let mut compiler = Compiler::new(true);
+    compiler.node(body)?;
+    compiler.chunk.write(OpCode::Pop);
+    compiler.chunk.write(OpCode::Nil);
+    compiler.chunk.write(OpCode::Return);
+
+    Ok(compiler.chunk)
+}
+
+// コンパイラ
+//
+// 各ノードはコンパイル後、評価結果を1つだけスタックに積む
+struct Compiler {
+    chunk: Chunk,
+    in_function: bool,
+}
+impl Compiler {
+    fn new(in_function: bool) -> Self {
+        Compiler {
+            chunk: Chunk::new(),
+            in_function,
+        }
+    }
+
+    /// ノードのコンパイル
+    ///
+    /// # Arguments
+    /// * `ast` - AST
+    ///
+    /// # Return
+    /// * CompileResult - コンパイル結果
+    fn node(&mut self, ast: &AstType) -> CompileResult {
+        match ast {
+            AstType::True => self.emit(OpCode::True),
+            AstType::False => self.emit(OpCode::False),
+            AstType::Nil | AstType::Empty => self.emit(OpCode::Nil),
+            AstType::Number(n) => self.constant(ReturnType::F64(*n)),
+            AstType::String(s) => self.constant(ReturnType::String(s.clone())),
+            AstType::Identifier(i) => {
+                let name = self.name(i);
+                self.emit(OpCode::GetVar(name))
+            }
+            AstType::Grouping(o) => self.node(o),
+            AstType::Bang(o) => self.unary(o, OpCode::Not),
+            AstType::UnaryMinus(o) => self.unary(o, OpCode::Negate),
+            AstType::BitNot(o) => self.unary(o, OpCode::BitNot),
+            AstType::Plus(l, r) => self.binary(l, r, OpCode::Add),
+            AstType::Minus(l, r) => self.binary(l, r, OpCode::Sub),
+            AstType::Mul(l, r) => self.binary(l, r, OpCode::Mul),
+            AstType::Div(l, r) => self.binary(l, r, OpCode::Div),
+            AstType::Pow(l, r) => self.binary(l, r, OpCode::Pow),
+            AstType::EqualEqual(l, r) => self.binary(l, r, OpCode::Equal),
+            AstType::BangEqual(l, r) => self.binary(l, r, OpCode::NotEqual),
+            AstType::Greater(l, r) => self.binary(l, r, OpCode::Greater),
+            AstType::GreaterEqual(l, r) => self.binary(l, r, OpCode::GreaterEqual),
+            AstType::Less(l, r) => self.binary(l, r, OpCode::Less),
+            AstType::LessEqual(l, r) => self.binary(l, r, OpCode::LessEqual),
+            AstType::And(l, r) => self.logical(l, r, OpCode::JumpIfFalseKeep(0), OpCode::And),
+            AstType::Or(l, r) => self.logical(l, r, OpCode::JumpIfTrueKeep(0), OpCode::Or),
+            AstType::BitOr(l, r) => self.binary(l, r, OpCode::BitOr),
+            AstType::BitXor(l, r) => self.binary(l, r, OpCode::BitXor),
+            AstType::BitAnd(l, r) => self.binary(l, r, OpCode::BitAnd),
+            AstType::ShiftLeft(l, r) => self.binary(l, r, OpCode::ShiftLeft),
+            AstType::ShiftRight(l, r) => self.binary(l, r, OpCode::ShiftRight),
+            AstType::List(elements) => {
+                elements.iter().try_for_each(|e| self.node(e))?;
+                self.emit(OpCode::List(elements.len()))
+            }
+            AstType::Print(o) => {
+                self.node(o)?;
+                self.emit(OpCode::Print)?;
+                self.emit(OpCode::Nil)
+            }
+            AstType::Var(i, Some(o)) => {
+                self.node(o)?;
+                let name = self.name(i);
+                self.emit(OpCode::DefineVar(name))?;
+                self.emit(OpCode::Nil)
+            }
+            AstType::Var(i, None) => {
+                let name = self.name(i);
+                self.emit(OpCode::DeclareVar(name))?;
+                self.emit(OpCode::Nil)
+            }
+            AstType::Assign(i, o) => {
+                self.node(o)?;
+                let name = self.name(i);
+                self.emit(OpCode::SetVar(name))
+            }
+            AstType::Fun(i, args, body) => {
+                self.constant(ReturnType::UserFunc(
+                    args.clone(),
+                    FuncBody::new(*body.clone()),
+                ))?;
+                let name = self.name(i);
+                self.emit(OpCode::DefineVar(name))?;
+                self.emit(OpCode::Nil)
+            }
+            AstType::Lambda(args, body) => self.constant(ReturnType::UserFunc(
+                args.clone(),
+                FuncBody::new(*body.clone()),
+            )),
+            AstType::Call(callee, args) => {
+                args.iter().try_for_each(|a| self.node(a))?;
+                let name = self.name(callee);
+                self.emit(OpCode::Call(name, args.len()))
+            }
+            // 末尾呼び出しはスタックを消費しないよう、関数ごとツリーウォーカーで評価する
+            AstType::Return(o) if matches!(**o, AstType::Call(_, _)) => {
+                Err(CompileError::NotSupport(format!("{:?}", ast)))
+            }
+            AstType::Return(o) if self.in_function => {
+                self.node(o)?;
+                self.emit(OpCode::Return)
+            }
+            AstType::Block(stmts) => self.block(stmts),
+            AstType::If(cond, if_stmt, else_stmt) => self.if_stmt(cond, if_stmt, else_stmt),
+            AstType::While(cond, stmt, increment, else_stmt) => {
+                self.while_stmt(cond, stmt, increment, else_stmt)
+            }
+            _ => Err(CompileError::NotSupport(format!("{:?}", ast))),
+        }
+    }
+
+    /// 命令追加
+    fn emit(&mut self, op: OpCode) -> CompileResult {
+        self.chunk.write(op);
+        Ok(())
+    }
+
+    /// 定数を積む命令追加
+    fn constant(&mut self, value: ReturnType) -> CompileResult {
+        let i = self.chunk.add_constant(value);
+        self.emit(OpCode::Constant(i))
+    }
+
+    /// 変数名を定数テーブルへ追加
+    fn name(&mut self, name: &str) -> usize {
+        self.chunk
+            .add_constant(ReturnType::String(name.to_string()))
+    }
+
+    /// 単項演算子のコンパイル
+    fn unary(&mut self, operand: &AstType, op: OpCode) -> CompileResult {
+        self.node(operand)?;
+        self.emit(op)
+    }
+
+    /// 二項演算子のコンパイル
+    fn binary(&mut self, left: &AstType, right: &AstType, op: OpCode) -> CompileResult {
+        self.node(left)?;
+        self.node(right)?;
+        self.emit(op)
+    }
+
+    /// and、orのコンパイル
+    ///
+    /// 左辺で結果が決まる場合は右辺を評価せずにジャンプする。
+    /// 右辺を評価する場合は二項演算子として型をチェックする
+    fn logical(
+        &mut self,
+        left: &AstType,
+        right: &AstType,
+        jump: OpCode,
+        op: OpCode,
+    ) -> CompileResult {
+        self.node(left)?;
+        let jump_end = self.chunk.write(jump);
+        self.node(right)?;
+        self.emit(op)?;
+
+        let end = self.chunk.code.len();
+        self.chunk.code[jump_end] = match self.chunk.code[jump_end] {
+            OpCode::JumpIfTrueKeep(_) => OpCode::JumpIfTrueKeep(end),
+            _ => OpCode::JumpIfFalseKeep(end),
+        };
+
+        Ok(())
+    }
+
+    /// ブロックのコンパイル。最後の文の評価結果をブロックの評価結果とする
+    fn block(&mut self, stmts: &[AstType]) -> CompileResult {
+        self.emit(OpCode::EnterScope)?;
+        if stmts.is_empty() {
+            self.emit(OpCode::Nil)?;
+        }
+        for (i, stmt) in stmts.iter().enumerate() {
+            if i > 0 {
+                self.emit(OpCode::Pop)?;
+            }
+            self.node(stmt)?;
+        }
+        self.emit(OpCode::ExitScope)
+    }
+
+    /// if文のコンパイル
+    fn if_stmt(&mut self, cond: &AstType, if_stmt: &AstType, else_stmt: &AstType) -> CompileResult {
+        self.node(cond)?;
+        self.if_stmt_on_stack(if_stmt, else_stmt)
+    }
+
+    /// スタック先頭の条件によるif文のコンパイル
+    fn if_stmt_on_stack(&mut self, if_stmt: &AstType, else_stmt: &AstType) -> CompileResult {
+        let jump_else = self.chunk.write(OpCode::JumpIfFalse(0));
+        self.node(if_stmt)?;
+        let jump_end = self.chunk.write(OpCode::Jump(0));
+
+        self.chunk.code[jump_else] = OpCode::JumpIfFalse(self.chunk.code.len());
+        self.node(else_stmt)?;
+        self.chunk.code[jump_end] = OpCode::Jump(self.chunk.code.len());
+
+        Ok(())
+    }
+
+    /// while文のコンパイル
+    fn while_stmt(
+        &mut self,
+        cond: &AstType,
+        stmt: &AstType,
+        increment: &AstType,
+        else_stmt: &AstType,
+    ) -> CompileResult {
+        // 繰り返し回数をスタックに積んでおく
+        self.constant(ReturnType::F64(0.0))?;
+        let start = self.chunk.code.len();
+        self.node(cond)?;
+        let jump_exit = self.chunk.write(OpCode::JumpIfFalse(0));
+        self.emit(OpCode::CountLoop)?;
+        self.node(stmt)?;
+        self.emit(OpCode::Pop)?;
+        self.node(increment)?;
+        self.emit(OpCode::Pop)?;
+        self.emit(OpCode::Jump(start))?;
+
+        self.chunk.code[jump_exit] = OpCode::JumpIfFalse(self.chunk.code.len());
+        if *else_stmt == AstType::Nil {
+            self.emit(OpCode::Pop)?;
+            return self.emit(OpCode::Nil);
+        }
+
+        // 繰り返し回数が0の場合のみelseを実行する
+        self.constant(ReturnType::F64(0.0))?;
+        self.emit(OpCode::Equal)?;
+        self.if_stmt_on_stack(else_stmt, &AstType::Nil)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn 四則演算_compile() {
+        // 1 + 2 * 3
+        let ast = AstType::Plus(
+            Box::new(AstType::Number(1.0)),
+            Box::new(AstType::Mul(
+                Box::new(AstType::Number(2.0)),
+                Box::new(AstType::Number(3.0)),
+            )),
+        );
+        let chunk = compile(&ast).unwrap();
+        assert_eq!(
+            vec![
+                OpCode::Constant(0),
+                OpCode::Constant(1),
+                OpCode::Constant(2),
+                OpCode::Mul,
+                OpCode::Add,
+                OpCode::Return,
+            ],
+            chunk.code
+        );
+        assert_eq!(
+            vec![
+                ReturnType::F64(1.0),
+                ReturnType::F64(2.0),
+                ReturnType::F64(3.0)
+            ],
+            chunk.constants
+        );
+    }
+
+    #[test]
+    fn 未対応構文_compile() {
+        assert!(compile(&AstType::Break(None)).is_err());
+        // 関数外のreturn文
+        assert!(compile(&AstType::Return(Box::new(AstType::Nil))).is_err());
+        assert!(compile_function(&AstType::Return(Box::new(AstType::Nil))).is_ok());
+        // 末尾呼び出し
+        let call = AstType::Call("f".to_string(), vec![]);
+        assert!(compile_function(&AstType::Return(Box::new(call))).is_err());
+    }
+}
@@ -0,0 +1,245 @@
+use crate::ast::AstType;
+use crate::config;
+use crate::environment::{Environment, Value};
+use crate::eval::{self, EvalResult, Frozen, Operand, ReturnType, RuntimeError};
+use crate::profile;
+use crate::vm::chunk::{Chunk, OpCode};
+use crate::vm::compiler;
+
+// スタックマシン
+pub struct Vm<'a> {
+    chunk: &'a Chunk,
+    ip: usize,
+    stack: Vec<Operand>,
+    scope_depth: usize,
+}
+impl<'a> Vm<'a> {
+    pub fn new(chunk: &'a Chunk) -> Self {
+        Vm {
+            chunk,
+            ip: 0,
+            stack: vec![],
+            scope_depth: 0,
+        }
+    }
+
+    /// バイトコード実行
+    ///
+    /// # Arguments
+    /// * `env` - 環境
+    ///
+    /// # Return
+    /// * EvalResult - Return命令で返された値
+    pub fn run(&mut self, env: &mut Environment) -> EvalResult {
+        let ret = self.execute(env);
+
+        // return文やエラーでブロックの途中から抜けた場合も、スコープを元に戻す
+        while self.scope_depth > 0 {
+            exit_scope(env);
+            self.scope_depth -= 1;
+        }
+
+        ret
+    }
+
+    /// 命令実行ループ
+    fn execute(&mut self, env: &mut Environment) -> EvalResult {
+        let chunk = self.chunk;
+        loop {
+            let op = &chunk.code[self.ip];
+            self.ip += 1;
+            profile::record_op(op);
+
+            match op {
+                OpCode::Constant(i) => self.stack.push(chunk.constants[*i].clone()),
+                OpCode::Nil => self.stack.push(ReturnType::Void),
+                OpCode::True => self.stack.push(ReturnType::Bool(true)),
+                OpCode::False => self.stack.push(ReturnType::Bool(false)),
+                OpCode::Pop => {
+                    self.pop();
+                }
+                OpCode::DefineVar(i) => {
+                    let value = eval::to_env_value(self.pop())?;
+                    env.define(self.name(*i), value);
+                }
+                OpCode::DeclareVar(i) => {
+                    env.define(self.name(*i), Value::Uninitialized);
+                }
+                OpCode::GetVar(i) => {
+                    let value = eval::identifier(&self.name(*i), env)?;
+                    self.stack.push(value);
+                }
+                OpCode::SetVar(i) => {
+                    let value = self.pop();
+                    let ret = eval::assign(&self.name(*i), value, env)?;
+                    self.stack.push(ret);
+                }
+                OpCode::EnterScope => {
+                    enter_scope(env);
+                    self.scope_depth += 1;
+                }
+                OpCode::ExitScope => {
+                    exit_scope(env);
+                    self.scope_depth -= 1;
+                }
+                OpCode::Add => self.binary(eval::plus)?,
+                OpCode::Sub => self.binary(eval::minus)?,
+                OpCode::Mul => self.binary(eval::mul)?,
+                OpCode::Div => self.binary(eval::div)?,
+                OpCode::Pow => self.binary(eval::pow)?,
+                OpCode::Equal => self.binary(eval::equal_equal)?,
+                OpCode::NotEqual => self.binary(eval::bang_equal)?,
+                OpCode::Greater => self.binary(eval::greater)?,
+                OpCode::GreaterEqual => self.binary(eval::greater_equal)?,
+                OpCode::Less => self.binary(eval::less)?,
+                OpCode::LessEqual => self.binary(eval::less_equal)?,
+                OpCode::And => self.binary(eval::and_eval)?,
+                OpCode::Or => self.binary(eval::or_eval)?,
+                OpCode::BitOr => self.binary(eval::bit_or)?,
+                OpCode::BitXor => self.binary(eval::bit_xor)?,
+                OpCode::BitAnd => self.binary(eval::bit_and)?,
+                OpCode::ShiftLeft => self.binary(eval::shift_left)?,
+                OpCode::ShiftRight => self.binary(eval::shift_right)?,
+                OpCode::Negate => {
+                    let operand = self.pop();
+                    self.stack.push(eval::unary_minus(operand)?);
+                }
+                OpCode::Not => {
+                    let operand = self.pop();
+                    self.stack.push(eval::bang(operand)?);
+                }
+                OpCode::BitNot => {
+                    let operand = self.pop();
+                    self.stack.push(eval::bit_not(operand)?);
+                }
+                OpCode::List(n) => {
+                    let elements = self.stack.split_off(self.stack.len() - n);
+                    self.stack
+                        .push(ReturnType::List(elements, Frozen::default()));
+                }
+                OpCode::Print => {
+                    let operand = self.pop();
+                    eval::print_stmt(operand)?;
+                }
+                OpCode::Jump(target) => self.ip = *target,
+                OpCode::CountLoop => {
+                    let iterations = match self.pop() {
+                        ReturnType::F64(n) => n as usize + 1,
+                        o => panic!("[CountLoop] iterations is not number: {:?}", o),
+                    };
+                    eval::check_loop_limit(iterations, config::get().max_loop_iterations)?;
+                    self.stack.push(ReturnType::F64(iterations as f64));
+                }
+                OpCode::JumpIfTrueKeep(target) => {
+                    if self.stack.last() == Some(&ReturnType::Bool(true)) {
+                        self.ip = *target;
+                    }
+                }
+                OpCode::JumpIfFalseKeep(target) => {
+                    if self.stack.last() == Some(&ReturnType::Bool(false)) {
+                        self.ip = *target;
+                    }
+                }
+                OpCode::JumpIfFalse(target) => match self.pop() {
+                    ReturnType::Bool(false) => self.ip = *target,
+                    ReturnType::Bool(true) => {}
+                    cond => return Err(RuntimeError::OperandType(cond)),
+                },
+                OpCode::Call(i, n) => {
+                    let args = self.stack.split_off(self.stack.len() - n);
+                    let ret = self.call(&self.name(*i), &args, env)?;
+                    self.stack.push(ret);
+                }
+                OpCode::Return => return Ok(self.pop()),
+            }
+        }
+    }
+
+    /// 関数呼び出し
+    ///
+    /// 関数本体は最初の呼び出し時のみコンパイルする。コンパイルできない場合は、ツリーウォーカーで評価する
+    ///
+    /// # Arguments
+    /// * `callee` - 関数名
+    /// * `args_val` - 引数値
+    /// * `env` - 環境
+    ///
+    /// # Return
+    /// * EvalResult - 関数の戻り値
+    fn call(&self, callee: &String, args_val: &[Operand], env: &mut Environment) -> EvalResult {
+        let (args, body) = match env.get(callee) {
+            Some(Value::UserFunc(args, body)) => (args.clone(), body.clone()),
+            Some(Value::EmbeddedFunc(f)) => return f(args_val, env),
+            Some(Value::MemoFunc(m)) => return m.clone().call(args_val, env),
+            _ => {
+                return Err(eval::not_found(
+                    RuntimeError::NotFoundFunc(callee.to_string()),
+                    env,
+                ))
+            }
+        };
+        if args.len() != args_val.len() {
+            return Err(RuntimeError::NotMatchArgsNum);
+        }
+        let Some(chunk) = body.chunk(|body| compiler::compile_function(body).ok()) else {
+            return eval::call_func(&body, &args, args_val, env);
+        };
+
+        // 引数の内容を環境に設定して、関数本体を実行
+        let values = args_val
+            .iter()
+            .map(|v| eval::to_env_value(v.clone()))
+            .collect::<Result<Vec<_>, _>>()?;
+        enter_scope(env);
+        args.iter().zip(values).for_each(|(var_name, value)| {
+            if let AstType::Identifier(key) = var_name {
+                env.define(key.to_string(), value);
+            }
+        });
+        let ret = Vm::new(chunk).run(env);
+        exit_scope(env);
+
+        ret
+    }
+
+    /// 二項演算子の実行
+    fn binary(&mut self, f: fn(Operand, Operand) -> EvalResult) -> Result<(), RuntimeError> {
+        let right = self.pop();
+        let left = self.pop();
+        self.stack.push(f(left, right)?);
+
+        Ok(())
+    }
+
+    /// スタックから値を取り出す
+    fn pop(&mut self) -> Operand {
+        self.stack.pop().expect("stack underflow")
+    }
+
+    /// 定数テーブルから変数名を取得
+    fn name(&self, i: usize) -> String {
+        match &self.chunk.constants[i] {
+            ReturnType::String(s) => s.clone(),
+            c => panic!("[name] constant is not name: {:?}", c),
+        }
+    }
+}
+
+/// スコープに入る
+///
+/// # Arguments
+/// * `env` - 環境。新しいスコープの環境に置き換わる
+fn enter_scope(env: &mut Environment) {
+    let enclosing = std::mem::replace(env, Environment::new());
+    *env = Environment::with_enclosing(enclosing);
+}
+
+/// スコープを抜ける
+///
+/// # Arguments
+/// * `env` - 環境。外側のスコープの環境に置き換わる
+fn exit_scope(env: &mut Environment) {
+    if let Some(enclosing) = env.enclosing.take() {
+        *env = *enclosing;
+    }
+}
@@ -0,0 +1,550 @@
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+/// 一時ファイルへスクリプトを書き込む
+///
+/// # Arguments
+/// * `name` - ファイル名
+/// * `contents` - ファイル内容
+///
+/// # Return
+/// * PathBuf - 書き込んだファイルパス
+fn write_script(name: &str, contents: &[u8]) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("r-lox-{}-{}", std::process::id(), name));
+    fs::write(&path, contents).expect("can not write script");
+
+    path
+}
+
+/// r-loxを実行
+///
+/// # Arguments
+/// * `args` - コマンドライン引数
+///
+/// # Return
+/// * Output - 実行結果
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_r-lox"))
+        .args(args)
+        .output()
+        .expect("can not run r-lox")
+}
+
+/// 標準入力を与えてr-loxを実行
+///
+/// # Arguments
+/// * `args` - コマンドライン引数
+/// * `input` - 標準入力へ書き込む内容
+///
+/// # Return
+/// * Output - 実行結果
+fn run_with_stdin(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_r-lox"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("can not run r-lox");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .expect("can not write stdin");
+
+    child.wait_with_output().expect("can not wait r-lox")
+}
+
+#[test]
+fn 不正なutf8ファイル() {
+    let path = write_script("invalid_utf8.lox", b"print \"caf\xe9\";");
+    let output = run(&[path.to_str().unwrap()]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(!output.status.success());
+    assert!(stderr.contains("file is not valid UTF-8"));
+    assert!(!stderr.contains("panicked"));
+
+    // latin1指定時はバイト列をそのまま文字として読み込む
+    let output = run(&["--encoding", "latin1", path.to_str().unwrap()]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("café"));
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn evalオプション() {
+    let output = run(&["-e", "print 6*7;"]);
+    assert!(output.status.success());
+    assert_eq!("42", String::from_utf8_lossy(&output.stdout).trim());
+
+    let output = run(&["--eval", "print 1 + 2;"]);
+    assert_eq!("3", String::from_utf8_lossy(&output.stdout).trim());
+
+    let output = run(&["-e"]);
+    assert!(!output.status.success());
+
+    // 後ろの引数はすべてスクリプト引数となる
+    let output = run(&["-e", "print args;", "x", "y"]);
+    assert_eq!(
+        "[\"x\", \"y\"]",
+        String::from_utf8_lossy(&output.stdout).trim()
+    );
+}
+
+#[test]
+fn 終了コード指定() {
+    let output = run(&["-e", "print 1; exit(3); print 2;"]);
+    assert_eq!(Some(3), output.status.code());
+    assert_eq!("1", String::from_utf8_lossy(&output.stdout).trim());
+
+    let output = run(&["-e", "exit(0);"]);
+    assert!(output.status.success());
+
+    // 不正な終了コードは実行時エラー
+    let output = run(&["-e", "exit(-1);"]);
+    assert_eq!(Some(1), output.status.code());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("exit code must be"));
+}
+
+#[cfg(feature = "vm")]
+#[test]
+fn vmオプション() {
+    let sources = [
+        // 四則演算
+        "print 1 + 2 * 3 - 4 / 2; print -(3 - 5); print \"a\" + \"b\"; 10 / 4;",
+        // ループ
+        "var sum = 0; for (var i = 0; i < 10; i = i + 1) { sum = sum + i; print sum; }",
+        // 関数呼び出し
+        "fun fib(n) { if (n < 2) return n; return fib(n - 1) + fib(n - 2); } print fib(15);",
+        // 関数の巻き上げ
+        "print twice(4); fun twice(n) { return n * 2; }",
+        // 末尾呼び出し
+        "fun c(n) { if (n == 0) return \"done\"; return c(n - 1); } print c(100000);",
+    ];
+    for source in sources {
+        let tree = run(&["-e", source]);
+        let vm = run(&["--vm", "-e", source]);
+        assert!(vm.status.success());
+        assert!(!vm.stdout.is_empty());
+        assert_eq!(
+            String::from_utf8_lossy(&tree.stdout),
+            String::from_utf8_lossy(&vm.stdout)
+        );
+    }
+}
+
+#[test]
+fn エラー箇所表示() {
+    let output = run(&["-e", "var a = 1;\nprint a +;"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("2 | print a +;\n"));
+    assert!(stderr.contains(&format!("  | {}^\n", " ".repeat(9))));
+
+    let output = run(&["-e", "print 1;\nprint 1 + \"a\";"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("2 | print 1 + \"a\";\n  | ^\n"));
+
+    // 改行を含む文字列リテラルの後も行数がずれない
+    let output = run(&["-e", "var s = \"a\nb\";\nprint 1 +;"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--> 3:10"));
+    assert!(stderr.contains("3 | print 1 +;\n"));
+
+    // 文字列リテラル内のタブも展開後の位置にキャレットを表示
+    let output = run(&["-e", "print \"\t\" +;"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(&format!("  | {}^\n", " ".repeat(18))));
+}
+
+#[test]
+fn ループ上限オプション() {
+    let output = run(&["--max-loop-iterations", "100", "-e", "while (true) {}"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("Loop iteration limit exceeded: 100"));
+    assert!(!stderr.contains("panicked"));
+
+    // 上限以内のループは実行できる
+    let output = run(&[
+        "--max-loop-iterations",
+        "100",
+        "-e",
+        "for (var i = 0; i < 100; i = i + 1) {} print \"done\";",
+    ]);
+    assert!(output.status.success());
+    assert_eq!("done", String::from_utf8_lossy(&output.stdout).trim());
+
+    let output = run(&["--max-loop-iterations", "x", "-e", "1;"]);
+    assert!(!output.status.success());
+}
+
+#[cfg(feature = "vm")]
+#[test]
+fn ループ上限オプション_vm() {
+    let output = run(&[
+        "--vm",
+        "--max-loop-iterations",
+        "3",
+        "-e",
+        "var i = 0; while (true) { i = i + 1; print i; }",
+    ]);
+    assert!(!output.status.success());
+    assert_eq!("1\n2\n3\n", String::from_utf8_lossy(&output.stdout));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Loop iteration limit exceeded: 3"));
+}
+
+#[test]
+fn repl直前の結果() {
+    let output = run_with_stdin(&[], "1 + 1;\n\n_ * 2;\n\n");
+    assert!(output.status.success());
+    assert_eq!("2\n4\n", String::from_utf8_lossy(&output.stdout));
+}
+
+#[test]
+fn repl環境の保持() {
+    // 別々に評価した入力の変数、関数を参照できる
+    let input = "var x = 1;\n\nfun inc() { x = x + 1; }\n\ninc();\n\nprint x;\n\n";
+    let output = run_with_stdin(&[], input);
+    assert!(output.status.success());
+    assert_eq!("2\n", String::from_utf8_lossy(&output.stdout));
+
+    // エラーの後も環境は保持される
+    let output = run_with_stdin(&[], "var y = 3;\n\nprint 1 / 0;\n\nprint y;\n\n");
+    assert_eq!("3\n", String::from_utf8_lossy(&output.stdout));
+}
+
+#[test]
+fn 数値リテラル範囲外() {
+    let source = format!("print {};", "9".repeat(400));
+    let output = run(&["-e", &source]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("numeric literal out of range"));
+    assert!(stderr.contains("--> 1:7"));
+    assert!(!stderr.contains("panicked"));
+}
+
+#[test]
+fn コメントのみのファイル() {
+    let path = write_script("comment_only.lox", b"// a comment\n");
+    let output = run(&[path.to_str().unwrap()]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(output.stderr.is_empty());
+
+    let output = run(&["-e", ""]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(output.stderr.is_empty());
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn lintオプション() {
+    let source = "var x = 1;\nfun f() { if (x = 5) print x; }\nprint x;";
+    let output = run(&["--lint", "-e", source]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    // 警告のみの場合は実行を継続する
+    assert!(output.status.success());
+    assert_eq!("1", String::from_utf8_lossy(&output.stdout).trim());
+    assert!(stderr.contains("warning: assignment used as a condition; did you mean '=='?"));
+    assert!(stderr.contains("  --> 2:15"));
+
+    // 指定しない場合は警告を表示しない
+    let output = run(&["-e", source]);
+    assert!(output.stderr.is_empty());
+
+    // 参照していないローカル変数
+    let output = run(&["--lint", "-e", "{\n  var unused = 1;\n}"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("warning: unused variable 'unused'"));
+    assert!(stderr.contains("  --> 2:3"));
+    let output = run(&["--lint", "-e", "{\n  var used = 1;\n  print used;\n}"]);
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn warnings_as_errorsオプション() {
+    let source = "var x = false;\nif (x = true) print x;\nprint x;";
+    // 指定しない場合は警告があっても実行する
+    let output = run(&["-e", source]);
+    assert!(output.status.success());
+    assert_eq!("true\ntrue\n", String::from_utf8_lossy(&output.stdout));
+
+    let output = run(&["--warnings-as-errors", "-e", source]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(Some(1), output.status.code());
+    // 警告はエラーとして表示し、スクリプトは実行しない
+    assert!(output.stdout.is_empty());
+    assert!(stderr.contains("error: assignment used as a condition; did you mean '=='?"));
+    assert!(stderr.contains("  --> 2:5"));
+
+    // 警告がなければ通常通り実行する
+    let output = run(&["--warnings-as-errors", "-e", "print 1;"]);
+    assert!(output.status.success());
+    assert_eq!("1\n", String::from_utf8_lossy(&output.stdout));
+}
+
+#[test]
+fn debug_envオプション() {
+    let source = "var a = 1;\nfun f(x) { var y = 2; print z; }\nf(a);";
+    let output = run(&["--debug-env", "-e", source]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    // 関数内から参照できる変数名を表示する
+    assert!(stderr.contains("defined variables: E, PI, a, args, f, x, y"));
+    assert!(stderr.contains("Could not found variable: \"z\""));
+
+    // 指定しない場合は表示しない
+    let output = run(&["-e", source]);
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("defined variables"));
+}
+
+#[test]
+fn スクリプト引数() {
+    let path = write_script("args.lox", b"print args; print args[1];");
+    let output = run(&[path.to_str().unwrap(), "a", "--vm", "3"]);
+    assert!(output.status.success());
+    assert_eq!(
+        "[\"a\", \"--vm\", \"3\"]\n--vm\n",
+        String::from_utf8_lossy(&output.stdout)
+    );
+
+    // 引数がない場合は空のリスト
+    let output = run(&[path.to_str().unwrap()]);
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("[]\n"));
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn repl空行で評価() {
+    let input = "fun add(a, b) {\n  return a + b;\n}\n\nprint add(1, 2);\n\n";
+    let output = run_with_stdin(&[], input);
+    assert!(output.status.success());
+    assert_eq!("3\n", String::from_utf8_lossy(&output.stdout));
+
+    // 文字列リテラル内の空行では評価しない
+    let output = run_with_stdin(&[], "var s = \"a\n\nb\";\n\nprint s;\n");
+    assert!(output.stderr.is_empty());
+    assert_eq!("a\n\nb\n", String::from_utf8_lossy(&output.stdout));
+}
+
+#[test]
+#[cfg(feature = "vm")]
+fn バイトコード表示オプション() {
+    let output = run(&["--dump-bytecode", "-e", "var a = 1; print a + 2;"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("== statement 0 ==\n0000 Constant            0 1\n"));
+    assert!(stdout.contains("== statement 1 ==\n0000 GetVar              0 \"a\"\n"));
+    assert!(stdout.contains("0001 Constant            1 2\n0002 Add\n0003 Print\n"));
+    // 実行はしない
+    assert!(!stdout.lines().any(|l| l == "3"));
+
+    let path = write_script("dump.lox", b"var a = 1;");
+    let output = run(&["--dump-bytecode", path.to_str().unwrap()]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("DefineVar"));
+
+    let output = run(&["--dump-bytecode"]);
+    assert_eq!(Some(2), output.status.code());
+
+    // 定数畳み込みは--fold指定時のみ
+    let output = run(&["--dump-bytecode", "-e", "print 1 + 2;"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Add"));
+    let output = run(&["--dump-bytecode", "--fold", "-e", "print 1 + 2;"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("0000 Constant            0 3\n0001 Print\n"));
+}
+
+#[test]
+fn repl読み込みコマンド() {
+    let path = write_script("load.lox", b"fun twice(n) {\n  return n * 2;\n}\n");
+    let input = format!(":load {}\nprint twice(21);\n\n", path.to_str().unwrap());
+    let output = run_with_stdin(&[], &input);
+    assert!(output.status.success());
+    assert_eq!("42", String::from_utf8_lossy(&output.stdout).trim());
+
+    // ファイル内のエラーを表示しても、REPLは継続する
+    let path = write_script("load_error.lox", b"var a = 1;\nprint b;\n");
+    let input = format!(":load {}\nprint a;\n\n:unknown\n", path.to_str().unwrap());
+    let output = run_with_stdin(&[], &input);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Could not found variable"));
+    assert!(stderr.contains("unknown command: :unknown"));
+    assert_eq!("1", String::from_utf8_lossy(&output.stdout).trim());
+
+    let output = run_with_stdin(&[], ":load /nonexistent/file.lox\n");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("can not read file"));
+}
+
+#[test]
+fn ast_jsonオプション() {
+    let output = run(&["--ast-json", "-e", "1 + 2;"]);
+    assert!(output.status.success());
+    assert_eq!(
+        concat!(
+            r#"[{"type":"Plus","line":1,"col":3,"children":["#,
+            r#"{"type":"Number","line":1,"col":1,"value":1,"children":[]},"#,
+            r#"{"type":"Number","line":1,"col":5,"value":2,"children":[]}]}]"#
+        ),
+        String::from_utf8_lossy(&output.stdout).trim()
+    );
+
+    // パースエラーの場合は、エラーを表示して終了コード1
+    let output = run(&["--ast-json", "-e", "1 +;"]);
+    assert_eq!(Some(1), output.status.code());
+    assert_eq!("[]", String::from_utf8_lossy(&output.stdout).trim());
+}
+
+#[test]
+fn repl端末以外はプロンプトなし() {
+    let output = run_with_stdin(&[], "fun f() {\n  return 1;\n}\n\nprint f();\n");
+    assert!(output.status.success());
+    assert_eq!("1\n", String::from_utf8_lossy(&output.stdout));
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn repl入力ごとの評価() {
+    // 評価済みの入力は再評価しない(出力、副作用が重複しない)
+    let input = "var n = 0;\n\nn += 1; print n;\n\nn += 1; print n;\n\nprint n;";
+    let output = run_with_stdin(&[], input);
+    assert!(output.status.success());
+    assert_eq!("1\n2\n2\n", String::from_utf8_lossy(&output.stdout));
+
+    // EOFで終了する
+    let output = run_with_stdin(&[], "");
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn 関数の巻き上げ() {
+    let source = "print twice(4); fun twice(n) { return n * 2; }";
+    let output = run(&["-e", source]);
+    assert!(output.status.success());
+    assert_eq!("8", String::from_utf8_lossy(&output.stdout).trim());
+}
+
+#[test]
+fn profileオプション() {
+    let output = run(&[
+        "--profile",
+        "-e",
+        "var s = 0; for (var i = 0; i < 3; i = i + 1) { s = s + i; } print s;",
+    ]);
+    assert!(output.status.success());
+    assert_eq!("3", String::from_utf8_lossy(&output.stdout).trim());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.starts_with("node"));
+    assert!(stderr
+        .lines()
+        .any(|l| l.split_whitespace().eq(["Less", "4"])));
+
+    // 指定しない場合は表示しない
+    let output = run(&["-e", "print 1;"]);
+    assert!(output.stderr.is_empty());
+
+    // 実行しないモードとは併用できない
+    let output = run(&["--profile", "--ast-json", "-e", "print 1;"]);
+    assert_eq!(Some(2), output.status.code());
+    assert!(output.stdout.is_empty());
+}
+
+#[cfg(feature = "vm")]
+#[test]
+fn profileオプション_vm() {
+    let output = run(&[
+        "--profile",
+        "--vm",
+        "-e",
+        "var s = 0; for (var i = 0; i < 3; i = i + 1) { s = s + i; } print s;",
+    ]);
+    assert!(output.status.success());
+    assert_eq!("3", String::from_utf8_lossy(&output.stdout).trim());
+    // VMでは命令ごとの実行回数を表示
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr
+        .lines()
+        .any(|l| l.split_whitespace().eq(["CountLoop", "3"])));
+
+    let output = run(&["--profile", "--dump-bytecode", "-e", "print 1;"]);
+    assert_eq!(Some(2), output.status.code());
+}
+
+#[test]
+fn strictオプション() {
+    let output = run(&["-e", "var x; print x;"]);
+    assert!(output.status.success());
+    assert_eq!("nil", String::from_utf8_lossy(&output.stdout).trim());
+
+    let output = run(&["--strict", "-e", "var x; print x;"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Read uninitialized variable: \"x\""));
+}
+
+#[test]
+fn repl未対応の文字() {
+    // スキャンエラーの後も入力を続けられる
+    let output = run_with_stdin(&[], "print 1 @ 2;\n\nprint 3;\n\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("error: unexpected character '@'"));
+    assert!(stderr.contains("  --> 1:9"));
+    assert_eq!("3\n", String::from_utf8_lossy(&output.stdout));
+}
+
+#[test]
+fn repl式文の結果表示() {
+    let output = run_with_stdin(
+        &[],
+        "var x = 5;\n\nx;\n\nprint x + 1;\n\nif (true) { x; }\n\n",
+    );
+    assert!(output.status.success());
+    assert_eq!("5\n6\n", String::from_utf8_lossy(&output.stdout));
+
+    // 代入文の値は表示しない
+    let output = run_with_stdin(&[], "var t = 0;\n\nt += 3;\n\nprint (t += 3);\n\n");
+    assert!(output.status.success());
+    assert_eq!("6\n", String::from_utf8_lossy(&output.stdout));
+
+    // マップ、関数の値も表示する
+    let output = run_with_stdin(
+        &[],
+        "var m = {\"a\": 1};\n\nm;\n\nfun f() {}\n\nf;\n\nclock;\n\n",
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        "{\"a\": 1}\n<fn>\n<native fn>\n",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
+
+#[test]
+fn 環境変数の取得() {
+    let getenv = |value: Option<&str>| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_r-lox"));
+        command.args(["-e", "print getenv(\"R_LOX_GETENV_TEST\");"]);
+        match value {
+            Some(v) => command.env("R_LOX_GETENV_TEST", v),
+            None => command.env_remove("R_LOX_GETENV_TEST"),
+        };
+        command.output().expect("can not run r-lox")
+    };
+
+    let output = getenv(Some("value"));
+    assert!(output.status.success());
+    assert_eq!("value\n", String::from_utf8_lossy(&output.stdout));
+
+    // 設定されていない場合はnil
+    let output = getenv(None);
+    assert!(output.status.success());
+    assert_eq!("nil\n", String::from_utf8_lossy(&output.stdout));
+}